@@ -1,3 +1,31 @@
 fn main() {
     built::write_built_file().expect("Failed to acquire build-time information");
+
+    // New versions of kcl-lib can lint differently, so the lint cache is keyed on it.
+    // Snapshots can also record it with `--embed-metadata`.
+    println!(
+        "cargo:rustc-env=KCL_LIB_VERSION={}",
+        kcl_lib_version().unwrap_or_else(|| "unknown".to_string())
+    );
+}
+
+/// The version of kcl-lib we are built with, from `cargo metadata`.
+fn kcl_lib_version() -> Option<String> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let manifest = std::path::Path::new(&std::env::var("CARGO_MANIFEST_DIR").ok()?).join("Cargo.toml");
+    let output = std::process::Command::new(cargo)
+        .args(["metadata", "--format-version", "1", "--offline", "--manifest-path"])
+        .arg(manifest)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let kcl_lib = metadata["packages"]
+        .as_array()?
+        .iter()
+        .find(|package| package["name"] == "kcl-lib")?;
+    kcl_lib["version"].as_str().map(|version| version.to_string())
 }
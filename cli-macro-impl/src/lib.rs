@@ -1050,21 +1050,27 @@ impl Operation {
         let api_call_params = self.get_api_call_params(tag)?;
 
         let mut api_call_params_all: Vec<TokenStream> = Vec::new();
+        // The filters, for the `--summary` footer.
+        let mut filter_names: Vec<String> = Vec::new();
+        let mut filter_idents: Vec<proc_macro2::Ident> = Vec::new();
         for p in self.get_all_param_names()? {
             if p == "limit" || p == "page_token" {
                 continue;
             }
 
+            let n = clean_param_name(&p);
+            let ident = format_ident!("{}", n);
+            filter_names.push(n);
+            filter_idents.push(ident.clone());
+
             if p == "sort_by" {
                 api_call_params_all.push(quote!(self.sort_by.clone()));
                 continue;
             }
 
-            let n = clean_param_name(&p);
-            let ident = format_ident!("{}", n);
-
             api_call_params_all.push(quote!(&self.#ident));
         }
+        let plural_tag_str = plural(&singular_tag_str);
 
         let additional_struct_params = self.get_additional_struct_params(tag)?;
 
@@ -1084,6 +1090,15 @@ impl Operation {
                 #[clap(long)]
                 pub paginate: bool,
 
+                /// Print the filters, limit and pagination the list was made with to stderr,
+                /// after the list.
+                #[clap(long)]
+                pub summary: bool,
+
+                /// Exit with an error if the list is empty, for checking that something exists.
+                #[clap(long)]
+                pub fail_on_empty: bool,
+
                 #format_flag
             }
 
@@ -1114,6 +1129,35 @@ impl Operation {
 
                 let format = ctx.format(&self.format)?;
                 ctx.io.write_output_for_vec(&format, &results)?;
+
+                if self.summary {
+                    // Only print the filters that are set, with the names the API knows
+                    // their values by, like `sort_by=created_at_descending`.
+                    let values: Vec<(&str, serde_json::Value)> =
+                        vec![#((#filter_names, serde_json::to_value(&self.#filter_idents)?)),*];
+                    let filters: Vec<String> = values
+                        .into_iter()
+                        .filter_map(|(name, value)| match value {
+                            serde_json::Value::Null => None,
+                            serde_json::Value::String(value) => Some(format!("{}={}", name, value)),
+                            value => Some(format!("{}={}", name, value)),
+                        })
+                        .collect();
+                    writeln!(
+                        ctx.io.err_out,
+                        "Listed {} {}, filters: {}, limit: {}, paginated: {}",
+                        results.len(),
+                        #plural_tag_str,
+                        if filters.is_empty() { "none".to_string() } else { filters.join(" ") },
+                        if self.paginate { "none".to_string() } else { self.limit.to_string() },
+                        if self.paginate { "yes" } else { "no" }
+                    )?;
+                }
+
+                if self.fail_on_empty && results.is_empty() {
+                    return Err(anyhow::anyhow!("no {} found", #plural_tag_str));
+                }
+
                 Ok(())
             }
         }
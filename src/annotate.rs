@@ -0,0 +1,177 @@
+//! Draw text onto rendered images, so a snapshot can describe itself.
+//!
+//! We only ever need a line or two of short ASCII labels, so this uses a tiny built in
+//! 5x7 bitmap font instead of pulling in a font file and a text shaping library.
+
+use anyhow::Result;
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Draw the lines of text in the bottom left corner of a PNG image, on top of a dark
+/// backdrop so they are readable against any model or background color.
+pub fn annotate_png(contents: &[u8], lines: &[String]) -> Result<Vec<u8>> {
+    let img = image::load_from_memory_with_format(contents, image::ImageFormat::Png)
+        .map_err(|err| anyhow::anyhow!("failed to decode the snapshot as a png: {}", err))?;
+    // Work in RGBA so images with and without an alpha channel are handled the same way.
+    let mut img = img.to_rgba8();
+
+    // Scale the text with the image, so it stays readable on large renders.
+    let scale = (img.width() / 400).max(1);
+    let margin = 4 * scale;
+    let line_height = (GLYPH_HEIGHT + 3) * scale;
+
+    let text_width = lines.iter().map(|l| l.chars().count() as u32).max().unwrap_or(0) * (GLYPH_WIDTH + 1) * scale;
+    let text_height = lines.len() as u32 * line_height;
+    let box_width = text_width + 2 * margin;
+    let box_height = text_height + 2 * margin;
+    if box_width > img.width() || box_height > img.height() {
+        anyhow::bail!(
+            "the snapshot ({}x{}) is too small to annotate",
+            img.width(),
+            img.height()
+        );
+    }
+
+    // Darken the area behind the text.
+    let top = img.height() - box_height;
+    for y in top..img.height() {
+        for x in 0..box_width {
+            let pixel = img.get_pixel_mut(x, y);
+            for channel in pixel.0.iter_mut().take(3) {
+                *channel /= 4;
+            }
+            pixel[3] = 255;
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        let y = top + margin + i as u32 * line_height;
+        for (j, c) in line.chars().enumerate() {
+            let x = margin + j as u32 * (GLYPH_WIDTH + 1) * scale;
+            draw_glyph(&mut img, c, x, y, scale);
+        }
+    }
+
+    let mut out = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgba8(img).write_to(&mut out, image::ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
+
+fn draw_glyph(img: &mut image::RgbaImage, c: char, x: u32, y: u32, scale: u32) {
+    let rows = glyph(c);
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    img.put_pixel(
+                        x + col * scale + dx,
+                        y + row as u32 * scale + dy,
+                        image::Rgba([255, 255, 255, 255]),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The rows of a glyph, top to bottom, with the leftmost pixel in the highest of the 5 bits.
+/// Lowercase letters are drawn as uppercase, and unknown characters as a box.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '=' => [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000],
+        '/' => [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '_' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+        _ => [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn blank_png(width: u32, height: u32, color: image::Rgba<u8>) -> Vec<u8> {
+        let img = image::RgbaImage::from_pixel(width, height, color);
+        let mut out = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(img)
+            .write_to(&mut out, image::ImageFormat::Png)
+            .unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_annotate_png() {
+        // A fully transparent image, to make sure the text ends up opaque.
+        let png = blank_png(200, 100, image::Rgba([128, 128, 128, 0]));
+
+        let result = annotate_png(&png, &["Volume: 1.5 cm3".to_string()]).unwrap();
+        let img = image::load_from_memory(&result).unwrap().to_rgba8();
+        assert_eq!(img.dimensions(), (200, 100));
+
+        // The top right corner is untouched.
+        assert_eq!(*img.get_pixel(199, 0), image::Rgba([128, 128, 128, 0]));
+        // The bottom left corner has the backdrop.
+        assert_eq!(*img.get_pixel(0, 99), image::Rgba([32, 32, 32, 255]));
+        // And some of the text got drawn.
+        assert!(img.pixels().any(|p| *p == image::Rgba([255, 255, 255, 255])));
+    }
+
+    #[test]
+    fn test_annotate_png_too_small() {
+        let png = blank_png(10, 10, image::Rgba([0, 0, 0, 255]));
+        assert!(annotate_png(&png, &["Volume: 1.5 cm3".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_annotate_png_not_png() {
+        assert!(annotate_png(b"not a png", &["hi".to_string()]).is_err());
+    }
+}
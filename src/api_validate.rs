@@ -0,0 +1,321 @@
+//! Checking API responses against the OpenAPI spec the CLI was built with, for
+//! `zoo api --validate`.
+//!
+//! This is meant to find places where the server and the spec have drifted apart, so it
+//! reports every discrepancy it finds instead of stopping at the first, including
+//! properties the spec doesn't know about.
+
+use anyhow::Result;
+use openapiv3::{AdditionalProperties, ReferenceOr, Schema, SchemaKind, StatusCode, Type};
+
+/// Load the OpenAPI spec embedded in the CLI.
+pub fn load_spec() -> Result<openapiv3::OpenAPI> {
+    Ok(serde_json::from_str(include_str!("../spec.json"))?)
+}
+
+/// Find the operation for a request, matching templated path segments like `{id}`
+/// against anything. Returns the path as written in the spec, along with the operation.
+pub fn find_operation<'a>(
+    spec: &'a openapiv3::OpenAPI,
+    method: &http::Method,
+    endpoint: &str,
+) -> Option<(&'a str, &'a openapiv3::Operation)> {
+    let endpoint = endpoint.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = endpoint.trim_end_matches('/').split('/').collect();
+
+    spec.paths.paths.iter().find_map(|(path, item)| {
+        let ReferenceOr::Item(item) = item else {
+            return None;
+        };
+        let template: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        let matches = template.len() == segments.len()
+            && template
+                .iter()
+                .zip(&segments)
+                .all(|(t, s)| t == s || (t.starts_with('{') && t.ends_with('}') && !s.is_empty()));
+        if !matches {
+            return None;
+        }
+
+        let operation = match *method {
+            http::Method::GET => item.get.as_ref(),
+            http::Method::POST => item.post.as_ref(),
+            http::Method::PUT => item.put.as_ref(),
+            http::Method::PATCH => item.patch.as_ref(),
+            http::Method::DELETE => item.delete.as_ref(),
+            http::Method::HEAD => item.head.as_ref(),
+            http::Method::OPTIONS => item.options.as_ref(),
+            http::Method::TRACE => item.trace.as_ref(),
+            _ => None,
+        };
+        operation.map(|operation| (path.as_str(), operation))
+    })
+}
+
+/// Validates JSON values against the schemas in a spec.
+pub struct Validator<'a> {
+    spec: &'a openapiv3::OpenAPI,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(spec: &'a openapiv3::OpenAPI) -> Self {
+        Self { spec }
+    }
+
+    /// The JSON schema the operation declares for a response with this status, if any.
+    pub fn response_schema(&self, operation: &'a openapiv3::Operation, status: u16) -> Option<&'a ReferenceOr<Schema>> {
+        let responses = &operation.responses;
+        let response = responses
+            .responses
+            .get(&StatusCode::Code(status))
+            .or_else(|| responses.responses.get(&StatusCode::Range(status / 100)))
+            .or(responses.default.as_ref())?;
+
+        let response = match response {
+            ReferenceOr::Item(response) => response,
+            ReferenceOr::Reference { reference } => {
+                let name = reference.strip_prefix("#/components/responses/")?;
+                match self.spec.components.as_ref()?.responses.get(name)? {
+                    ReferenceOr::Item(response) => response,
+                    ReferenceOr::Reference { .. } => return None,
+                }
+            }
+        };
+
+        response
+            .content
+            .iter()
+            .find(|(content_type, _)| content_type.starts_with("application/json"))
+            .and_then(|(_, media)| media.schema.as_ref())
+    }
+
+    /// Check a value against a schema, returning the discrepancies, each with the
+    /// path of the value it is about, like `$.items[0].name`.
+    pub fn validate(&self, schema: &ReferenceOr<Schema>, value: &serde_json::Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        self.validate_ref(schema, value, "$", &mut errors);
+        errors
+    }
+
+    fn validate_ref<T: std::borrow::Borrow<Schema>>(
+        &self,
+        schema: &ReferenceOr<T>,
+        value: &serde_json::Value,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        match schema {
+            ReferenceOr::Item(schema) => self.validate_schema(schema.borrow(), value, path, errors),
+            ReferenceOr::Reference { reference } => {
+                let schema = reference.strip_prefix("#/components/schemas/").and_then(|name| {
+                    self.spec
+                        .components
+                        .as_ref()
+                        .and_then(|components| components.schemas.get(name))
+                });
+                match schema {
+                    Some(schema) => self.validate_ref(schema, value, path, errors),
+                    None => errors.push(format!("{}: the spec has no schema `{}`", path, reference)),
+                }
+            }
+        }
+    }
+
+    fn validate_schema(&self, schema: &Schema, value: &serde_json::Value, path: &str, errors: &mut Vec<String>) {
+        if value.is_null() && schema.schema_data.nullable {
+            return;
+        }
+
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => {
+                let Some(s) = value.as_str() else {
+                    errors.push(format!("{}: expected a string, got {}", path, describe(value)));
+                    return;
+                };
+                if !string.enumeration.is_empty() && !string.enumeration.iter().any(|e| e.as_deref() == Some(s)) {
+                    let valid = string.enumeration.iter().flatten().cloned().collect::<Vec<_>>();
+                    errors.push(format!(
+                        "{}: `{}` is not one of the values in the spec: {}",
+                        path,
+                        s,
+                        valid.join(", ")
+                    ));
+                }
+            }
+            SchemaKind::Type(Type::Number { .. }) => {
+                if !value.is_number() {
+                    errors.push(format!("{}: expected a number, got {}", path, describe(value)));
+                }
+            }
+            SchemaKind::Type(Type::Integer { .. }) => {
+                if !value.is_i64() && !value.is_u64() {
+                    errors.push(format!("{}: expected an integer, got {}", path, describe(value)));
+                }
+            }
+            SchemaKind::Type(Type::Boolean { .. }) => {
+                if !value.is_boolean() {
+                    errors.push(format!("{}: expected a boolean, got {}", path, describe(value)));
+                }
+            }
+            SchemaKind::Type(Type::Array(array)) => {
+                let Some(values) = value.as_array() else {
+                    errors.push(format!("{}: expected an array, got {}", path, describe(value)));
+                    return;
+                };
+                if let Some(items) = &array.items {
+                    for (i, value) in values.iter().enumerate() {
+                        self.validate_ref(items, value, &format!("{}[{}]", path, i), errors);
+                    }
+                }
+            }
+            SchemaKind::Type(Type::Object(object)) => {
+                let Some(map) = value.as_object() else {
+                    errors.push(format!("{}: expected an object, got {}", path, describe(value)));
+                    return;
+                };
+                for name in &object.required {
+                    if !map.contains_key(name) {
+                        errors.push(format!("{}: missing required property `{}`", path, name));
+                    }
+                }
+                for (name, value) in map {
+                    let path = format!("{}.{}", path, name);
+                    if let Some(property) = object.properties.get(name) {
+                        self.validate_ref(property, value, &path, errors);
+                        continue;
+                    }
+                    match &object.additional_properties {
+                        Some(AdditionalProperties::Schema(schema)) => {
+                            self.validate_ref(&**schema, value, &path, errors)
+                        }
+                        Some(AdditionalProperties::Any(true)) => {}
+                        // Free-form objects have no properties to be missing from.
+                        None if object.properties.is_empty() => {}
+                        _ => errors.push(format!("{}: property is not in the spec", path)),
+                    }
+                }
+            }
+            SchemaKind::OneOf { one_of: schemas } | SchemaKind::AnyOf { any_of: schemas } => {
+                // Tagged unions often overlap, so matching more than one is fine.
+                let matches = schemas.iter().any(|schema| {
+                    let mut branch_errors = Vec::new();
+                    self.validate_ref(schema, value, path, &mut branch_errors);
+                    branch_errors.is_empty()
+                });
+                if !matches {
+                    errors.push(format!(
+                        "{}: {} does not match any of the {} variants in the spec",
+                        path,
+                        describe(value),
+                        schemas.len()
+                    ));
+                }
+            }
+            SchemaKind::AllOf { all_of } => {
+                for schema in all_of {
+                    self.validate_ref(schema, value, path, errors);
+                }
+            }
+            // Anything else is too loose to say a value doesn't match.
+            _ => {}
+        }
+    }
+}
+
+/// Describe a value for an error message, without printing all of it.
+fn describe(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) if s.chars().count() > 40 => {
+            format!("\"{}...\"", s.chars().take(40).collect::<String>())
+        }
+        serde_json::Value::String(s) => format!("{:?}", s),
+        serde_json::Value::Array(_) => "an array".to_string(),
+        serde_json::Value::Object(_) => "an object".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn test_spec() -> openapiv3::OpenAPI {
+        serde_json::from_value(serde_json::json!({
+            "openapi": "3.0.3",
+            "info": {"title": "test", "version": "1"},
+            "paths": {
+                "/things/{id}": {"get": {"responses": {"200": {
+                    "description": "ok",
+                    "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Thing"}}},
+                }}}},
+            },
+            "components": {"schemas": {
+                "Thing": {
+                    "type": "object",
+                    "required": ["id", "kind"],
+                    "properties": {
+                        "id": {"type": "string"},
+                        "kind": {"type": "string", "enum": ["a", "b"]},
+                        "count": {"type": "integer"},
+                        "parent": {"allOf": [{"$ref": "#/components/schemas/Thing"}], "nullable": true},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                    },
+                },
+            }},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_operation() {
+        let spec = test_spec();
+        let (path, _) = find_operation(&spec, &http::Method::GET, "/things/abc?limit=1").unwrap();
+        assert_eq!(path, "/things/{id}");
+
+        assert!(find_operation(&spec, &http::Method::POST, "/things/abc").is_none());
+        assert!(find_operation(&spec, &http::Method::GET, "/things").is_none());
+        assert!(find_operation(&spec, &http::Method::GET, "/things/abc/more").is_none());
+    }
+
+    #[test]
+    fn test_validate() {
+        let spec = test_spec();
+        let (_, operation) = find_operation(&spec, &http::Method::GET, "/things/abc").unwrap();
+        let validator = Validator::new(&spec);
+        let schema = validator.response_schema(operation, 200).unwrap();
+        assert!(validator.response_schema(operation, 404).is_none());
+
+        let value = serde_json::json!({"id": "x", "kind": "a", "parent": null, "tags": ["t"]});
+        assert_eq!(validator.validate(schema, &value), Vec::<String>::new());
+
+        let value = serde_json::json!({
+            "kind": "c",
+            "count": 1.5,
+            "parent": {"id": "y", "kind": "b", "tags": [1]},
+            "extra": true,
+        });
+        let mut errors = validator.validate(schema, &value);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "$.count: expected an integer, got 1.5",
+                "$.extra: property is not in the spec",
+                "$.kind: `c` is not one of the values in the spec: a, b",
+                "$.parent.tags[0]: expected a string, got 1",
+                "$: missing required property `id`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_spec() {
+        let spec = load_spec().unwrap();
+        let (path, _) = find_operation(&spec, &http::Method::GET, "/user/api-calls/some-id").unwrap();
+        assert_eq!(path, "/user/api-calls/{id}");
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use thiserror::Error;
 
 /*pub struct Example {
     pub description: String,
@@ -15,3 +16,9 @@ pub trait Command {
 /*pub trait CommandExamples {
     fn examples(&self) -> Vec<Example>;
 }*/
+
+/// Returned by a command to exit with a specific exit code, without printing an error.
+/// The command should have already told the user what happened.
+#[derive(Error, Debug)]
+#[error("exit code {0}")]
+pub struct ExitCode(pub i32);
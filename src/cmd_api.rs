@@ -34,12 +34,44 @@ use serde::{Deserialize, Serialize};
 ///
 /// In `--paginate` mode, all pages of results will sequentially be requested until
 /// there are no more pages of results.
+///
+/// The `--input` body can contain `{{key}}` placeholders, which are replaced with the
+/// values given with `--var key=value`. The values are inserted as is, so quote the
+/// placeholder in the body for string values:
+///
+///     $ zoo api --input body.json --var name=foo --var count=3 <endpoint>
+///
+/// In `--raw` mode, the response body is printed byte for byte as it was sent by the
+/// server, which is useful for responses that are not JSON or for hashing a response.
+/// Use `--output` to save the response body to a file.
+///
+/// With `--validate`, the response body is checked against the schema for the endpoint
+/// in the API spec zoo was built with, and any differences are printed as warnings:
+///
+///     $ zoo api --validate /user
+///
+/// To see exactly what is sent, for example for a support ticket, use `--dump-request`,
+/// or `--dry-run` to only print the request without sending it. Commands like
+/// `zoo user view` don't have these flags, so to see their request, make the same call
+/// here:
+///
+///     $ zoo api --dry-run /user
+///
+/// To make many requests at once, see `zoo api batch`. To talk to a websocket
+/// endpoint, see `zoo api ws`.
 #[derive(Parser, Debug, Clone)]
-#[clap(verbatim_doc_comment)]
+#[clap(
+    verbatim_doc_comment,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 pub struct CmdApi {
+    #[clap(subcommand)]
+    subcmd: Option<SubCommand>,
+
     /// The endpoint to request.
     #[clap(name = "endpoint", required = true)]
-    pub endpoint: String,
+    pub endpoint: Option<String>,
 
     /// The HTTP method for the request.
     #[clap(short = 'X', long)]
@@ -61,6 +93,15 @@ pub struct CmdApi {
     #[clap(long, default_value = "", conflicts_with = "paginate")]
     pub input: String,
 
+    /// Replace `{{key}}` placeholders in the input body, in key=value format.
+    #[clap(long = "var")]
+    pub vars: Vec<String>,
+
+    /// Leave placeholders in the input body that have no matching `--var` as they are,
+    /// instead of failing.
+    #[clap(long, requires = "vars")]
+    pub allow_unresolved: bool,
+
     /// Include HTTP response headers in the output.
     #[clap(short, long)]
     pub include: bool,
@@ -68,6 +109,36 @@ pub struct CmdApi {
     /// Add a HTTP request header in `key:value` format.
     #[clap(short = 'H', long)]
     pub header: Vec<String>,
+
+    /// Print the response body exactly as it was sent by the server, instead of
+    /// pretty-printing it as JSON.
+    #[clap(long, conflicts_with = "paginate")]
+    pub raw: bool,
+
+    /// Write the response body to a file instead of standard output.
+    #[clap(short, long)]
+    pub output: Option<std::path::PathBuf>,
+
+    /// Check the response body against the API spec zoo was built with, and print where
+    /// they differ to standard error. This is handy for finding where the server and the
+    /// spec have drifted apart. Endpoints that are not in the spec are not checked.
+    #[clap(long, conflicts_with = "raw")]
+    pub validate: bool,
+
+    /// Print the method, URL, headers and body of each request to standard error before
+    /// sending it. The API token is masked, so the output can be shared.
+    #[clap(long)]
+    pub dump_request: bool,
+
+    /// Print the request like `--dump-request`, but don't send it.
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+enum SubCommand {
+    Batch(CmdApiBatch),
+    Ws(CmdApiWs),
 }
 
 /// The JSON type for a paginated response.
@@ -82,14 +153,17 @@ pub struct PaginatableResponse {
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdApi {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        match &self.subcmd {
+            Some(SubCommand::Batch(cmd)) => return cmd.run(ctx).await,
+            Some(SubCommand::Ws(cmd)) => return cmd.run(ctx).await,
+            None => {}
+        }
+
         // Let's get the api client.
         let client = ctx.api_client("")?;
 
         // Make sure the endpoint starts with a slash.
-        let mut endpoint = self.endpoint.to_string();
-        if !self.endpoint.starts_with('/') {
-            endpoint = format!("/{endpoint}");
-        }
+        let mut endpoint = normalize_endpoint(self.endpoint.as_deref().unwrap_or_default());
 
         // Parse the fields.
         let params = self.parse_fields(ctx)?;
@@ -116,6 +190,10 @@ impl crate::cmd::Command for CmdApi {
             return Err(anyhow!("the `--paginate` option is not supported for non-GET requests",));
         }
 
+        if !self.vars.is_empty() && self.input.is_empty() {
+            return Err(anyhow!("the `--var` option requires an `--input` body"));
+        }
+
         // Parse the input file.
         if !self.input.is_empty() {
             // Read the input file.
@@ -129,6 +207,12 @@ impl crate::cmd::Command for CmdApi {
                 input_file.read_to_end(&mut buf)?;
             }
 
+            // Fill in the placeholders.
+            if !self.vars.is_empty() {
+                let body = String::from_utf8(buf).map_err(|_| anyhow!("the input body is not valid UTF-8"))?;
+                buf = render_template(&body, &self.parse_vars()?, self.allow_unresolved)?.into_bytes();
+            }
+
             // Set this as our body.
             bytes.clone_from(&buf);
 
@@ -146,9 +230,27 @@ impl crate::cmd::Command for CmdApi {
             }
         }
 
+        // Find the schema to check the response against.
+        let spec = if self.validate {
+            Some(crate::api_validate::load_spec()?)
+        } else {
+            None
+        };
+        let operation = spec
+            .as_ref()
+            .and_then(|spec| crate::api_validate::find_operation(spec, &method, &endpoint));
+        if spec.is_some() && operation.is_none() {
+            writeln!(
+                ctx.io.err_out,
+                "warning: `{} {}` is not in the API spec, not validating the response",
+                method, endpoint
+            )?;
+        }
+
         // Make the request.
         let mut has_next_page = true;
         let mut result = serde_json::Value::Null;
+        let mut raw_body = Vec::new();
         let mut page_results: Vec<serde_json::Value> = Vec::new();
         while has_next_page {
             let body = if bytes.is_empty() {
@@ -167,6 +269,16 @@ impl crate::cmd::Command for CmdApi {
                 }
             }
 
+            if self.dump_request || self.dry_run {
+                if let Some(dump) = req.0.try_clone() {
+                    let request = dump.build()?;
+                    write!(ctx.io.err_out, "{}", format_request(&request))?;
+                }
+                if self.dry_run {
+                    return Ok(());
+                }
+            }
+
             let resp = req.0.send().await?;
 
             // Print the response headers if requested.
@@ -187,8 +299,13 @@ impl crate::cmd::Command for CmdApi {
                 ));
             }
 
+            let status = resp.status().as_u16();
             if self.paginate {
-                let mut page: PaginatableResponse = resp.json().await?;
+                let page: serde_json::Value = resp.json().await?;
+                if let (Some(spec), Some((_, operation))) = (&spec, operation) {
+                    print_validation(ctx, spec, operation, status, &page)?;
+                }
+                let mut page: PaginatableResponse = serde_json::from_value(page)?;
 
                 if !page.items.is_empty() {
                     page_results.append(&mut page.items);
@@ -202,9 +319,16 @@ impl crate::cmd::Command for CmdApi {
                         has_next_page = false;
                     }
                 }
+            } else if self.raw {
+                // Keep the response body exactly as we got it.
+                raw_body = resp.bytes().await?.to_vec();
+                has_next_page = false;
             } else {
                 // Read the response body.
                 result = resp.json().await?;
+                if let (Some(spec), Some((_, operation))) = (&spec, operation) {
+                    print_validation(ctx, spec, operation, status, &result)?;
+                }
                 has_next_page = false;
             }
         }
@@ -213,7 +337,15 @@ impl crate::cmd::Command for CmdApi {
             result = serde_json::Value::Array(page_results);
         }
 
-        ctx.io.write_output_json(&result)?;
+        match (&self.output, self.raw) {
+            (Some(output), true) => std::fs::write(output, &raw_body)?,
+            (Some(output), false) => std::fs::write(output, format!("{}\n", serde_json::to_string_pretty(&result)?))?,
+            (None, true) => {
+                ctx.io.out.write_all(&raw_body)?;
+                ctx.io.out.flush()?;
+            }
+            (None, false) => ctx.io.write_output_json(&result)?,
+        }
 
         Ok(())
     }
@@ -234,6 +366,20 @@ impl CmdApi {
         Ok(headers)
     }
 
+    fn parse_vars(&self) -> Result<HashMap<String, String>> {
+        let mut vars: HashMap<String, String> = HashMap::new();
+
+        for v in self.vars.iter() {
+            let mut parts = v.splitn(2, '=');
+            let key = parts.next().ok_or_else(|| anyhow!("missing key in --var"))?;
+            let value = parts.next().ok_or_else(|| anyhow!("missing value in --var"))?;
+
+            vars.insert(key.trim().to_string(), value.to_string());
+        }
+
+        Ok(vars)
+    }
+
     fn parse_fields(&self, ctx: &mut crate::context::Context) -> Result<HashMap<String, serde_json::Value>> {
         let mut params: HashMap<String, serde_json::Value> = HashMap::new();
 
@@ -297,6 +443,61 @@ impl CmdApi {
     }
 }
 
+/// Print where a response body differs from the schema in the spec.
+fn print_validation(
+    ctx: &mut crate::context::Context,
+    spec: &openapiv3::OpenAPI,
+    operation: &openapiv3::Operation,
+    status: u16,
+    body: &serde_json::Value,
+) -> Result<()> {
+    let validator = crate::api_validate::Validator::new(spec);
+    let Some(schema) = validator.response_schema(operation, status) else {
+        writeln!(
+            ctx.io.err_out,
+            "warning: the API spec has no JSON schema for a {} response, not validating it",
+            status
+        )?;
+        return Ok(());
+    };
+
+    for error in validator.validate(schema, body) {
+        writeln!(
+            ctx.io.err_out,
+            "warning: response does not match the API spec: {}",
+            error
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Format a request like `curl --verbose` does, with the API token masked.
+fn format_request(request: &reqwest::Request) -> String {
+    let mut out = format!("> {} {}\n", request.method(), request.url());
+    for (name, value) in request.headers() {
+        let value = if name == reqwest::header::AUTHORIZATION {
+            match value.to_str().unwrap_or_default().split_once(' ') {
+                Some((scheme, _)) => format!("{} *******************", scheme),
+                None => "*******************".to_string(),
+            }
+        } else {
+            String::from_utf8_lossy(value.as_bytes()).to_string()
+        };
+        out.push_str(&format!("> {}: {}\n", name, value));
+    }
+    out.push_str(">\n");
+
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        if !body.is_empty() {
+            out.push_str(&String::from_utf8_lossy(body));
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
 fn print_headers(ctx: &mut crate::context::Context, headers: &reqwest::header::HeaderMap) -> Result<()> {
     let mut names: Vec<String> = headers.keys().map(|k| k.as_str().to_string()).collect();
     names.sort_by_key(|a| a.to_lowercase());
@@ -322,6 +523,275 @@ fn print_headers(ctx: &mut crate::context::Context, headers: &reqwest::header::H
     Ok(())
 }
 
+/// Make many requests to the Zoo API at once, and print the responses as a single JSON object.
+///
+/// The requests are read as a JSON array from a file, or from standard input if the file is "-".
+/// Each request has a `method` (defaults to "GET"), a `path`, an optional JSON `body`, and an
+/// optional `key` for its response in the output, which defaults to its index in the array.
+///
+///     $ echo '[{"key": "me", "path": "/user"}, {"path": "/user/api-calls"}]' | zoo api batch -
+///
+/// Each response in the output has the HTTP `status` and the `body` of the response. If any
+/// request fails, the exit code is 1.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdApiBatch {
+    /// The file with the requests (use "-" to read from standard input).
+    #[clap(name = "input", required = true)]
+    pub input: String,
+
+    /// The maximum number of requests to make at the same time.
+    #[clap(long, short, default_value = "4")]
+    pub concurrency: usize,
+}
+
+/// A request in a batch.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BatchRequest {
+    /// The key for the response in the output.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// The HTTP method for the request.
+    #[serde(default = "default_batch_method")]
+    pub method: String,
+    /// The endpoint to request.
+    pub path: String,
+    /// The JSON body for the request.
+    #[serde(default)]
+    pub body: Option<serde_json::Value>,
+}
+
+fn default_batch_method() -> String {
+    "GET".to_string()
+}
+
+/// The response to a request in a batch.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BatchResponse {
+    /// The HTTP status code, or 0 if the request could not be sent.
+    pub status: u16,
+    /// The response body, parsed as JSON if it is JSON.
+    pub body: serde_json::Value,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdApiBatch {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if self.concurrency < 1 {
+            return Err(anyhow!("--concurrency must be greater than 0"));
+        }
+
+        let mut buf = Vec::new();
+        if self.input == "-" {
+            ctx.io.stdin.read_to_end(&mut buf)?;
+        } else {
+            std::fs::File::open(&self.input)?.read_to_end(&mut buf)?;
+        }
+        let requests: Vec<BatchRequest> =
+            serde_json::from_slice(&buf).map_err(|err| anyhow!("invalid batch of requests: {}", err))?;
+
+        // Check all the requests before we send any of them.
+        let mut keys = std::collections::HashSet::new();
+        let mut methods = Vec::new();
+        for (i, request) in requests.iter().enumerate() {
+            let key = request.key.clone().unwrap_or_else(|| i.to_string());
+            if !keys.insert(key.clone()) {
+                return Err(anyhow!("duplicate key `{}` in batch of requests", key));
+            }
+            methods.push(
+                http::method::Method::from_bytes(request.method.to_uppercase().as_bytes())
+                    .map_err(|_| anyhow!("invalid method `{}` for request `{}`", request.method, key))?,
+            );
+        }
+
+        let client = ctx.api_client("")?;
+        let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(self.concurrency));
+        let mut tasks = tokio::task::JoinSet::new();
+        for (i, (request, method)) in requests.into_iter().zip(methods).enumerate() {
+            let client = client.clone();
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let key = request.key.clone().unwrap_or_else(|| i.to_string());
+                let response = send_batch_request(&client, method, &request)
+                    .await
+                    .unwrap_or_else(|err| BatchResponse {
+                        status: 0,
+                        body: serde_json::Value::String(err.to_string()),
+                    });
+                (key, response)
+            });
+        }
+
+        let mut results = serde_json::Map::new();
+        let mut failed = false;
+        while let Some(result) = tasks.join_next().await {
+            let (key, response) = result?;
+            failed |= !(200..300).contains(&response.status);
+            results.insert(key, serde_json::to_value(response)?);
+        }
+
+        ctx.io.write_output_json(&serde_json::Value::Object(results))?;
+
+        if failed {
+            return Err(crate::cmd::ExitCode(1).into());
+        }
+
+        Ok(())
+    }
+}
+
+async fn send_batch_request(
+    client: &kittycad::Client,
+    method: http::method::Method,
+    request: &BatchRequest,
+) -> Result<BatchResponse> {
+    let body = match &request.body {
+        Some(body) => Some(reqwest::Body::from(serde_json::to_vec(body)?)),
+        None => None,
+    };
+
+    let req = client
+        .request_raw(method, &normalize_endpoint(&request.path), body)
+        .await?;
+    let resp = req.0.send().await?;
+
+    let status = resp.status().as_u16();
+    let text = resp.text().await?;
+    let body = if text.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text))
+    };
+
+    Ok(BatchResponse { status, body })
+}
+
+/// Open a websocket to a Zoo API endpoint and exchange JSON messages with it.
+///
+/// Each line of standard input is sent as a text message, and must be JSON. Blank
+/// lines are skipped. Every message the server sends back is printed to standard
+/// output on a line of its own, with binary messages encoded as base64.
+///
+///     $ echo '{"type": "ping"}' | zoo api ws /ws/modeling/commands --timeout 10s
+///
+/// All of standard input is read and checked before the websocket is opened. After
+/// the messages are sent, zoo prints what the server sends until it closes the
+/// websocket or `--timeout` passes, then closes the websocket cleanly.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdApiWs {
+    /// The websocket endpoint to connect to, like `/ws/modeling/commands`.
+    #[clap(name = "endpoint", required = true)]
+    pub endpoint: String,
+
+    /// How long to keep the websocket open, like `30s` or `5m`. Without it, the
+    /// websocket stays open until the server closes it.
+    #[clap(long, value_parser = crate::cmd_file::parse_timeout)]
+    pub timeout: Option<std::time::Duration>,
+}
+
+/// How long to wait for the server to finish closing the websocket after we close it.
+const WS_CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdApiWs {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::{protocol::Role, Error as WsError, Message};
+
+        let mut input = String::new();
+        ctx.io.stdin.read_to_string(&mut input)?;
+        let messages = parse_ws_messages(&input)?;
+
+        let upgraded = ctx.api_ws("", &normalize_endpoint(&self.endpoint)).await?;
+        let mut ws = tokio_tungstenite::WebSocketStream::from_raw_socket(upgraded, Role::Client, None).await;
+
+        for message in messages {
+            ws.send(Message::Text(message)).await?;
+        }
+
+        let mut deadline = self.timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+        let mut closing = false;
+        loop {
+            let next = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline, ws.next()).await,
+                None => Ok(ws.next().await),
+            };
+            match next {
+                // We ran out of time: close the websocket, and give the server a moment
+                // to do the same.
+                Err(_) if !closing => {
+                    ws.close(None).await?;
+                    closing = true;
+                    deadline = Some(tokio::time::Instant::now() + WS_CLOSE_TIMEOUT);
+                }
+                Err(_) => return Err(anyhow!("the server did not close the websocket")),
+                Ok(None) | Ok(Some(Err(WsError::ConnectionClosed))) => break,
+                Ok(Some(Ok(Message::Text(text)))) => writeln!(ctx.io.out, "{}", text)?,
+                Ok(Some(Ok(Message::Binary(data)))) => writeln!(ctx.io.out, "{}", data_encoding::BASE64.encode(&data))?,
+                // Pings are answered and closes are acknowledged for us.
+                Ok(Some(Ok(_))) => {}
+                Ok(Some(Err(err))) => return Err(err.into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The messages to send over a websocket, one JSON value per line of the input.
+fn parse_ws_messages(input: &str) -> Result<Vec<String>> {
+    let mut messages = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        serde_json::from_str::<serde_json::Value>(line)
+            .map_err(|err| anyhow!("line {} of the input is not valid JSON: {}", i + 1, err))?;
+        messages.push(line.to_string());
+    }
+    Ok(messages)
+}
+
+/// Replace the `{{key}}` placeholders in a template with their values.
+fn render_template(template: &str, vars: &HashMap<String, String>, allow_unresolved: bool) -> Result<String> {
+    let re = regex::Regex::new(r"\{\{\s*([A-Za-z0-9_.-]+)\s*\}\}")?;
+
+    let mut unresolved: Vec<String> = Vec::new();
+    let result = re.replace_all(template, |caps: &regex::Captures| {
+        let key = &caps[1];
+        match vars.get(key) {
+            Some(value) => value.to_string(),
+            None => {
+                if !unresolved.iter().any(|k| k == key) {
+                    unresolved.push(key.to_string());
+                }
+                caps[0].to_string()
+            }
+        }
+    });
+
+    if !unresolved.is_empty() && !allow_unresolved {
+        return Err(anyhow!(
+            "no --var given for placeholders in the input body: {} (use --allow-unresolved to leave them as is)",
+            unresolved.join(", ")
+        ));
+    }
+
+    Ok(result.to_string())
+}
+
+/// Make sure the endpoint starts with a slash.
+fn normalize_endpoint(endpoint: &str) -> String {
+    if endpoint.starts_with('/') {
+        endpoint.to_string()
+    } else {
+        format!("/{endpoint}")
+    }
+}
+
 fn add_query_string(endpoint: &str, query_string: &str) -> String {
     if endpoint.contains('?') {
         format!("{endpoint}&{query_string}")
@@ -336,6 +806,31 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_format_request() {
+        let mut request = reqwest::Request::new(
+            http::Method::POST,
+            "https://api.zoo.dev/user/api-tokens".parse().unwrap(),
+        );
+        request
+            .headers_mut()
+            .insert(reqwest::header::AUTHORIZATION, "Bearer secret-token".parse().unwrap());
+        request
+            .headers_mut()
+            .insert(reqwest::header::CONTENT_TYPE, "application/json".parse().unwrap());
+        *request.body_mut() = Some(reqwest::Body::from(r#"{"label":"ci"}"#));
+
+        assert_eq!(
+            format_request(&request),
+            r#"> POST https://api.zoo.dev/user/api-tokens
+> authorization: Bearer *******************
+> content-type: application/json
+>
+{"label":"ci"}
+"#
+        );
+    }
+
     #[test]
     fn test_add_query_string() {
         let mut endpoint = "https://api.github.com/users/octocat/repos";
@@ -352,4 +847,33 @@ mod test {
         expected = "https://api.github.com/users/octocat/repos?page=2&per_page=100&foo=bar";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_parse_ws_messages() {
+        let messages = parse_ws_messages("{\"type\": \"ping\"}\n\n  [1, 2]  \n").unwrap();
+        assert_eq!(messages, vec![r#"{"type": "ping"}"#.to_string(), "[1, 2]".to_string()]);
+
+        let err = parse_ws_messages("{\"type\": \"ping\"}\nnot json\n").unwrap_err();
+        assert!(
+            err.to_string().starts_with("line 2 of the input is not valid JSON"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_render_template() {
+        let vars = HashMap::from([
+            ("name".to_string(), "foo".to_string()),
+            ("count".to_string(), "3".to_string()),
+        ]);
+
+        let result = render_template(r#"{"name": "{{name}}", "count": {{ count }}}"#, &vars, false).unwrap();
+        assert_eq!(result, r#"{"name": "foo", "count": 3}"#);
+
+        let err = render_template(r#"{"name": "{{name}}", "id": "{{id}}"}"#, &vars, false).unwrap_err();
+        assert!(err.to_string().contains(": id "), "{err}");
+
+        let result = render_template(r#"{"name": "{{name}}", "id": "{{id}}"}"#, &vars, true).unwrap();
+        assert_eq!(result, r#"{"name": "foo", "id": "{{id}}"}"#);
+    }
 }
@@ -15,6 +15,7 @@ pub struct CmdApiCall {
 
 #[derive(Parser, Debug, Clone)]
 enum SubCommand {
+    Metrics(CmdApiCallMetrics),
     Status(CmdApiCallStatus),
 }
 
@@ -22,6 +23,7 @@ enum SubCommand {
 impl crate::cmd::Command for CmdApiCall {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
         match &self.subcmd {
+            SubCommand::Metrics(cmd) => cmd.run(ctx).await,
             SubCommand::Status(cmd) => cmd.run(ctx).await,
         }
     }
@@ -100,3 +102,215 @@ impl crate::cmd::Command for CmdApiCallStatus {
         Ok(())
     }
 }
+
+/// Summarize your async API calls over a window of time, like file conversions and
+/// text-to-CAD prompts, by type and status.
+///
+///     # the last week
+///     $ zoo api-call metrics
+///
+///     # the last day, as json
+///     $ zoo api-call metrics --since 24h --format json
+///
+/// The duration is the total time the calls of each type and status took to run, from
+/// when they started to when they completed, in seconds.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdApiCallMetrics {
+    /// How far back to look, like `30m`, `12h`, `7d` or `2w`.
+    #[clap(long, default_value = "7d", value_parser = parse_since)]
+    pub since: chrono::Duration,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+}
+
+/// The API calls of one type and status.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, tabled::Tabled)]
+pub struct ApiCallMetrics {
+    /// The type of API call.
+    #[serde(rename = "type")]
+    #[tabled(rename = "type")]
+    pub call_type: String,
+    /// The status of the API calls.
+    pub status: String,
+    /// How many API calls there were.
+    pub count: usize,
+    /// How long they took to run in total, in seconds.
+    pub total_duration_seconds: f64,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdApiCallMetrics {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let client = ctx.api_client("")?;
+        let since = chrono::Utc::now()
+            .checked_sub_signed(self.since)
+            .ok_or_else(|| anyhow::anyhow!("`--since` goes too far back"))?;
+
+        // Newest first, so we can stop at the first page that goes past the window.
+        let mut calls = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = client
+                .api_calls()
+                .list_async_operations(
+                    Some(100),
+                    page_token,
+                    Some(kittycad::types::CreatedAtSortMode::CreatedAtDescending),
+                    None,
+                )
+                .await?;
+            let page_len = page.items.len();
+            let before = calls.len();
+            calls.extend(page.items.into_iter().filter(|call| call.created_at >= since));
+            // Once one call is older than the window, the rest are too.
+            let done = page_len == 0 || calls.len() - before < page_len;
+            match page.next_page {
+                Some(next_page) if !done => page_token = Some(next_page),
+                _ => break,
+            }
+        }
+
+        let metrics = summarize(&calls)?;
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, metrics)?;
+
+        Ok(())
+    }
+}
+
+/// Parse a window of time like `30m`, `12h`, `7d` or `2w`.
+fn parse_since(s: &str) -> Result<chrono::Duration> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(digits);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid window `{}`, expected one like `7d` or `12h`", s))?;
+
+    let duration = match unit.trim() {
+        "m" => chrono::Duration::try_minutes(number),
+        "h" => chrono::Duration::try_hours(number),
+        "d" => chrono::Duration::try_days(number),
+        "w" => chrono::Duration::try_weeks(number),
+        unit => anyhow::bail!("invalid window unit `{}`, expected one of m, h, d or w", unit),
+    };
+    duration.ok_or_else(|| anyhow::anyhow!("window `{}` is too large", s))
+}
+
+/// Count the API calls and add up how long they took, by type and status.
+fn summarize(calls: &[kittycad::types::AsyncApiCall]) -> Result<Vec<ApiCallMetrics>> {
+    let mut metrics: std::collections::BTreeMap<(String, String), ApiCallMetrics> = Default::default();
+    for call in calls {
+        // Use the names from the API, like `file_conversion`.
+        let call_type = serde_json::to_value(&call.type_)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let status = serde_json::to_value(&call.status)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let entry = metrics
+            .entry((call_type.clone(), status.clone()))
+            .or_insert_with(|| ApiCallMetrics {
+                call_type,
+                status,
+                count: 0,
+                total_duration_seconds: 0.0,
+            });
+        entry.count += 1;
+        if let (Some(started_at), Some(completed_at)) = (call.started_at, call.completed_at) {
+            entry.total_duration_seconds += (completed_at - started_at).num_milliseconds() as f64 / 1000.0;
+        }
+    }
+
+    Ok(metrics.into_values().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("7d").unwrap(), chrono::Duration::try_days(7).unwrap());
+        assert_eq!(parse_since("12h").unwrap(), chrono::Duration::try_hours(12).unwrap());
+        assert_eq!(parse_since("2w").unwrap(), chrono::Duration::try_weeks(2).unwrap());
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("7y").is_err());
+    }
+
+    fn api_call(call_type: &str, status: &str, started_at: &str, completed_at: &str) -> kittycad::types::AsyncApiCall {
+        serde_json::from_value(serde_json::json!({
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "started_at": started_at,
+            "completed_at": completed_at,
+            "id": "6c7b3c5f-0b32-4f4e-8d3f-3f1c1b2a9d10",
+            "status": status,
+            "type": call_type,
+            "user_id": "2d4f1a60-7a0c-4c4b-9a40-0b7d9d7e2a11",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_summarize() {
+        let calls = vec![
+            api_call(
+                "text_to_cad",
+                "completed",
+                "2024-01-01T00:00:00Z",
+                "2024-01-01T00:01:00Z",
+            ),
+            api_call(
+                "file_conversion",
+                "completed",
+                "2024-01-01T00:00:00Z",
+                "2024-01-01T00:00:02.5Z",
+            ),
+            api_call(
+                "file_conversion",
+                "completed",
+                "2024-01-01T00:00:00Z",
+                "2024-01-01T00:00:01Z",
+            ),
+            api_call(
+                "file_conversion",
+                "failed",
+                "2024-01-01T00:00:00Z",
+                "2024-01-01T00:00:01Z",
+            ),
+        ];
+
+        assert_eq!(
+            summarize(&calls).unwrap(),
+            vec![
+                ApiCallMetrics {
+                    call_type: "file_conversion".to_string(),
+                    status: "completed".to_string(),
+                    count: 2,
+                    total_duration_seconds: 3.5,
+                },
+                ApiCallMetrics {
+                    call_type: "file_conversion".to_string(),
+                    status: "failed".to_string(),
+                    count: 1,
+                    total_duration_seconds: 1.0,
+                },
+                ApiCallMetrics {
+                    call_type: "text_to_cad".to_string(),
+                    status: "completed".to_string(),
+                    count: 1,
+                    total_duration_seconds: 60.0,
+                },
+            ]
+        );
+    }
+}
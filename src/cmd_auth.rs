@@ -19,6 +19,9 @@ enum SubCommand {
     Login(CmdAuthLogin),
     Logout(CmdAuthLogout),
     Status(CmdAuthStatus),
+    Token(CmdAuthToken),
+    Switch(CmdAuthSwitch),
+    List(CmdAuthList),
 }
 
 #[async_trait::async_trait(?Send)]
@@ -28,6 +31,9 @@ impl crate::cmd::Command for CmdAuth {
             SubCommand::Login(cmd) => cmd.run(ctx).await,
             SubCommand::Logout(cmd) => cmd.run(ctx).await,
             SubCommand::Status(cmd) => cmd.run(ctx).await,
+            SubCommand::Token(cmd) => cmd.run(ctx).await,
+            SubCommand::Switch(cmd) => cmd.run(ctx).await,
+            SubCommand::List(cmd) => cmd.run(ctx).await,
         }
     }
 }
@@ -95,11 +101,17 @@ pub fn parse_host(input: &str) -> Result<url::Url> {
 ///
 ///     # authenticate with an insecure Zoo instance (not recommended)
 ///     $ zoo auth login --host http://zoo.internal
+///
+///     # authenticate on a machine without a browser, by entering a code on another one
+///     $ zoo auth login --device-code
+///
+///     # log in to a second account on the same host, and make it the active one
+///     $ zoo auth login --account work
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdAuthLogin {
     /// Read token from standard input.
-    #[clap(long)]
+    #[clap(long, conflicts_with_all = ["web", "device_code"])]
     pub with_token: bool,
 
     /// The host of the Zoo instance to authenticate with.
@@ -111,13 +123,30 @@ pub struct CmdAuthLogin {
     /// Open a browser to authenticate.
     #[clap(short, long)]
     pub web: bool,
+
+    /// Print a URL and a code to enter on another device, and wait for the login to be
+    /// approved there. Use this on machines without a browser, like servers.
+    #[clap(long, conflicts_with = "web")]
+    pub device_code: bool,
+
+    /// The name of the account to log in to, for having more than one account on a host.
+    /// This account becomes the active one, see `zoo auth switch`.
+    #[clap(long)]
+    pub account: Option<String>,
+
+    /// The scopes to request for the token, separated by commas.
+    /// By default the token gets the scopes the Zoo instance gives the CLI.
+    #[clap(long, value_delimiter = ',', conflicts_with = "with_token")]
+    pub scopes: Vec<String>,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdAuthLogin {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        if !ctx.io.can_prompt() && !self.with_token {
-            return Err(anyhow!("--with-token required when not running interactively"));
+        if !ctx.io.can_prompt() && !self.with_token && !self.device_code {
+            return Err(anyhow!(
+                "--with-token required when not running interactively, or use --device-code"
+            ));
         }
 
         let mut token = String::new();
@@ -156,6 +185,11 @@ impl crate::cmd::Command for CmdAuthLogin {
             return Err(err);
         }
 
+        // Everything below reads and writes the token of the active account.
+        if let Some(account) = &self.account {
+            ctx.config.set(host, "account", Some(account))?;
+        }
+
         let cs = ctx.io.color_scheme();
 
         // Do the login flow if we didn't get a token from stdin.
@@ -183,7 +217,7 @@ impl crate::cmd::Command for CmdAuthLogin {
             // Check the method they would like to login, web or otherwise.
             let mut web = self.web;
             // Only do this if they didn't already select web, and we can run interactively.
-            if interactive && !self.web {
+            if interactive && !self.web && !self.device_code {
                 let auth_options = vec!["Login with a web browser", "Paste an authentication token"];
                 match dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
                     .with_prompt("How would you like to authenticate Zoo CLI?")
@@ -203,54 +237,8 @@ impl crate::cmd::Command for CmdAuthLogin {
                 }
             }
 
-            token = if web {
-                // Do an OAuth 2.0 Device Authorization Grant dance to get a token.
-                let device_auth_url = oauth2::DeviceAuthorizationUrl::new(format!("{host}oauth2/device/auth"))?;
-                // We can hardcode the client ID.
-                // This value is safe to be embedded in version control.
-                // This is the client ID of the cli.
-                let client_id = "6bd9f64f-0ed6-40c2-ada0-87e1fc699227".to_string();
-                let auth_client = oauth2::basic::BasicClient::new(
-                    oauth2::ClientId::new(client_id),
-                    None,
-                    oauth2::AuthUrl::new(format!("{host}authorize"))?,
-                    Some(oauth2::TokenUrl::new(format!("{host}oauth2/device/token"))?),
-                )
-                .set_auth_type(oauth2::AuthType::RequestBody)
-                .set_device_authorization_url(device_auth_url);
-                writeln!(ctx.io.err_out, "Tip: you can generate an API Token here {host}account")?;
-
-                let details: oauth2::devicecode::StandardDeviceAuthorizationResponse = auth_client
-                    .exchange_device_code()?
-                    .request_async(oauth2::reqwest::async_http_client)
-                    .await?;
-
-                if let Some(uri) = details.verification_uri_complete() {
-                    writeln!(
-                        ctx.io.out,
-                        "Opening {} in your browser.\n\
-                     Please verify user code: {}\n",
-                        **details.verification_uri(),
-                        details.user_code().secret()
-                    )?;
-                    ctx.browser(host, uri.secret())?;
-                } else {
-                    writeln!(
-                        ctx.io.out,
-                        "Open this URL in your browser:\n{}\n\
-                     And enter the code: {}\n",
-                        **details.verification_uri(),
-                        details.user_code().secret()
-                    )?;
-                }
-
-                auth_client
-                    .exchange_device_access_token(&details)
-                    .request_async(oauth2::reqwest::async_http_client, tokio::time::sleep, None)
-                    .await?
-                    .access_token()
-                    .secret()
-                    .to_string()
+            token = if web || self.device_code {
+                device_code_login(ctx, host, &self.scopes, !self.device_code).await?
             } else {
                 writeln!(ctx.io.err_out, "Tip: you can generate an API Token here {host}account")?;
 
@@ -289,6 +277,85 @@ impl crate::cmd::Command for CmdAuthLogin {
     }
 }
 
+/// Get a token with the OAuth 2.0 Device Authorization Grant: print a URL and code for
+/// the user to approve, optionally opening the URL in their browser, and poll until
+/// they do.
+async fn device_code_login(
+    ctx: &mut crate::context::Context<'_>,
+    host: &str,
+    scopes: &[String],
+    open_browser: bool,
+) -> Result<String> {
+    let device_auth_url = oauth2::DeviceAuthorizationUrl::new(format!("{host}oauth2/device/auth"))?;
+    // We can hardcode the client ID.
+    // This value is safe to be embedded in version control.
+    // This is the client ID of the cli.
+    let client_id = "6bd9f64f-0ed6-40c2-ada0-87e1fc699227".to_string();
+    let auth_client = oauth2::basic::BasicClient::new(
+        oauth2::ClientId::new(client_id),
+        None,
+        oauth2::AuthUrl::new(format!("{host}authorize"))?,
+        Some(oauth2::TokenUrl::new(format!("{host}oauth2/device/token"))?),
+    )
+    .set_auth_type(oauth2::AuthType::RequestBody)
+    .set_device_authorization_url(device_auth_url);
+    writeln!(ctx.io.err_out, "Tip: you can generate an API Token here {host}account")?;
+
+    let details: oauth2::devicecode::StandardDeviceAuthorizationResponse = auth_client
+        .exchange_device_code()?
+        .add_scopes(scopes.iter().map(|scope| oauth2::Scope::new(scope.to_string())))
+        .request_async(oauth2::reqwest::async_http_client)
+        .await?;
+
+    match details.verification_uri_complete() {
+        Some(uri) if open_browser => {
+            writeln!(
+                ctx.io.out,
+                "Opening {} in your browser.\n\
+                 Please verify user code: {}\n",
+                **details.verification_uri(),
+                details.user_code().secret()
+            )?;
+            ctx.browser(host, uri.secret())?;
+        }
+        _ => {
+            writeln!(
+                ctx.io.out,
+                "Open this URL in your browser:\n{}\n\
+                 And enter the code: {}\n",
+                **details.verification_uri(),
+                details.user_code().secret()
+            )?;
+            if !open_browser {
+                writeln!(
+                    ctx.io.out,
+                    "Waiting for the login to be approved, this code expires in {} minutes.",
+                    details.expires_in().as_secs().div_ceil(60)
+                )?;
+            }
+        }
+    }
+
+    // This polls until the login is approved, denied, or the code expires.
+    match auth_client
+        .exchange_device_access_token(&details)
+        .request_async(oauth2::reqwest::async_http_client, tokio::time::sleep, None)
+        .await
+    {
+        Ok(token) => Ok(token.access_token().secret().to_string()),
+        Err(oauth2::RequestTokenError::ServerResponse(resp)) => match resp.error() {
+            oauth2::DeviceCodeErrorResponseType::AccessDenied => {
+                Err(anyhow!("the login was denied, run `zoo auth login` to try again"))
+            }
+            oauth2::DeviceCodeErrorResponseType::ExpiredToken => Err(anyhow!(
+                "the code expired before the login was approved, run `zoo auth login` to get a new one"
+            )),
+            _ => Err(anyhow!("login failed: {}", resp)),
+        },
+        Err(err) => Err(anyhow!("login failed: {}", err)),
+    }
+}
+
 /// Log out of an Zoo host.
 ///
 /// This command removes the authentication configuration for a host either specified
@@ -422,6 +489,12 @@ impl crate::cmd::Command for CmdAuthLogout {
 ///
 /// This command will test your authentication state for each Zoo host that `zoo`
 /// knows about and report on any issues.
+///
+///     # check every account on every host, as a table
+///     $ zoo auth status --all
+///
+///     # or as json
+///     $ zoo auth status --all --format json
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdAuthStatus {
@@ -432,11 +505,54 @@ pub struct CmdAuthStatus {
     /// Check a specific hostname's auth status.
     #[clap(short = 'H', long, env = "ZOO_HOST", value_parser = parse_host)]
     pub host: Option<url::Url>,
+
+    /// Check every account on every host, not only the active ones, and report them
+    /// together.
+    #[clap(long, conflicts_with_all = ["host", "show_token"])]
+    pub all: bool,
+
+    /// Output format for `--all`.
+    #[clap(long, short, value_enum, requires = "all")]
+    pub format: Option<crate::types::FormatOutput>,
+}
+
+/// Whether an account's token works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, serde::Serialize)]
+#[display(style = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TokenStatus {
+    /// The API accepted the token.
+    Valid,
+    /// The API did not accept the token.
+    Invalid,
+    /// There is no token.
+    Missing,
+}
+
+/// The auth status of one account on a host, for `zoo auth status --all`.
+#[derive(Debug, Clone, serde::Serialize, tabled::Tabled)]
+pub struct AccountStatus {
+    /// The host.
+    pub host: String,
+    /// The name of the account, or empty for a host without named accounts.
+    pub account: String,
+    /// Whether this is the account commands use for the host.
+    pub active: bool,
+    /// Whether the token works.
+    pub status: TokenStatus,
+    /// The user the token is for, or why it didn't work.
+    pub detail: String,
+    /// Where the token came from.
+    pub source: String,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdAuthStatus {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if self.all {
+            return self.run_all(ctx).await;
+        }
+
         let cs = ctx.io.color_scheme();
 
         let mut status_info: HashMap<String, Vec<String>> = HashMap::new();
@@ -528,6 +644,241 @@ impl crate::cmd::Command for CmdAuthStatus {
     }
 }
 
+impl CmdAuthStatus {
+    async fn run_all(&self, ctx: &mut crate::context::Context<'_>) -> Result<()> {
+        let mut statuses = Vec::new();
+        for host in ctx.config.hosts()? {
+            let active = ctx.config.get(&host, "account").unwrap_or_default();
+            let accounts = ctx.config.accounts(&host)?;
+            if accounts.is_empty() {
+                statuses.push(account_status(ctx, &host, "", true).await);
+                continue;
+            }
+
+            // The token of an account is the one the config gives while it is active, so
+            // make each one active in turn. This is never written.
+            for account in &accounts {
+                ctx.config.set(&host, "account", Some(account))?;
+                statuses.push(account_status(ctx, &host, account, *account == active).await);
+            }
+            ctx.config
+                .set(&host, "account", Some(active.as_str()).filter(|a| !a.is_empty()))?;
+        }
+
+        if statuses.is_empty() {
+            let cs = ctx.io.color_scheme();
+            writeln!(
+                ctx.io.err_out,
+                "You are not logged into any Zoo hosts. Run `{}` to authenticate.",
+                cs.bold("zoo auth login")
+            )?;
+            return Ok(());
+        }
+
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, &statuses)?;
+
+        if statuses.iter().any(|status| status.status != TokenStatus::Valid) {
+            return Err(crate::cmd::ExitCode(1).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the token the config gives for a host, without failing if it doesn't work.
+async fn account_status(ctx: &crate::context::Context<'_>, host: &str, account: &str, active: bool) -> AccountStatus {
+    let mut status = AccountStatus {
+        host: host.to_string(),
+        account: account.to_string(),
+        active,
+        status: TokenStatus::Missing,
+        detail: "not logged in".to_string(),
+        source: String::new(),
+    };
+
+    match ctx.config.get_with_source(host, "token") {
+        Ok((token, source)) if !token.is_empty() => status.source = source,
+        _ => return status,
+    }
+
+    let result = match ctx.api_client(host) {
+        Ok(client) => client.users().get_self().await.map_err(anyhow::Error::from),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(user) => {
+            status.status = TokenStatus::Valid;
+            status.detail = user.email.unwrap_or_else(|| user.id.to_string());
+        }
+        Err(err) => {
+            status.status = TokenStatus::Invalid;
+            status.detail = err.to_string();
+        }
+    }
+    status
+}
+
+/// Print the authentication token for a Zoo host.
+///
+/// This prints the token `zoo` would use, for passing to other tools. The token is
+/// resolved the same way as for every other command: from `ZOO_TOKEN`, then from the
+/// file at `ZOO_TOKEN_FILE`, then from the config.
+///
+/// Anyone with this token can act as you on the Zoo API, until it is revoked. Be careful
+/// where it ends up: in shell history, logs, CI output, or on a screen someone else can
+/// see. That is why it is only printed to a terminal with `--force`.
+///
+///     # pass the token to another tool
+///     $ curl -H "Authorization: Bearer $(zoo auth token)" https://api.zoo.dev/user
+///
+///     # get the token for a specific host
+///     $ zoo auth token --host zoo.internal | pbcopy
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdAuthToken {
+    /// The host of the Zoo instance to get the token for.
+    /// By default this is the default host.
+    #[clap(short = 'H', long, env = "ZOO_HOST", value_parser = parse_host)]
+    pub host: Option<url::Url>,
+
+    /// Print the token even if stdout is a terminal.
+    #[clap(long)]
+    pub force: bool,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdAuthToken {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if ctx.io.is_stdout_tty() && !self.force {
+            return Err(anyhow!(
+                "refusing to print the token to a terminal, pipe it to another command or use --force"
+            ));
+        }
+
+        let host = if let Some(host) = &self.host {
+            host.to_string()
+        } else {
+            ctx.config.default_host()?
+        };
+
+        let token = ctx.config.get(&host, "token").unwrap_or_default();
+        if token.is_empty() {
+            return Err(anyhow!(
+                "not logged in to {}, run `zoo auth login` to authenticate",
+                host
+            ));
+        }
+
+        writeln!(ctx.io.out, "{token}")?;
+
+        Ok(())
+    }
+}
+
+/// Switch the active account for a Zoo host.
+///
+/// Log in to more accounts with `zoo auth login --account <name>`. The active account's
+/// token is used for every command.
+///
+///     $ zoo auth switch work
+///
+///     $ zoo auth switch personal --host zoo.internal
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdAuthSwitch {
+    /// The name of the account to switch to.
+    #[clap(name = "account", required = true)]
+    pub account: String,
+
+    /// The host of the Zoo instance the account is on.
+    /// By default this is the default host.
+    #[clap(short = 'H', long, env = "ZOO_HOST", value_parser = parse_host)]
+    pub host: Option<url::Url>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdAuthSwitch {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let host = if let Some(host) = &self.host {
+            host.to_string()
+        } else {
+            ctx.config.default_host()?
+        };
+
+        let accounts = ctx.config.accounts(&host)?;
+        if !accounts.contains(&self.account) {
+            return Err(anyhow!(
+                "no account `{}` for {}, log in to it with `zoo auth login --account {}`",
+                self.account,
+                host,
+                self.account
+            ));
+        }
+
+        ctx.config.set(&host, "account", Some(&self.account))?;
+        ctx.config.write()?;
+
+        let cs = ctx.io.color_scheme();
+        let user = ctx.config.get(&host, "user").unwrap_or_default();
+        writeln!(
+            ctx.io.out,
+            "{} Switched to account {} on {} ({})",
+            cs.success_icon(),
+            cs.bold(&self.account),
+            host,
+            user
+        )?;
+
+        Ok(())
+    }
+}
+
+/// List the accounts you are logged in to, for each Zoo host.
+///
+/// The active account of each host is marked with a `*`.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdAuthList {
+    /// Only list the accounts for this host.
+    #[clap(short = 'H', long, env = "ZOO_HOST", value_parser = parse_host)]
+    pub host: Option<url::Url>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdAuthList {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let cs = ctx.io.color_scheme();
+
+        let hosts = if let Some(host) = &self.host {
+            vec![host.to_string()]
+        } else {
+            ctx.config.hosts()?
+        };
+
+        for host in hosts {
+            writeln!(ctx.io.out, "{}", cs.bold(&host))?;
+
+            let accounts = ctx.config.accounts(&host)?;
+            if accounts.is_empty() {
+                writeln!(
+                    ctx.io.out,
+                    "  no named accounts, use `zoo auth login --account <name>` to add one"
+                )?;
+                continue;
+            }
+
+            let active = ctx.config.get(&host, "account").unwrap_or_default();
+            for account in accounts {
+                let marker = if account == active { "*" } else { " " };
+                writeln!(ctx.io.out, "{} {}", marker, account)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
@@ -556,6 +907,8 @@ mod test {
                 cmd: crate::cmd_auth::SubCommand::Status(crate::cmd_auth::CmdAuthStatus {
                     show_token: false,
                     host: None,
+                    all: false,
+                    format: None,
                 }),
                 stdin: "".to_string(),
                 want_out: "".to_string(),
@@ -567,6 +920,9 @@ mod test {
                     host: Some(test_host.clone()),
                     with_token: false,
                     web: false,
+                    device_code: false,
+                    account: None,
+                    scopes: vec![],
                 }),
                 stdin: test_token.to_string(),
                 want_out: "".to_string(),
@@ -578,6 +934,9 @@ mod test {
                     host: Some(test_host.clone()),
                     with_token: true,
                     web: false,
+                    device_code: false,
+                    account: None,
+                    scopes: vec![],
                 }),
                 stdin: test_token.to_string(),
                 want_out: "✔ Logged in as ".to_string(),
@@ -588,11 +947,35 @@ mod test {
                 cmd: crate::cmd_auth::SubCommand::Status(crate::cmd_auth::CmdAuthStatus {
                     show_token: false,
                     host: Some(test_host.clone()),
+                    all: false,
+                    format: None,
                 }),
                 stdin: "".to_string(),
                 want_out: format!("{test_host}\n✔ Logged in to {test_host} as"),
                 want_err: "".to_string(),
             },
+            TestItem {
+                name: "status --all".to_string(),
+                cmd: crate::cmd_auth::SubCommand::Status(crate::cmd_auth::CmdAuthStatus {
+                    show_token: false,
+                    host: None,
+                    all: true,
+                    format: Some(crate::types::FormatOutput::Json),
+                }),
+                stdin: "".to_string(),
+                want_out: format!("\"host\": \"{test_host}\""),
+                want_err: "".to_string(),
+            },
+            TestItem {
+                name: "token".to_string(),
+                cmd: crate::cmd_auth::SubCommand::Token(crate::cmd_auth::CmdAuthToken {
+                    host: Some(test_host.clone()),
+                    force: false,
+                }),
+                stdin: "".to_string(),
+                want_out: format!("{test_token}\n"),
+                want_err: "".to_string(),
+            },
             TestItem {
                 name: "logout no prompt no host".to_string(),
                 cmd: crate::cmd_auth::SubCommand::Logout(crate::cmd_auth::CmdAuthLogout { host: None }),
@@ -624,11 +1007,7 @@ mod test {
             io.set_color_enabled(false);
             // TODO: we should figure out how to test the prompts.
             io.set_never_prompt(true);
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             let cmd_auth = crate::cmd_auth::CmdAuth { subcmd: t.cmd };
             match cmd_auth.run(&mut ctx).await {
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use clap::{Command, CommandFactory, Parser};
 use clap_complete::{generate, Shell};
@@ -48,12 +50,56 @@ use clap_complete::{generate, Shell};
 /// Add the line and save the file:
 ///
 ///     Invoke-Expression -Command $(zoo completion -s powershell | Out-String)
+///
+/// ### Installing automatically
+///
+/// For bash, zsh and fish, `--install` writes the completion script to a completion
+/// directory the shell already loads from, and prints where it went:
+///
+///     zoo completion -s zsh --install
+///
+/// Only directories you can write to are used. If none is found, the manual
+/// instructions above for the shell are printed instead, along with how to write the
+/// script to a directory of your own. Use `--completions-dir` to pick the directory
+/// yourself.
+///
+/// ### Checking for changes
+///
+/// `--check` compares the completion script to a snapshot of it, and fails if they
+/// differ, so an upgrade that changes the completions doesn't go unnoticed in CI.
+/// `--update` writes the snapshot:
+///
+///     zoo completion -s bash --update
+///     zoo completion -s bash --check
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdCompletion {
     /// The shell type.
     #[clap(short, long, default_value = "bash", value_enum)]
     pub shell: Shell,
+
+    /// Install the completion script into the shell's completion directory, instead
+    /// of printing it.
+    #[clap(long)]
+    pub install: bool,
+
+    /// The directory to install the completion script into.
+    /// Implies `--install`.
+    #[clap(long)]
+    pub completions_dir: Option<PathBuf>,
+
+    /// Compare the completion script to the snapshot, and fail if it changed.
+    #[clap(long, conflicts_with_all = ["install", "completions_dir", "update"])]
+    pub check: bool,
+
+    /// Write the completion script to the snapshot, for `--check`.
+    #[clap(long, conflicts_with_all = ["install", "completions_dir"])]
+    pub update: bool,
+
+    /// The snapshot for `--check` and `--update`.
+    /// This defaults to `tests/completions/<shell>`.
+    #[clap(long)]
+    pub snapshot: Option<PathBuf>,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -62,16 +108,221 @@ impl crate::cmd::Command for CmdCompletion {
         // Convert our opts into a clap app.
         let mut app: Command = crate::Opts::command();
         let name = app.get_name().to_string();
-        // Generate the completion script.
-        generate(self.shell, &mut app, name, &mut ctx.io.out);
 
-        // Add a new line.
-        writeln!(ctx.io.out)?;
+        if self.check || self.update {
+            let mut script = Vec::new();
+            generate(self.shell, &mut app, name, &mut script);
+            writeln!(script)?;
+
+            let snapshot = self
+                .snapshot
+                .clone()
+                .unwrap_or_else(|| PathBuf::from("tests/completions").join(self.shell.to_string()));
+            if self.update {
+                install_script(snapshot.parent().unwrap_or(Path::new(".")), &snapshot, &script)?;
+                writeln!(
+                    ctx.io.out,
+                    "Updated {} completions in {}",
+                    self.shell,
+                    snapshot.display()
+                )?;
+                return Ok(());
+            }
+
+            let expected = std::fs::read(&snapshot).map_err(|err| {
+                anyhow::anyhow!(
+                    "failed to read the snapshot `{}`: {}, create it with `--update`",
+                    snapshot.display(),
+                    err
+                )
+            })?;
+            if let Some(line) = first_difference(&expected, &script) {
+                anyhow::bail!(
+                    "the {} completions changed from `{}` at line {}, run with `--update` if that was expected",
+                    self.shell,
+                    snapshot.display(),
+                    line
+                );
+            }
+            writeln!(
+                ctx.io.out,
+                "The {} completions match {}",
+                self.shell,
+                snapshot.display()
+            )?;
+            return Ok(());
+        }
+
+        if !self.install && self.completions_dir.is_none() {
+            // Generate the completion script.
+            generate(self.shell, &mut app, name, &mut ctx.io.out);
+
+            // Add a new line.
+            writeln!(ctx.io.out)?;
+
+            return Ok(());
+        }
+
+        let mut script = Vec::new();
+        generate(self.shell, &mut app, name.clone(), &mut script);
+        writeln!(script)?;
+
+        let Some(file_name) = completion_file_name(self.shell, &name) else {
+            anyhow::bail!(
+                "installing completions is not supported for {}, run `zoo completion --help` for how to set them up",
+                self.shell
+            );
+        };
+
+        let dirs = if let Some(dir) = &self.completions_dir {
+            vec![dir.clone()]
+        } else {
+            completion_dirs(self.shell, |key| std::env::var(key).ok(), dirs::home_dir())
+        };
+
+        for dir in &dirs {
+            let path = dir.join(&file_name);
+            match install_script(dir, &path, &script) {
+                Ok(()) => {
+                    writeln!(ctx.io.out, "Installed {} completions to {}", self.shell, path.display())?;
+                    if self.shell == Shell::Zsh {
+                        writeln!(
+                            ctx.io.out,
+                            "Make sure `compinit` runs in your `~/.zshrc`, then restart your shell."
+                        )?;
+                    } else {
+                        writeln!(ctx.io.out, "Restart your shell to start using them.")?;
+                    }
+                    return Ok(());
+                }
+                Err(err) => {
+                    if ctx.debug {
+                        writeln!(ctx.io.err_out, "skipping {}: {}", dir.display(), err)?;
+                    }
+                }
+            }
+        }
+
+        if let Some(dir) = &self.completions_dir {
+            anyhow::bail!("could not write completions to {}", dir.display());
+        }
+
+        writeln!(
+            ctx.io.err_out,
+            "could not find a writable completion directory for {}, set them up yourself like this, or pass `--completions-dir`",
+            self.shell
+        )?;
+        let help = CmdCompletion::command().get_long_about().map(|help| help.to_string());
+        if let Some(instructions) = help.and_then(|help| manual_instructions(&help, self.shell)) {
+            writeln!(ctx.io.out, "{}\n", instructions)?;
+        }
+        writeln!(
+            ctx.io.out,
+            "Or write the script to a directory {} loads completions from:\n\n    {} completion -s {} > <dir>/{}",
+            self.shell, name, self.shell, file_name
+        )?;
 
         Ok(())
     }
 }
 
+/// The manual setup instructions for the shell, from its `### <shell>` section of the
+/// help.
+fn manual_instructions(help: &str, shell: Shell) -> Option<String> {
+    let heading = format!("### {}", shell).to_lowercase();
+    let section = help
+        .lines()
+        .skip_while(|line| line.trim().to_lowercase() != heading)
+        .skip(1)
+        .take_while(|line| !line.starts_with("###"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let section = section.trim();
+    (!section.is_empty()).then(|| section.to_string())
+}
+
+/// The name of the completion script file for the shell, if we know where that
+/// shell looks for them.
+fn completion_file_name(shell: Shell, name: &str) -> Option<String> {
+    match shell {
+        Shell::Bash => Some(name.to_string()),
+        Shell::Zsh => Some(format!("_{}", name)),
+        Shell::Fish => Some(format!("{}.fish", name)),
+        _ => None,
+    }
+}
+
+/// The directories the shell loads completions from, most preferred first.
+///
+/// For zsh we only use directories that are already on the `$FPATH`, or the usual
+/// `site-functions` directories, and only if they exist. For bash and fish, the user
+/// completion directories are always loaded, so they are created if needed.
+fn completion_dirs(shell: Shell, env: impl Fn(&str) -> Option<String>, home: Option<PathBuf>) -> Vec<PathBuf> {
+    let env = |key: &str| env(key).filter(|v| !v.is_empty());
+    let mut dirs = Vec::new();
+
+    match shell {
+        Shell::Bash => {
+            if let Some(dir) = env("BASH_COMPLETION_USER_DIR") {
+                dirs.push(PathBuf::from(dir).join("completions"));
+            } else if let Some(dir) = env("XDG_DATA_HOME") {
+                dirs.push(PathBuf::from(dir).join("bash-completion").join("completions"));
+            } else if let Some(home) = &home {
+                dirs.push(home.join(".local/share/bash-completion/completions"));
+            }
+        }
+        Shell::Zsh => {
+            let mut candidates: Vec<PathBuf> = env("FPATH")
+                .map(|fpath| fpath.split(':').filter(|d| !d.is_empty()).map(PathBuf::from).collect())
+                .unwrap_or_default();
+            // Prefer the directories in the home directory, they are the user's own.
+            if let Some(home) = &home {
+                candidates.sort_by_key(|d| !d.starts_with(home));
+            }
+            candidates.push(PathBuf::from("/usr/local/share/zsh/site-functions"));
+            candidates.push(PathBuf::from("/opt/homebrew/share/zsh/site-functions"));
+            for dir in candidates {
+                if dir.is_dir() && !dirs.contains(&dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+        Shell::Fish => {
+            if let Some(dir) = env("XDG_CONFIG_HOME") {
+                dirs.push(PathBuf::from(dir).join("fish").join("completions"));
+            } else if let Some(home) = &home {
+                dirs.push(home.join(".config/fish/completions"));
+            }
+        }
+        _ => {}
+    }
+
+    dirs
+}
+
+/// The first line, counting from 1, where two scripts differ, if they do.
+fn first_difference(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    if expected == actual {
+        return None;
+    }
+
+    let expected: Vec<&[u8]> = expected.split(|b| *b == b'\n').collect();
+    let actual: Vec<&[u8]> = actual.split(|b| *b == b'\n').collect();
+    let line = expected
+        .iter()
+        .zip(&actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or_else(|| expected.len().min(actual.len()));
+    Some(line + 1)
+}
+
+/// Write the completion script, creating the directory if needed.
+fn install_script(dir: &Path, path: &Path, script: &[u8]) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, script)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use clap::ValueEnum;
@@ -129,16 +380,17 @@ mod test {
 
             let cmd = crate::cmd_completion::CmdCompletion {
                 shell: clap_complete::Shell::from_str(&t.input, true).unwrap(),
+                install: false,
+                completions_dir: None,
+                check: false,
+                update: false,
+                snapshot: None,
             };
 
             let (io, stdout_path, stderr_path) = crate::iostreams::IoStreams::test();
             let mut config = crate::config::new_blank_config().unwrap();
             let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             cmd.run(&mut ctx).await.unwrap();
 
@@ -152,4 +404,120 @@ mod test {
             assert!(stderr.contains(&t.want_err), "test {}", t.name);
         }
     }
+
+    #[test]
+    fn test_completion_dirs() {
+        let home = Some(std::path::PathBuf::from("/home/zoo"));
+
+        let env = |key: &str| match key {
+            "XDG_DATA_HOME" => Some("/data".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            super::completion_dirs(clap_complete::Shell::Bash, env, home.clone()),
+            vec![std::path::PathBuf::from("/data/bash-completion/completions")]
+        );
+        assert_eq!(
+            super::completion_dirs(clap_complete::Shell::Fish, env, home.clone()),
+            vec![std::path::PathBuf::from("/home/zoo/.config/fish/completions")]
+        );
+        assert!(super::completion_dirs(clap_complete::Shell::PowerShell, env, home.clone()).is_empty());
+
+        // Only existing fpath entries are used for zsh, home directory first.
+        let tmp = tempfile::tempdir().unwrap();
+        let fpath = format!("/does/not/exist:{}", tmp.path().display());
+        let env = |key: &str| if key == "FPATH" { Some(fpath.clone()) } else { None };
+        let dirs = super::completion_dirs(clap_complete::Shell::Zsh, env, Some(tmp.path().to_path_buf()));
+        assert_eq!(dirs[0], tmp.path());
+        assert!(!dirs.contains(&std::path::PathBuf::from("/does/not/exist")));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cmd_completion_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("completions");
+
+        let cmd = crate::cmd_completion::CmdCompletion {
+            shell: clap_complete::Shell::Zsh,
+            install: false,
+            completions_dir: Some(dir.clone()),
+            check: false,
+            update: false,
+            snapshot: None,
+        };
+
+        let (io, stdout_path, _stderr_path) = crate::iostreams::IoStreams::test();
+        let mut config = crate::config::new_blank_config().unwrap();
+        let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+        let mut ctx = crate::context::Context::test(&mut c, io);
+
+        cmd.run(&mut ctx).await.unwrap();
+
+        let script = std::fs::read_to_string(dir.join("_zoo")).unwrap();
+        assert!(script.starts_with("#compdef zoo"));
+
+        let stdout = std::fs::read_to_string(&stdout_path).unwrap();
+        assert!(stdout.contains(&format!("Installed zsh completions to {}", dir.join("_zoo").display())));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cmd_completion_check() {
+        let tmp = tempfile::tempdir().unwrap();
+        let snapshot = tmp.path().join("completions").join("bash");
+
+        let run = |check: bool, update: bool| {
+            let snapshot = snapshot.clone();
+            async move {
+                let cmd = crate::cmd_completion::CmdCompletion {
+                    shell: clap_complete::Shell::Bash,
+                    install: false,
+                    completions_dir: None,
+                    check,
+                    update,
+                    snapshot: Some(snapshot),
+                };
+
+                let (io, _stdout_path, _stderr_path) = crate::iostreams::IoStreams::test();
+                let mut config = crate::config::new_blank_config().unwrap();
+                let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+                let mut ctx = crate::context::Context::test(&mut c, io);
+                cmd.run(&mut ctx).await
+            }
+        };
+
+        // There is nothing to check against yet.
+        assert!(run(true, false).await.is_err());
+
+        run(false, true).await.unwrap();
+        run(true, false).await.unwrap();
+
+        let mut script = std::fs::read_to_string(&snapshot).unwrap();
+        script.insert_str(0, "# an old line\n");
+        std::fs::write(&snapshot, script).unwrap();
+        let err = run(true, false).await.unwrap_err().to_string();
+        assert!(err.contains("changed") && err.contains("at line 1"), "{err}");
+    }
+
+    #[test]
+    fn test_manual_instructions() {
+        use clap::CommandFactory;
+
+        let help = crate::cmd_completion::CmdCompletion::command()
+            .get_long_about()
+            .unwrap()
+            .to_string();
+        let zsh = super::manual_instructions(&help, clap_complete::Shell::Zsh).unwrap();
+        assert!(zsh.contains("zoo completion -s zsh > "), "{zsh}");
+        assert!(!zsh.contains("fish"), "{zsh}");
+        let fish = super::manual_instructions(&help, clap_complete::Shell::Fish).unwrap();
+        assert!(fish.contains("zoo completion -s fish > "), "{fish}");
+        assert!(super::manual_instructions(&help, clap_complete::Shell::Elvish).is_none());
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(super::first_difference(b"a\nb\n", b"a\nb\n"), None);
+        assert_eq!(super::first_difference(b"a\nb\n", b"a\nc\n"), Some(2));
+        assert_eq!(super::first_difference(b"a\n", b"a\nb\n"), Some(2));
+    }
 }
@@ -11,6 +11,9 @@ use clap::Parser;
 /// - pager: the terminal pager program to send standard output to
 /// - browser: the web browser to use for opening URLs
 /// - format: the formatting style for command output
+/// - length_unit, area_unit, volume_unit, mass_unit, density_unit: the default output
+///   units for the measurement commands
+/// - system_prompt: a standing instruction to send ahead of machine learning prompts
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdConfig {
@@ -23,6 +26,7 @@ enum SubCommand {
     Set(CmdConfigSet),
     List(CmdConfigList),
     Get(CmdConfigGet),
+    SetDefaultUnit(CmdConfigSetDefaultUnit),
 }
 
 #[async_trait::async_trait(?Send)]
@@ -32,6 +36,7 @@ impl crate::cmd::Command for CmdConfig {
             SubCommand::Get(cmd) => cmd.run(ctx).await,
             SubCommand::Set(cmd) => cmd.run(ctx).await,
             SubCommand::List(cmd) => cmd.run(ctx).await,
+            SubCommand::SetDefaultUnit(cmd) => cmd.run(ctx).await,
         }
     }
 }
@@ -126,6 +131,46 @@ impl crate::cmd::Command for CmdConfigSet {
     }
 }
 
+/// Set the default output unit for a kind of measurement.
+///
+/// The measurement commands, like `zoo kcl volume` or `zoo file mass`, use this unit
+/// when `--output-unit` is not given.
+///
+///     # output volumes in cubic centimeters by default
+///     $ zoo config set-default-unit volume cm3
+///
+///     # go back to requiring `--output-unit`
+///     $ zoo config set-default-unit volume ""
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdConfigSetDefaultUnit {
+    /// The kind of measurement to set the default unit for.
+    #[clap(name = "measurement", required = true, value_enum)]
+    pub measurement: crate::config::Measurement,
+
+    /// The unit to use, or an empty string to unset the default.
+    #[clap(name = "unit", required = true)]
+    pub unit: String,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdConfigSetDefaultUnit {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let key = self.measurement.config_key();
+        crate::config::validate_value(key, &self.unit)?;
+
+        if let Err(err) = ctx.config.set("", key, Some(&self.unit)) {
+            bail!("{}", err);
+        }
+
+        if let Err(err) = ctx.config.write() {
+            bail!("{}", err);
+        }
+
+        Ok(())
+    }
+}
+
 /// Print a list of configuration keys and values.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
@@ -187,7 +232,7 @@ mod test {
             TestItem {
                 name: "list empty".to_string(),
                 cmd: crate::cmd_config::SubCommand::List(crate::cmd_config::CmdConfigList { host: "".to_string() }),
-                want_out: "the text editor program to use for authoring text\neditor=\n\ntoggle interactive prompting in the terminal\nprompt=enabled\n\nthe terminal pager program to send standard output to\npager=\n\nthe web browser to use for opening URLs\nbrowser=\n\nthe formatting style for command output\nformat=table\n\n".to_string(),
+                want_out: "the text editor program to use for authoring text\neditor=\n\ntoggle interactive prompting in the terminal\nprompt=enabled\n\nthe terminal pager program to send standard output to\npager=\n\nthe web browser to use for opening URLs\nbrowser=\n\nthe formatting style for command output\nformat=table\n\nthe default output unit for lengths\nlength_unit=\n\nthe default output unit for areas\narea_unit=\n\nthe default output unit for volumes\nvolume_unit=\n\nthe default output unit for masses\nmass_unit=\n\nthe default output unit for densities\ndensity_unit=\n\na standing instruction to send ahead of machine learning prompts\nsystem_prompt=\n\n".to_string(),
                 want_err: "".to_string(),
             },
             TestItem {
@@ -247,11 +292,29 @@ mod test {
                 want_out: "".to_string(),
                 want_err: "Key 'blah' not found".to_string(),
             },
+            TestItem {
+                name: "set a default unit".to_string(),
+                cmd: crate::cmd_config::SubCommand::SetDefaultUnit(crate::cmd_config::CmdConfigSetDefaultUnit {
+                    measurement: crate::config::Measurement::Volume,
+                    unit: "cm3".to_string(),
+                }),
+                want_out: "".to_string(),
+                want_err: "".to_string(),
+            },
+            TestItem {
+                name: "get a default unit we set".to_string(),
+                cmd: crate::cmd_config::SubCommand::Get(crate::cmd_config::CmdConfigGet {
+                    key: "volume_unit".to_string(),
+                    host: "".to_string(),
+                }),
+                want_out: "cm3\n".to_string(),
+                want_err: "".to_string(),
+            },
             TestItem {
                 name: "list all default".to_string(),
                 cmd: crate::cmd_config::SubCommand::List(crate::cmd_config::CmdConfigList { host: "".to_string() }),
                 // want_out: "editor=\nprompt=enabled\npager=\nbrowser=bar\nformat=table\n".to_string(),
-                want_out: "the text editor program to use for authoring text\neditor=\n\ntoggle interactive prompting in the terminal\nprompt=enabled\n\nthe terminal pager program to send standard output to\npager=\n\nthe web browser to use for opening URLs\nbrowser=bar\n\nthe formatting style for command output\nformat=table\n\n".to_string(),
+                want_out: "the text editor program to use for authoring text\neditor=\n\ntoggle interactive prompting in the terminal\nprompt=enabled\n\nthe terminal pager program to send standard output to\npager=\n\nthe web browser to use for opening URLs\nbrowser=bar\n\nthe formatting style for command output\nformat=table\n\nthe default output unit for lengths\nlength_unit=\n\nthe default output unit for areas\narea_unit=\n\nthe default output unit for volumes\nvolume_unit=cm3\n\nthe default output unit for masses\nmass_unit=\n\nthe default output unit for densities\ndensity_unit=\n\na standing instruction to send ahead of machine learning prompts\nsystem_prompt=\n\n".to_string(),
                 want_err: "".to_string(),
             },
         ];
@@ -261,11 +324,7 @@ mod test {
 
         for t in tests {
             let (io, stdout_path, stderr_path) = crate::iostreams::IoStreams::test();
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             let cmd_config = crate::cmd_config::CmdConfig { subcmd: t.cmd };
             match cmd_config.run(&mut ctx).await {
@@ -296,11 +355,7 @@ mod test {
         let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
 
         let (io, _stdout_path, _stderr_path) = crate::iostreams::IoStreams::test();
-        let mut ctx = crate::context::Context {
-            config: &mut c,
-            io,
-            debug: false,
-        };
+        let mut ctx = crate::context::Context::test(&mut c, io);
 
         let mut cmd_config = crate::cmd_config::CmdConfig {
             subcmd: crate::cmd_config::SubCommand::Set(crate::cmd_config::CmdConfigSet {
@@ -0,0 +1,310 @@
+use anyhow::Result;
+use clap::Parser;
+use parse_display::Display;
+
+/// Check that `zoo` is set up correctly, and explain how to fix anything that isn't.
+///
+///     # check everything
+///     $ zoo doctor
+///
+///     # get the report as json, for a support request
+///     $ zoo doctor --format json
+///
+/// This checks that the config files parse, that the API host can be reached, that you
+/// are logged in with a token that works, and that there is a browser for `--web`.
+///
+/// The command fails if any of the checks fail. Warnings, like a missing browser, only
+/// matter for some commands, so they don't fail it.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdDoctor {
+    /// Check a specific host, instead of the default one.
+    #[clap(short = 'H', long, env = "ZOO_HOST", value_parser = crate::cmd_auth::parse_host)]
+    pub host: Option<url::Url>,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+}
+
+/// How a check went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, serde::Serialize)]
+#[display(style = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Everything is fine.
+    Pass,
+    /// Some commands won't work, but most will.
+    Warn,
+    /// Most commands won't work until this is fixed.
+    Fail,
+}
+
+/// The result of one check.
+#[derive(Debug, Clone, serde::Serialize, tabled::Tabled)]
+pub struct Check {
+    /// What was checked.
+    pub check: String,
+    /// How it went.
+    pub status: CheckStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix it, or empty if it passed.
+    pub hint: String,
+}
+
+impl Check {
+    fn pass(check: &str, detail: impl Into<String>) -> Self {
+        Self::new(check, CheckStatus::Pass, detail, "")
+    }
+
+    fn new(check: &str, status: CheckStatus, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Check {
+            check: check.to_string(),
+            status,
+            detail: detail.into(),
+            hint: hint.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdDoctor {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let mut checks = vec![check_config_files()];
+
+        let host = match &self.host {
+            Some(host) => Ok(host.to_string()),
+            None => ctx.config.default_host(),
+        };
+        match host {
+            Ok(host) => {
+                checks.push(check_host(ctx, &host).await);
+                checks.push(check_token(ctx, &host).await);
+                checks.push(check_browser(ctx, &host));
+            }
+            Err(err) => checks.push(Check::new(
+                "host",
+                CheckStatus::Fail,
+                format!("no host is configured: {}", err),
+                "run `zoo auth login`",
+            )),
+        }
+
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, &checks)?;
+
+        if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+            return Err(crate::cmd::ExitCode(1).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the config and hosts files parse, if they exist.
+fn check_config_files() -> Check {
+    let files = match (crate::config_file::config_file(), crate::config_file::hosts_file()) {
+        (Ok(config), Ok(hosts)) => [config, hosts],
+        (Err(err), _) | (_, Err(err)) => {
+            return Check::new(
+                "config",
+                CheckStatus::Fail,
+                format!("could not find the config directory: {}", err),
+                "set `ZOO_CONFIG_DIR` to a directory you can write to",
+            )
+        }
+    };
+
+    let mut found = Vec::new();
+    for file in &files {
+        let path = std::path::Path::new(file);
+        if !path.exists() {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(contents.parse::<toml_edit::DocumentMut>()?));
+        if let Err(err) = parsed {
+            return Check::new(
+                "config",
+                CheckStatus::Fail,
+                format!("`{}` is not valid: {}", file, err),
+                format!("fix or remove `{}`, then run `zoo auth login` if needed", file),
+            );
+        }
+        found.push(file.as_str());
+    }
+
+    if found.is_empty() {
+        Check::pass("config", "no config files yet, using the defaults")
+    } else {
+        Check::pass("config", found.join(", "))
+    }
+}
+
+/// Check the API host answers, without needing a token.
+async fn check_host(ctx: &crate::context::Context<'_>, host: &str) -> Check {
+    let result = match ctx.api_client(host) {
+        Ok(client) => client.meta().ping().await.map_err(anyhow::Error::from),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(_) => Check::pass("host", format!("{} is reachable", host)),
+        Err(err) => Check::new(
+            "host",
+            CheckStatus::Fail,
+            format!("could not reach {}: {}", host, err),
+            "check your network connection, proxy settings and `ZOO_HOST`",
+        ),
+    }
+}
+
+/// Check there is a token for the host, and that the API accepts it.
+async fn check_token(ctx: &crate::context::Context<'_>, host: &str) -> Check {
+    let source = match ctx.config.get_with_source(host, "token") {
+        Ok((token, source)) if !token.is_empty() => source,
+        _ => {
+            return Check::new(
+                "token",
+                CheckStatus::Fail,
+                format!("not logged in to {}", host),
+                "run `zoo auth login`, or set `ZOO_TOKEN`",
+            )
+        }
+    };
+
+    let result = match ctx.api_client(host) {
+        Ok(client) => client.users().get_self().await.map_err(anyhow::Error::from),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(user) => Check::pass(
+            "token",
+            format!(
+                "logged in as {} (from {})",
+                user.email.unwrap_or_else(|| user.id.to_string()),
+                source
+            ),
+        ),
+        Err(err) => Check::new(
+            "token",
+            CheckStatus::Fail,
+            format!("the token from {} was not accepted: {}", source, err),
+            "the token may have expired or been revoked, run `zoo auth login`",
+        ),
+    }
+}
+
+/// Check there is a browser to open for `--web`, in the same order `Context::browser`
+/// looks for one.
+fn check_browser(ctx: &crate::context::Context<'_>, host: &str) -> Check {
+    let configured = ["ZOO_BROWSER", "BROWSER"]
+        .iter()
+        .map(|key| (crate::config_file::get_env_var(key), key.to_string()))
+        .chain(std::iter::once((
+            ctx.config.get(host, "browser").unwrap_or_default(),
+            "the config".to_string(),
+        )))
+        .find(|(browser, _)| !browser.is_empty());
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let (command, hint) = match &configured {
+        // On macOS the browser is an application name for `open -a`, not a command.
+        Some((browser, source)) if cfg!(target_os = "macos") => {
+            return Check::pass("browser", format!("{} (from {})", browser, source))
+        }
+        Some((browser, source)) => (
+            shlex::split(browser)
+                .and_then(|args| args.into_iter().next())
+                .unwrap_or_else(|| browser.to_string()),
+            format!("the browser from {} was not found, fix it or unset it", source),
+        ),
+        None => match system_opener() {
+            Some(opener) => (
+                opener.to_string(),
+                format!(
+                    "install `{}`, or set `BROWSER` to the browser to use for `--web`",
+                    opener
+                ),
+            ),
+            // The system knows how to open urls without a command.
+            None => return Check::pass("browser", "the system default"),
+        },
+    };
+
+    match find_in_path(&command, &path) {
+        Some(found) => Check::pass("browser", found.display().to_string()),
+        None => Check::new(
+            "browser",
+            CheckStatus::Warn,
+            format!("`{}` was not found", command),
+            hint,
+        ),
+    }
+}
+
+/// The command used to open urls on this system, if it needs one.
+fn system_opener() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("open")
+    } else if cfg!(windows) {
+        None
+    } else {
+        Some("xdg-open")
+    }
+}
+
+/// Find a command the way the shell would, either as a path or in `path`.
+fn find_in_path(command: &str, path: &std::ffi::OsStr) -> Option<std::path::PathBuf> {
+    let command = std::path::Path::new(command);
+    if command.components().count() > 1 {
+        return command.is_file().then(|| command.to_path_buf());
+    }
+
+    std::env::split_paths(path).find_map(|dir| {
+        let candidate = dir.join(command);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        let candidate = candidate.with_extension("exe");
+        (cfg!(windows) && candidate.is_file()).then_some(candidate)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_find_in_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let browser = dir.path().join("my-browser");
+        std::fs::write(&browser, "").unwrap();
+
+        let path = std::env::join_paths(["/does/not/exist".into(), dir.path().to_path_buf()]).unwrap();
+        assert_eq!(find_in_path("my-browser", &path), Some(browser.clone()));
+        assert_eq!(find_in_path("not-a-browser", &path), None);
+
+        // Paths are used as they are.
+        assert_eq!(find_in_path(browser.to_str().unwrap(), &path), Some(browser.clone()));
+        assert_eq!(find_in_path("/does/not/exist/my-browser", &path), None);
+    }
+
+    #[test]
+    fn test_check_output() {
+        let check = Check::new("token", CheckStatus::Fail, "not logged in", "run `zoo auth login`");
+        assert_eq!(
+            serde_json::to_value(&check).unwrap(),
+            serde_json::json!({
+                "check": "token",
+                "status": "fail",
+                "detail": "not logged in",
+                "hint": "run `zoo auth login`",
+            })
+        );
+        assert_eq!(CheckStatus::Warn.to_string(), "warn");
+    }
+}
@@ -31,6 +31,7 @@ enum SubCommand {
     CenterOfMass(CmdFileCenterOfMass),
     Density(CmdFileDensity),
     SurfaceArea(CmdFileSurfaceArea),
+    Formats(CmdFileFormats),
 }
 
 #[async_trait::async_trait(?Send)]
@@ -39,11 +40,12 @@ impl crate::cmd::Command for CmdFile {
         match &self.subcmd {
             SubCommand::Convert(cmd) => cmd.run(ctx).await,
             SubCommand::Snapshot(cmd) => cmd.run(ctx).await,
-            SubCommand::Volume(cmd) => cmd.run(ctx).await,
-            SubCommand::Mass(cmd) => cmd.run(ctx).await,
-            SubCommand::CenterOfMass(cmd) => cmd.run(ctx).await,
-            SubCommand::Density(cmd) => cmd.run(ctx).await,
-            SubCommand::SurfaceArea(cmd) => cmd.run(ctx).await,
+            SubCommand::Volume(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Mass(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::CenterOfMass(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Density(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::SurfaceArea(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Formats(cmd) => cmd.run(ctx).await,
         }
     }
 }
@@ -60,28 +62,48 @@ impl crate::cmd::Command for CmdFile {
 ///     # convert obj to step
 ///     $ zoo file convert --output-format=step my-obj.obj .
 ///
+///     # convert step to obj, taking the output format from the output file
+///     $ zoo file convert my-file.step my-file.obj
+///
 ///     # pass a file to convert from stdin
 ///     # when converting from stdin, the original file type is required
 ///     $ cat my-obj.obj | zoo file convert --output-format=step - output_dir
+///
+///     # convert many files, and keep going if some of them fail
+///     $ zoo file convert --keep-going --output-format=obj *.step output_dir
+///
+///     # convert files with the same contents only once
+///     $ zoo file convert --dedupe --output-format=obj dataset/*.step output_dir
+///
+///     # convert a library of parts, keeping its folders, so library/gears/spur.step
+///     # is converted into output_dir/gears/
+///     $ zoo file convert --preserve-tree --output-format=obj library/*/*.step output_dir
+///
+/// When converting more than one file, a summary of each conversion is printed to stderr.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdFileConvert {
-    /// The path to the input file to convert.
+    /// The paths to the input files to convert.
     /// If you pass `-` as the path, the file will be read from stdin.
-    #[clap(name = "input", required = true)]
-    pub input: std::path::PathBuf,
+    #[clap(name = "input", required = true, num_args = 1..)]
+    pub input: Vec<std::path::PathBuf>,
 
-    /// The path to a directory to output the files.
+    /// The path to a directory to output the files, or to a single output file.
+    /// If it is a file, the output format is taken from its extension, so
+    /// `--output-format` is not needed, and only one input can be converted.
     #[clap(name = "output-dir", required = true)]
     pub output_dir: std::path::PathBuf,
 
     /// A valid source file format.
+    /// If not set, this is taken from the file extension, or for files without one (like
+    /// stdin), guessed from the contents of the file.
     #[clap(short = 's', long = "src-format", value_enum)]
     src_format: Option<kittycad::types::FileImportFormat>,
 
     /// A valid output file format.
+    /// This is required if the output is a directory.
     #[clap(short = 't', long = "output-format", value_enum)]
-    output_format: kittycad::types::FileExportFormat,
+    output_format: Option<kittycad::types::FileExportFormat>,
 
     /// Command output format.
     #[clap(long, short, value_enum)]
@@ -92,48 +114,298 @@ pub struct CmdFileConvert {
     /// This is useful for when pushing to version control.
     #[clap(long, default_value = "false")]
     pub deterministic: bool,
+
+    /// Keep converting the rest of the files if one of them fails, instead of stopping
+    /// at the first failure. The command still fails if any of the conversions failed.
+    #[clap(long, default_value = "false")]
+    pub keep_going: bool,
+
+    /// Only convert each distinct input once. Inputs with the same contents and source
+    /// format as one already converted are not sent to the API again, instead the files
+    /// converted for the first one are written for them too.
+    #[clap(long, default_value = "false")]
+    pub dedupe: bool,
+
+    /// Write the outputs of each input into the same folders under the output directory
+    /// as the input is in, relative to the deepest folder all the inputs are in, instead
+    /// of all into the output directory. The folders are created as needed.
+    #[clap(long, default_value = "false")]
+    pub preserve_tree: bool,
+
+    /// How many times to upload an input again if the upload fails before the server
+    /// started converting it, like a connection that couldn't be made or being rate
+    /// limited, waiting longer between each attempt. Other failures are not retried, since
+    /// the conversion may already have been done and billed. The API can't resume an
+    /// upload, so each attempt sends the whole file. Use `--debug` to see the uploads and
+    /// retries.
+    #[clap(long, default_value_t = 0)]
+    pub upload_retries: u32,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
+}
+
+/// A conversion already done in this batch, for `--dedupe`.
+struct ConvertedInput {
+    /// The input that was converted.
+    input: std::path::PathBuf,
+    /// The conversion, with its outputs, so they can be written again for duplicates.
+    conversion: kittycad::types::FileConversion,
+}
+
+/// The result of converting a single file, for the summary.
+#[derive(Debug, Clone, tabled::Tabled)]
+struct ConvertSummary {
+    input: String,
+    status: String,
+    error: String,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileConvert {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Make sure the output dir is a directory.
-        if !self.output_dir.is_dir() {
-            anyhow::bail!(
-                "output directory `{}` does not exist or is not a directory",
-                self.output_dir.to_str().unwrap_or("")
+        let (output_format, output_file) =
+            resolve_convert_output(&self.output_dir, self.output_dir.is_dir(), &self.output_format)?;
+        if output_file.is_some() && self.input.len() > 1 {
+            anyhow::bail!("only one input can be converted to an output file, use an output directory instead");
+        }
+        if output_file.is_some() && self.preserve_tree {
+            anyhow::bail!("`--preserve-tree` can only be used with an output directory");
+        }
+        let input_root = self.preserve_tree.then(|| common_input_root(&self.input));
+
+        let timeout_total = self.timeouts.total(self.input.len());
+        let deadline = timeout_total.map(|timeout_total| tokio::time::Instant::now() + timeout_total);
+
+        let mut summary = Vec::new();
+        let mut converted = std::collections::HashMap::new();
+        let mut deduped = 0;
+        for (i, input) in self.input.iter().enumerate() {
+            let remaining = deadline.map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()));
+            if let (Some(timeout_total), Some(remaining)) = (timeout_total, remaining) {
+                if remaining.is_zero() {
+                    let skipped = &self.input[i..];
+                    if !self.keep_going {
+                        anyhow::bail!(
+                            "converting all the inputs timed out after {:?}, {} were not converted",
+                            timeout_total,
+                            skipped.len()
+                        );
+                    }
+                    summary.extend(skipped.iter().map(|input| ConvertSummary {
+                        input: input.display().to_string(),
+                        status: "skipped".to_string(),
+                        error: format!("converting all the inputs timed out after {:?}", timeout_total),
+                    }));
+                    break;
+                }
+            }
+
+            let output_dir = match &input_root {
+                Some(root) => tree_output_dir(&self.output_dir, root, input),
+                None => self.output_dir.clone(),
+            };
+            let convert = self.convert(
+                ctx,
+                input,
+                &output_format,
+                &output_dir,
+                output_file.as_deref(),
+                &mut converted,
             );
+            let per_file = self.timeouts.timeout_per_file;
+            let result = match [per_file, remaining].into_iter().flatten().min() {
+                Some(timeout) => tokio::time::timeout(timeout, convert).await.unwrap_or_else(|_| {
+                    if per_file.is_some_and(|per_file| timeout >= per_file) {
+                        Err(anyhow::anyhow!(
+                            "converting `{}` timed out after {:?}",
+                            input.display(),
+                            timeout
+                        ))
+                    } else {
+                        Err(anyhow::anyhow!(
+                            "converting all the inputs timed out after {:?}",
+                            timeout_total.unwrap_or(timeout)
+                        ))
+                    }
+                }),
+                None => convert.await,
+            };
+            match result {
+                Ok(None) => summary.push(ConvertSummary {
+                    input: input.display().to_string(),
+                    status: "ok".to_string(),
+                    error: String::new(),
+                }),
+                Ok(Some(original)) => {
+                    deduped += 1;
+                    summary.push(ConvertSummary {
+                        input: input.display().to_string(),
+                        status: format!("ok (same as {})", original.display()),
+                        error: String::new(),
+                    });
+                }
+                Err(err) if self.keep_going => summary.push(ConvertSummary {
+                    input: input.display().to_string(),
+                    status: "failed".to_string(),
+                    error: err.to_string(),
+                }),
+                Err(err) => return Err(err),
+            }
         }
 
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
+        if summary.len() > 1 || self.keep_going {
+            // Keep stdout clean for the output of the conversions.
+            let table = tabled::Table::new(&summary)
+                .with(tabled::settings::Style::psql())
+                .to_string();
+            writeln!(ctx.io.err_out, "{table}")?;
+        }
+        if self.dedupe {
+            writeln!(
+                ctx.io.err_out,
+                "Skipped {} conversion{} of duplicate inputs",
+                deduped,
+                if deduped == 1 { "" } else { "s" }
+            )?;
+        }
+
+        let failed = summary.iter().filter(|s| !s.status.starts_with("ok")).count();
+        if failed > 0 {
+            anyhow::bail!("{} of {} conversions failed", failed, summary.len());
+        }
+
+        Ok(())
+    }
+}
 
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+impl CmdFileConvert {
+    /// Convert a single input file, writing the outputs to `output_dir`.
+    /// If `output_file` is given, the output with its extension is written there instead,
+    /// and any other outputs (like the `.bin` of a glTF) next to it.
+    /// With `--dedupe`, the conversions done so far are in `converted`, and if the input
+    /// is the same as one of them, its outputs are written again instead of converting it,
+    /// and the input it is the same as is returned.
+    async fn convert(
+        &self,
+        ctx: &mut crate::context::Context<'_>,
+        input_path: &std::path::Path,
+        output_format: &kittycad::types::FileExportFormat,
+        output_dir: &std::path::Path,
+        output_file: Option<&std::path::Path>,
+        converted: &mut std::collections::HashMap<String, ConvertedInput>,
+    ) -> Result<Option<std::path::PathBuf>> {
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, input_path)?;
+        if self.preserve_tree {
+            std::fs::create_dir_all(output_dir)?;
+        }
+
+        let key = self.dedupe.then(|| dedupe_key(&input, &src_format));
+        if let Some(original) = key.as_ref().and_then(|key| converted.get(key)) {
+            self.write_outputs(ctx, &original.conversion, output_format, output_dir, output_file)?;
+            return Ok(Some(original.input.clone()));
+        }
 
         // Do the conversion.
         let client = ctx.api_client("")?;
 
         // Create the file conversion.
-        let mut file_conversion = client
-            .file()
-            .create_conversion(self.output_format.clone(), src_format, &input.into())
-            .await?;
+        let mut attempt = 0;
+        let file_conversion = loop {
+            if ctx.debug {
+                writeln!(
+                    ctx.io.err_out,
+                    "uploading {} ({} bytes)",
+                    input_path.display(),
+                    input.len()
+                )?;
+            }
+            let start = std::time::Instant::now();
+            match client
+                .file()
+                .create_conversion(output_format.clone(), src_format.clone(), &input.clone().into())
+                .await
+            {
+                Ok(file_conversion) => {
+                    if ctx.debug {
+                        writeln!(
+                            ctx.io.err_out,
+                            "converted {} in {:?}",
+                            input_path.display(),
+                            start.elapsed()
+                        )?;
+                    }
+                    break file_conversion;
+                }
+                Err(err) if attempt < self.upload_retries && is_retryable(&err) => {
+                    attempt += 1;
+                    let backoff = upload_backoff(attempt);
+                    if ctx.debug {
+                        writeln!(
+                            ctx.io.err_out,
+                            "upload attempt {}/{} for {} failed, retrying in {:?}: {}",
+                            attempt,
+                            self.upload_retries + 1,
+                            input_path.display(),
+                            backoff,
+                            err
+                        )?;
+                    }
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        };
+        self.write_outputs(ctx, &file_conversion, output_format, output_dir, output_file)?;
+
+        if let Some(key) = key {
+            converted.insert(
+                key,
+                ConvertedInput {
+                    input: input_path.to_path_buf(),
+                    conversion: file_conversion,
+                },
+            );
+        }
+
+        Ok(None)
+    }
 
+    /// Write the outputs of a file conversion, then print the conversion.
+    fn write_outputs(
+        &self,
+        ctx: &mut crate::context::Context<'_>,
+        file_conversion: &kittycad::types::FileConversion,
+        output_format: &kittycad::types::FileExportFormat,
+        output_dir: &std::path::Path,
+        output_file: Option<&std::path::Path>,
+    ) -> Result<()> {
         // If they specified an output file, save the output to that file.
         if file_conversion.status == kittycad::types::ApiCallStatus::Completed {
-            if let Some(outputs) = file_conversion.outputs {
+            if let Some(outputs) = &file_conversion.outputs {
+                let main_output = output_file.and_then(|_| main_output_name(outputs.keys(), output_format));
+                if let (Some(output_file), None) = (output_file, main_output) {
+                    let mut names = outputs.keys().map(|name| format!("`{}`", name)).collect::<Vec<_>>();
+                    names.sort();
+                    anyhow::bail!(
+                        "none of the outputs is a {} file to write to `{}`, the conversion made {}, pass an output directory to keep them",
+                        output_format,
+                        output_file.display(),
+                        names.join(", ")
+                    );
+                }
                 // Write the contents of the files to the output directory.
                 for (filename, data) in outputs.iter() {
-                    let path = self.output_dir.clone().join(filename);
+                    let path = match output_file {
+                        Some(output_file) if main_output == Some(filename) => output_file.to_path_buf(),
+                        Some(output_file) => output_file.with_file_name(filename),
+                        None => output_dir.join(filename),
+                    };
                     if self.deterministic {
                         write_deterministic_export(&path, &data.0)?;
                     } else {
-                        std::fs::write(&path, data)?;
+                        std::fs::write(&path, &data.0)?;
                     }
                     writeln!(
                         ctx.io.out,
@@ -147,11 +419,12 @@ impl crate::cmd::Command for CmdFileConvert {
             }
         }
 
-        // Reset the outputs field of the file conversion.
+        // Print the output of the conversion, without the outputs field.
         // Otherwise what we print will be crazy big.
-        file_conversion.outputs = None;
-
-        // Print the output of the conversion.
+        let file_conversion = kittycad::types::FileConversion {
+            outputs: None,
+            ..file_conversion.clone()
+        };
         let format = ctx.format(&self.format)?;
         ctx.io.write_output(&format, &file_conversion)?;
 
@@ -159,6 +432,126 @@ impl crate::cmd::Command for CmdFileConvert {
     }
 }
 
+/// The `--timeout-per-file` and `--timeout-total` flags of the commands that work on files.
+/// Neither is set by default, so nothing times out unless asked to.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct Timeouts {
+    /// The longest to spend on each input, like `90s`, `10m` or `1h`, including any
+    /// retries. An input that takes longer fails, so with `zoo file convert --keep-going`
+    /// one slow file doesn't hold up the rest.
+    #[clap(long, value_parser = parse_timeout)]
+    pub timeout_per_file: Option<std::time::Duration>,
+
+    /// The longest to spend on all the inputs, like `30m`. When it runs out, the input
+    /// being worked on fails and the rest are skipped.
+    /// If only `--timeout-per-file` is set, this is that times the number of inputs.
+    #[clap(long, value_parser = parse_timeout)]
+    pub timeout_total: Option<std::time::Duration>,
+}
+
+impl Timeouts {
+    /// The longest to spend on `inputs` inputs altogether, if there is a limit.
+    fn total(&self, inputs: usize) -> Option<std::time::Duration> {
+        self.timeout_total.or_else(|| {
+            self.timeout_per_file
+                .map(|per_file| per_file.saturating_mul(inputs as u32))
+        })
+    }
+
+    /// Run a command on a single input, failing if it takes longer than either timeout.
+    pub async fn run_one<T>(
+        &self,
+        input: &std::path::Path,
+        run: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        match [self.timeout_per_file, self.timeout_total].into_iter().flatten().min() {
+            Some(timeout) => tokio::time::timeout(timeout, run)
+                .await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("`{}` timed out after {:?}", input.display(), timeout))),
+            None => run.await,
+        }
+    }
+}
+
+/// Parse a timeout like `90s`, `10m` or `1h`.
+pub(crate) fn parse_timeout(s: &str) -> Result<std::time::Duration> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(digits);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid timeout `{}`, expected one like `90s` or `10m`", s))?;
+
+    let seconds = match unit.trim() {
+        "s" => Some(number),
+        "m" => number.checked_mul(60),
+        "h" => number.checked_mul(60 * 60),
+        unit => anyhow::bail!("invalid timeout unit `{}`, expected one of s, m or h", unit),
+    };
+    seconds
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| anyhow::anyhow!("timeout `{}` is too large", s))
+}
+
+/// If a conversion request failed before the server started on it, so sending it again
+/// can't convert (and bill for) the same file twice. That is a connection that was never
+/// made, or the server turning the request away because of the rate limit. A timeout or
+/// a server error might come after the conversion was done, so those are not retried.
+fn is_retryable(err: &kittycad::types::error::Error) -> bool {
+    if err.status() == Some(http::StatusCode::TOO_MANY_REQUESTS) {
+        return true;
+    }
+    std::iter::successors(Some(err as &(dyn std::error::Error + 'static)), |err| err.source()).any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|err| err.is_connect())
+    })
+}
+
+/// How long to wait before the `attempt`th retry of an upload. This backs off
+/// exponentially, but never waits more than a minute.
+fn upload_backoff(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(2u64.saturating_pow(attempt)).min(std::time::Duration::from_secs(60))
+}
+
+/// The deepest directory all the inputs are in, for `--preserve-tree`.
+/// Stdin has no directory, so it is left out.
+fn common_input_root(inputs: &[std::path::PathBuf]) -> std::path::PathBuf {
+    let mut root: Option<std::path::PathBuf> = None;
+    for input in inputs.iter().filter(|input| input.to_str() != Some("-")) {
+        let parent = input.parent().unwrap_or(std::path::Path::new(""));
+        root = Some(match root {
+            None => parent.to_path_buf(),
+            Some(root) => root
+                .components()
+                .zip(parent.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    root.unwrap_or_default()
+}
+
+/// The directory to write the outputs of an input to with `--preserve-tree`: the same
+/// directory relative to `output_dir` as the input is relative to `root`.
+fn tree_output_dir(
+    output_dir: &std::path::Path,
+    root: &std::path::Path,
+    input: &std::path::Path,
+) -> std::path::PathBuf {
+    match input.parent().and_then(|parent| parent.strip_prefix(root).ok()) {
+        Some(relative) => output_dir.join(relative),
+        None => output_dir.to_path_buf(),
+    }
+}
+
+/// The key for `--dedupe`: a hash of the contents of an input, along with its format.
+fn dedupe_key(input: &[u8], src_format: &kittycad::types::FileImportFormat) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, input);
+    format!("{:?}:{}", src_format, data_encoding::HEXLOWER.encode(digest.as_ref()))
+}
+
 /// Snapshot a render of a CAD file as any supported image format.
 ///
 ///     # snapshot as png
@@ -166,15 +559,27 @@ impl crate::cmd::Command for CmdFileConvert {
 ///
 ///     # pass a file to snapshot from stdin
 ///     $ cat my-obj.obj | zoo file snapshot --output-format=png - my-file.png
+///
+///     # snapshot several files together, to see how they fit
+///     $ zoo file snapshot bracket.step bolt.step combined.png
+///
+///     # spread them out along x, 50mm apart
+///     $ zoo file snapshot --offset 50,0,0 bracket.step bolt.step combined.png
+///
+///     # render at 1920x1080
+///     $ zoo file snapshot --width 1920 --height 1080 my-file.obj my-file.png
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdFileSnapshot {
-    /// The path to the input file to snapshot.
-    /// If you pass `-` as the path, the file will be read from stdin.
-    #[clap(name = "input", required = true)]
-    pub input: std::path::PathBuf,
+    /// The paths to the input files to snapshot. Several files are imported into the same
+    /// scene and snapshotted together.
+    /// If you pass `-` as a path, that file will be read from stdin.
+    #[clap(name = "input", required = true, num_args = 1..)]
+    pub input: Vec<std::path::PathBuf>,
 
     /// A valid source file format.
+    /// If not set, this is taken from the file extension, or for files without one (like
+    /// stdin), guessed from the contents of the file.
     #[clap(short = 's', long = "src-format", value_enum)]
     src_format: Option<kittycad::types::FileImportFormat>,
 
@@ -186,6 +591,21 @@ pub struct CmdFileSnapshot {
     #[clap(short = 't', long = "output-format", value_enum)]
     output_format: Option<kittycad::types::ImageFormat>,
 
+    /// How far to move each input from the one before it, as `x,y,z` in millimeters.
+    /// By default every input is left where it is.
+    #[clap(long, value_parser = parse_offset)]
+    pub offset: Option<kcmc::shared::Point3d<f64>>,
+
+    /// The width of the image, in pixels, up to 4096.
+    /// If only one of `--width` and `--height` is given, the other keeps a 16:9 aspect
+    /// ratio. This defaults to the engine's resolution.
+    #[clap(long)]
+    pub width: Option<u32>,
+
+    /// The height of the image, in pixels, up to 4096.
+    #[clap(long)]
+    pub height: Option<u32>,
+
     /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
@@ -213,102 +633,54 @@ impl crate::cmd::Command for CmdFileSnapshot {
         } else {
             crate::cmd_kcl::get_image_format_from_extension(&crate::cmd_file::get_extension(self.output_file.clone()))?
         };
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
+        if self.input.iter().filter(|input| input.to_str() == Some("-")).count() > 1 {
+            anyhow::bail!("only one input can be read from stdin");
+        }
+        ctx.video_resolution = crate::cmd_kcl::video_resolution(self.width, self.height)?;
+
+        // Read all the inputs before connecting, so a bad file is reported first.
+        let mut imports = Vec::with_capacity(self.input.len());
+        for input_path in &self.input {
+            // Get the contents of the input file and its source format.
+            let (input, src_format) = read_input_with_format(ctx, &self.src_format, input_path)?;
+
+            // TODO: let user choose the units.
+            let src_format = get_input_format(src_format, kittycad::types::UnitLength::Mm)?;
+            let filename = input_path.file_name().unwrap_or_default().to_str().unwrap_or("");
+
+            let parent = input_path.parent().unwrap_or(std::path::Path::new("")).to_path_buf();
+            let files = import_files(filename, input, &src_format, |uri| {
+                ctx.read_file(parent.join(uri).to_str().unwrap_or_default())
+            })?;
+            imports.push((files, src_format));
+        }
 
-        // TODO: let user choose the units.
-        let src_format = get_input_format(src_format, kittycad::types::UnitLength::Mm)?;
-
-        // Get the contents of the input file.
-        let file_location_str = self.input.to_str().unwrap_or_default();
-        let input = ctx.read_file(file_location_str)?;
-        let filename = self.input.file_name().unwrap_or_default().to_str().unwrap_or("");
-
-        // gltf with "standard" storage is an oddball in the KittyCAD system.
-        // In order for the program to know it's dealing with this type, an
-        // attempt to parse as json is made, then we check for the buffers
-        // property which describes what external files are needed.
-        let mut files: Vec<kittycad::types::ImportFile> = vec![kittycad::types::ImportFile {
-            path: filename.to_string(),
-            data: input.clone(),
-        }];
-
-        if let kittycad::types::InputFormat::Gltf {} = src_format {
-            if let Ok(str) = std::str::from_utf8(&input) {
-                if let Ok(json) = serde_json::from_str::<crate::types::GltfStandardJsonLite>(str) {
-                    // Use the path of the control file as the prefix path of
-                    // the relative file name.
-
-                    for buffer in json.buffers {
-                        if is_data_uri(&buffer.uri) {
-                            // Using the whole data URI would create massive
-                            // path properties. Use a hash instead.
-                            let mut hasher = DefaultHasher::new();
-                            buffer.uri.hash(&mut hasher);
-                            let hash_u64 = hasher.finish();
-
-                            if let Some(buf_base64) = buffer.uri.split(',').nth(1) {
-                                files.push(kittycad::types::ImportFile {
-                                    path: hash_u64.to_string(),
-                                    data: BASE64_STANDARD.decode(buf_base64)?,
-                                });
-                            } else {
-                                anyhow::bail!("invalid data uri in gltf.buffers.uri property");
-                            }
-                        } else {
-                            let path_ = self
-                                .input
-                                .parent()
-                                .unwrap_or(std::path::Path::new(""))
-                                .join(std::path::Path::new(&buffer.uri));
-                            let path = path_.to_str().unwrap_or_default();
-                            let data = ctx.read_file(path)?;
-                            files.push(kittycad::types::ImportFile {
-                                path: path_.file_name().unwrap_or_default().to_str().unwrap_or("").to_string(),
-                                data,
-                            });
-                        }
-                    }
+        let engine = ctx.engine("", None).await?;
+        let mut object_ids = Vec::with_capacity(imports.len());
+        for (i, (files, src_format)) in imports.into_iter().enumerate() {
+            let object_id = import_into_engine(&engine, files, src_format).await?;
+            if let Some(offset) = &self.offset {
+                if i > 0 {
+                    move_object(&engine, object_id, offset, i as f64).await?;
                 }
             }
+            object_ids.push(object_id);
         }
 
-        let engine = ctx.engine("", None).await?;
-
-        // Send an import request to the engine.
-        let resp = engine
-            .send_modeling_cmd(
-                uuid::Uuid::new_v4(),
-                kcl_lib::SourceRange::default(),
-                kcmc::ModelingCmd::ImportFiles(kcmc::ImportFiles {
-                    files: files.into_iter().map(|f| f.into()).collect(),
-                    format: src_format.into(),
-                }),
-            )
-            .await?;
-
-        let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::ImportFiles(data),
-        } = &resp
-        else {
-            anyhow::bail!("Unexpected response from engine import: {:?}", resp);
+        // Zoom on the objects.
+        let zoom = if let [object_id] = object_ids[..] {
+            kittycad_modeling_cmds::ModelingCmd::DefaultCameraFocusOn(kittycad_modeling_cmds::DefaultCameraFocusOn {
+                uuid: object_id,
+            })
+        } else {
+            kittycad_modeling_cmds::ModelingCmd::ZoomToFit(kittycad_modeling_cmds::ZoomToFit {
+                animated: false,
+                object_ids,
+                padding: crate::context::DEFAULT_ZOOM_PADDING,
+            })
         };
-
-        let object_id = data.object_id;
-
-        // Zoom on the object.
         engine
-            .send_modeling_cmd(
-                uuid::Uuid::new_v4(),
-                kcl_lib::SourceRange::default(),
-                kittycad_modeling_cmds::ModelingCmd::DefaultCameraFocusOn(
-                    kittycad_modeling_cmds::DefaultCameraFocusOn { uuid: object_id },
-                ),
-            )
+            .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), zoom)
             .await?;
 
         // Spin up websockets and do the conversion.
@@ -330,7 +702,7 @@ impl crate::cmd::Command for CmdFileSnapshot {
             // Save the snapshot locally.
             std::fs::write(&self.output_file, &data.contents.0)?;
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            return Err(crate::engine_response::unexpected("a snapshot", &resp));
         }
 
         writeln!(
@@ -363,6 +735,8 @@ pub struct CmdFileVolume {
     pub input: std::path::PathBuf,
 
     /// A valid source file format.
+    /// If not set, this is taken from the file extension, or for files without one (like
+    /// stdin), guessed from the contents of the file.
     #[clap(short = 's', long = "src-format", value_enum)]
     src_format: Option<kittycad::types::FileImportFormat>,
 
@@ -371,29 +745,28 @@ pub struct CmdFileVolume {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitVolume,
+    /// Defaults to the unit set with `zoo config set-default-unit volume`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitVolume>::new())]
+    pub output_unit: Option<kittycad::types::UnitVolume>,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileVolume {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Volume)?;
 
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, &self.input)?;
 
         // Do the operation.
         let client = ctx.api_client("")?;
 
         let file_volume = client
             .file()
-            .create_volume(Some(self.output_unit.clone()), src_format, &input.into())
+            .create_volume(Some(output_unit), src_format, &input.into())
             .await?;
 
         // Print the output of the conversion.
@@ -424,6 +797,8 @@ pub struct CmdFileMass {
     pub input: std::path::PathBuf,
 
     /// A valid source file format.
+    /// If not set, this is taken from the file extension, or for files without one (like
+    /// stdin), guessed from the contents of the file.
     #[clap(short = 's', long = "src-format", value_enum)]
     src_format: Option<kittycad::types::FileImportFormat>,
 
@@ -432,7 +807,7 @@ pub struct CmdFileMass {
     material_density: f32,
 
     /// Material density unit.
-    #[clap(long = "material-density-unit", value_enum)]
+    #[clap(long = "material-density-unit", value_parser = crate::units::UnitParser::<kittycad::types::UnitDensity>::new())]
     material_density_unit: kittycad::types::UnitDensity,
 
     /// Output format.
@@ -440,26 +815,25 @@ pub struct CmdFileMass {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitMass,
+    /// Defaults to the unit set with `zoo config set-default-unit mass`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitMass>::new())]
+    pub output_unit: Option<kittycad::types::UnitMass>,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileMass {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Mass)?;
+
         if self.material_density == 0.0 {
             anyhow::bail!("`--material-density` must not be 0.0");
         }
 
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
-
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, &self.input)?;
 
         // Do the operation.
         let client = ctx.api_client("")?;
@@ -469,7 +843,7 @@ impl crate::cmd::Command for CmdFileMass {
             .create_mass(
                 self.material_density.into(),
                 Some(self.material_density_unit.clone()),
-                Some(self.output_unit.clone()),
+                Some(output_unit),
                 src_format,
                 &input.into(),
             )
@@ -503,6 +877,8 @@ pub struct CmdFileCenterOfMass {
     pub input: std::path::PathBuf,
 
     /// A valid source file format.
+    /// If not set, this is taken from the file extension, or for files without one (like
+    /// stdin), guessed from the contents of the file.
     #[clap(short = 's', long = "src-format", value_enum)]
     src_format: Option<kittycad::types::FileImportFormat>,
 
@@ -511,29 +887,28 @@ pub struct CmdFileCenterOfMass {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitLength,
+    /// Defaults to the unit set with `zoo config set-default-unit length`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub output_unit: Option<kittycad::types::UnitLength>,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileCenterOfMass {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Length)?;
 
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, &self.input)?;
 
         // Do the operation.
         let client = ctx.api_client("")?;
 
         let file_center_of_mass = client
             .file()
-            .create_center_of_mass(Some(self.output_unit.clone()), src_format, &input.into())
+            .create_center_of_mass(Some(output_unit), src_format, &input.into())
             .await?;
 
         // Print the output of the conversion.
@@ -572,7 +947,7 @@ pub struct CmdFileDensity {
     material_mass: f32,
 
     /// The unit of the material mass.
-    #[clap(long = "material-mass-unit", value_enum)]
+    #[clap(long = "material-mass-unit", value_parser = crate::units::UnitParser::<kittycad::types::UnitMass>::new())]
     material_mass_unit: kittycad::types::UnitMass,
 
     /// Output format.
@@ -580,26 +955,25 @@ pub struct CmdFileDensity {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitDensity,
+    /// Defaults to the unit set with `zoo config set-default-unit density`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitDensity>::new())]
+    pub output_unit: Option<kittycad::types::UnitDensity>,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileDensity {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Density)?;
+
         if self.material_mass == 0.0 {
             anyhow::bail!("`--material-mass` must not be 0.0");
         }
 
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
-
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, &self.input)?;
 
         // Do the operation.
         let client = ctx.api_client("")?;
@@ -609,7 +983,7 @@ impl crate::cmd::Command for CmdFileDensity {
             .create_density(
                 self.material_mass.into(),
                 Some(self.material_mass_unit.clone()),
-                Some(self.output_unit.clone()),
+                Some(output_unit),
                 src_format,
                 &input.into(),
             )
@@ -651,29 +1025,28 @@ pub struct CmdFileSurfaceArea {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitArea,
+    /// Defaults to the unit set with `zoo config set-default-unit area`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitArea>::new())]
+    pub output_unit: Option<kittycad::types::UnitArea>,
+
+    #[clap(flatten)]
+    pub timeouts: Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdFileSurfaceArea {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Parse the source format.
-        let src_format = if let Some(src_format) = &self.src_format {
-            src_format.clone()
-        } else {
-            get_import_format_from_extension(&get_extension(self.input.clone()))?
-        };
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Area)?;
 
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
+        // Get the contents of the input file and its source format.
+        let (input, src_format) = read_input_with_format(ctx, &self.src_format, &self.input)?;
 
         // Do the operation.
         let client = ctx.api_client("")?;
 
         let file_surface_area = client
             .file()
-            .create_surface_area(Some(self.output_unit.clone()), src_format, &input.into())
+            .create_surface_area(Some(output_unit), src_format, &input.into())
             .await?;
 
         // Print the output of the conversion.
@@ -684,6 +1057,80 @@ impl crate::cmd::Command for CmdFileSurfaceArea {
     }
 }
 
+/// List the file formats that can be converted from and to.
+///
+/// The extensions are the ones the format is picked from when `--src-format` or
+/// `--output-format` is not given.
+///
+///     $ zoo file formats
+///
+///     $ zoo file formats --format json
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdFileFormats {
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+}
+
+/// A format that files can be converted from or to.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, tabled::Tabled)]
+pub struct FileFormat {
+    /// Whether files can be converted from (`import`) or to (`export`) the format.
+    pub direction: String,
+    /// The name of the format, for `--src-format` or `--output-format`.
+    pub format: String,
+    /// The file extensions of the format.
+    pub extensions: String,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdFileFormats {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, file_formats())?;
+        Ok(())
+    }
+}
+
+/// Extensions that are taken to mean a source format, other than its name.
+const IMPORT_EXTENSION_ALIASES: &[(&str, kittycad::types::FileImportFormat)] = &[
+    ("stp", kittycad::types::FileImportFormat::Step),
+    ("glb", kittycad::types::FileImportFormat::Gltf),
+];
+
+/// Extensions that are taken to mean an output format, other than its name.
+const EXPORT_EXTENSION_ALIASES: &[(&str, kittycad::types::FileExportFormat)] =
+    &[("stp", kittycad::types::FileExportFormat::Step)];
+
+/// Every import and export format, with its extensions.
+fn file_formats() -> Vec<FileFormat> {
+    fn formats<T: clap::ValueEnum + PartialEq>(direction: &str, aliases: &[(&str, T)]) -> Vec<FileFormat> {
+        T::value_variants()
+            .iter()
+            .filter_map(|variant| {
+                let name = variant.to_possible_value()?.get_name().to_string();
+                let extensions = std::iter::once(name.as_str())
+                    .chain(aliases.iter().filter(|(_, f)| f == variant).map(|(ext, _)| *ext))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some(FileFormat {
+                    direction: direction.to_string(),
+                    format: name,
+                    extensions,
+                })
+            })
+            .collect()
+    }
+
+    let mut formats = formats::<kittycad::types::FileImportFormat>("import", IMPORT_EXTENSION_ALIASES);
+    formats.extend(formats::<kittycad::types::FileExportFormat>(
+        "export",
+        EXPORT_EXTENSION_ALIASES,
+    ));
+    formats
+}
+
 /// Get the extension for a path buffer.
 pub fn get_extension(path: std::path::PathBuf) -> String {
     path.into_boxed_path()
@@ -694,27 +1141,172 @@ pub fn get_extension(path: std::path::PathBuf) -> String {
         .to_string()
 }
 
+/// Read the input file, and get its source format from the `--src-format` flag, the
+/// extension of the input path, or failing both (for example when reading from stdin),
+/// the contents of the file.
+fn read_input_with_format(
+    ctx: &mut crate::context::Context,
+    src_format: &Option<kittycad::types::FileImportFormat>,
+    path: &std::path::Path,
+) -> Result<(Vec<u8>, kittycad::types::FileImportFormat)> {
+    let ext = get_extension(path.to_path_buf());
+
+    // Check the format before reading, so a bad extension is reported first.
+    let src_format = match src_format {
+        Some(src_format) => Some(src_format.clone()),
+        None if !ext.is_empty() => Some(get_import_format_from_extension(&ext)?),
+        None => None,
+    };
+
+    let input = ctx.read_file(path.to_str().unwrap_or(""))?;
+
+    let src_format = match src_format {
+        Some(src_format) => src_format,
+        None => match sniff_import_format(&input) {
+            Some(src_format) => src_format,
+            None => get_import_format_from_extension(&ext)?,
+        },
+    };
+
+    Ok((input, src_format))
+}
+
+/// Guess the source format from the first bytes of a file.
+/// This only knows about formats with a clear signature, and returns `None` otherwise.
+fn sniff_import_format(contents: &[u8]) -> Option<kittycad::types::FileImportFormat> {
+    // Binary formats with a magic number.
+    if contents.starts_with(b"glTF") {
+        return Some(kittycad::types::FileImportFormat::Gltf);
+    }
+    if contents.starts_with(b"Kaydara FBX Binary") {
+        return Some(kittycad::types::FileImportFormat::Fbx);
+    }
+
+    // Binary STL has an 80 byte header, then the triangle count, then 50 bytes per triangle.
+    if contents.len() >= 84 && !contents.starts_with(b"solid") {
+        let count = u32::from_le_bytes([contents[80], contents[81], contents[82], contents[83]]) as usize;
+        if count > 0 && Some(contents.len()) == count.checked_mul(50).and_then(|n| n.checked_add(84)) {
+            return Some(kittycad::types::FileImportFormat::Stl);
+        }
+    }
+
+    // Text formats, skipping a byte order mark and any leading whitespace.
+    let text = String::from_utf8_lossy(&contents[..contents.len().min(1024)]);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+
+    if text.starts_with("ISO-10303-21") {
+        Some(kittycad::types::FileImportFormat::Step)
+    } else if text.starts_with("solid") {
+        Some(kittycad::types::FileImportFormat::Stl)
+    } else if text.starts_with("ply") && text[3..].starts_with(['\n', '\r']) {
+        Some(kittycad::types::FileImportFormat::Ply)
+    } else if text.starts_with("; FBX") {
+        Some(kittycad::types::FileImportFormat::Fbx)
+    } else if text.starts_with('{') {
+        // glTF is JSON with an `asset` property.
+        match serde_json::from_slice::<serde_json::Value>(contents) {
+            Ok(json) if json.get("asset").is_some() => Some(kittycad::types::FileImportFormat::Gltf),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
 /// Get the source format from the extension.
-fn get_import_format_from_extension(ext: &str) -> Result<kittycad::types::FileImportFormat> {
+pub(crate) fn get_import_format_from_extension(ext: &str) -> Result<kittycad::types::FileImportFormat> {
     match kittycad::types::FileImportFormat::from_str(ext) {
         Ok(format) => Ok(format),
-        Err(_) => {
-            if ext == "stp" {
-                Ok(kittycad::types::FileImportFormat::Step)
-            } else if ext == "glb" {
-                Ok(kittycad::types::FileImportFormat::Gltf)
-            } else {
-                anyhow::bail!(
-                    "unknown source format for file extension: {}. Try setting the `--src-format` flag explicitly or use a valid format.",
-                    ext
-                )
-            }
+        Err(_) => match IMPORT_EXTENSION_ALIASES.iter().find(|(alias, _)| *alias == ext) {
+            Some((_, format)) => Ok(format.clone()),
+            None => anyhow::bail!(
+                "unknown source format for file extension: {}. Try setting the `--src-format` flag explicitly or use a valid format.",
+                ext
+            ),
+        },
+    }
+}
+
+/// Get the output format from the extension.
+fn get_export_format_from_extension(ext: &str) -> Result<kittycad::types::FileExportFormat> {
+    match kittycad::types::FileExportFormat::from_str(ext) {
+        Ok(format) => Ok(format),
+        Err(_) => match EXPORT_EXTENSION_ALIASES.iter().find(|(alias, _)| *alias == ext) {
+            Some((_, format)) => Ok(format.clone()),
+            None => anyhow::bail!(
+                "unknown output format for file extension: {}. Try setting the `--output-format` flag explicitly or use a valid format.",
+                ext
+            ),
+        },
+    }
+}
+
+/// Work out the output format for `zoo file convert`, and the file to write to, if the
+/// output is a file rather than a directory.
+fn resolve_convert_output(
+    output: &std::path::Path,
+    is_dir: bool,
+    output_format: &Option<kittycad::types::FileExportFormat>,
+) -> Result<(kittycad::types::FileExportFormat, Option<std::path::PathBuf>)> {
+    let ext = get_extension(output.to_path_buf());
+    if is_dir || ext.is_empty() {
+        // Make sure the output dir is a directory.
+        if !is_dir {
+            anyhow::bail!(
+                "output directory `{}` does not exist or is not a directory",
+                output.to_str().unwrap_or("")
+            );
         }
+        let Some(output_format) = output_format else {
+            anyhow::bail!("`--output-format` is required when the output is a directory");
+        };
+        return Ok((output_format.clone(), None));
     }
+
+    // Make sure the parent directory of the output file exists.
+    if let Some(parent) = output.parent() {
+        if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
+            anyhow::bail!(
+                "directory `{}` does not exist or is not a directory",
+                parent.to_str().unwrap_or("")
+            );
+        }
+    }
+
+    let inferred = get_export_format_from_extension(&ext.to_lowercase())?;
+    if let Some(output_format) = output_format {
+        if *output_format != inferred {
+            anyhow::bail!(
+                "`--output-format {}` does not match the extension of the output file `{}`",
+                output_format,
+                output.display()
+            );
+        }
+    }
+
+    Ok((inferred, Some(output.to_path_buf())))
+}
+
+/// The name of the output that should be written to the output file, out of all the
+/// files a conversion made. That is the one with the extension of the output format, or
+/// the only one if there is just one.
+fn main_output_name<'a>(
+    names: impl Iterator<Item = &'a String>,
+    output_format: &kittycad::types::FileExportFormat,
+) -> Option<&'a String> {
+    let names: Vec<&String> = names.collect();
+    if names.len() == 1 {
+        return names.first().copied();
+    }
+
+    let ext = output_format.to_string();
+    names
+        .into_iter()
+        .find(|name| get_extension(std::path::PathBuf::from(name)) == ext)
 }
 
 /// Get the source format from the extension.
-fn get_input_format(
+pub(crate) fn get_input_format(
     format: kittycad::types::FileImportFormat,
     ul: kittycad::types::UnitLength,
 ) -> Result<kittycad::types::InputFormat> {
@@ -748,6 +1340,145 @@ fn get_input_format(
     }
 }
 
+/// The files to send to the engine to import a file.
+/// A glTF with "standard" storage keeps its buffers in other files, or in data URIs, so
+/// those are sent too. Buffers in other files are read with `read_buffer`, which is given
+/// their path relative to the glTF.
+pub(crate) fn import_files(
+    filename: &str,
+    input: Vec<u8>,
+    src_format: &kittycad::types::InputFormat,
+    mut read_buffer: impl FnMut(&str) -> Result<Vec<u8>>,
+) -> Result<Vec<kittycad::types::ImportFile>> {
+    // gltf with "standard" storage is an oddball in the KittyCAD system.
+    // In order for the program to know it's dealing with this type, an
+    // attempt to parse as json is made, then we check for the buffers
+    // property which describes what external files are needed.
+    let mut files: Vec<kittycad::types::ImportFile> = vec![kittycad::types::ImportFile {
+        path: filename.to_string(),
+        data: input.clone(),
+    }];
+
+    if let kittycad::types::InputFormat::Gltf {} = src_format {
+        if let Ok(str) = std::str::from_utf8(&input) {
+            if let Ok(json) = serde_json::from_str::<crate::types::GltfStandardJsonLite>(str) {
+                // Use the path of the control file as the prefix path of
+                // the relative file name.
+
+                for buffer in json.buffers {
+                    if is_data_uri(&buffer.uri) {
+                        // Using the whole data URI would create massive
+                        // path properties. Use a hash instead.
+                        let mut hasher = DefaultHasher::new();
+                        buffer.uri.hash(&mut hasher);
+                        let hash_u64 = hasher.finish();
+
+                        if let Some(buf_base64) = buffer.uri.split(',').nth(1) {
+                            files.push(kittycad::types::ImportFile {
+                                path: hash_u64.to_string(),
+                                data: BASE64_STANDARD.decode(buf_base64)?,
+                            });
+                        } else {
+                            anyhow::bail!("invalid data uri in gltf.buffers.uri property");
+                        }
+                    } else {
+                        let data = read_buffer(&buffer.uri)?;
+                        let path = std::path::Path::new(&buffer.uri);
+                        files.push(kittycad::types::ImportFile {
+                            path: path.file_name().unwrap_or_default().to_str().unwrap_or("").to_string(),
+                            data,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Import files into the engine, and return the id of the imported object.
+pub(crate) async fn import_into_engine(
+    engine: &kcl_lib::native_engine::EngineConnection,
+    files: Vec<kittycad::types::ImportFile>,
+    src_format: kittycad::types::InputFormat,
+) -> Result<uuid::Uuid> {
+    // Send an import request to the engine.
+    let resp = engine
+        .send_modeling_cmd(
+            uuid::Uuid::new_v4(),
+            kcl_lib::SourceRange::default(),
+            kcmc::ModelingCmd::ImportFiles(kcmc::ImportFiles {
+                files: files.into_iter().map(|f| f.into()).collect(),
+                format: src_format.into(),
+            }),
+        )
+        .await?;
+
+    let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+        modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::ImportFiles(data),
+    } = &resp
+    else {
+        return Err(crate::engine_response::unexpected("an import", &resp));
+    };
+
+    Ok(data.object_id)
+}
+
+/// Move an imported object by `times` the offset, in millimeters.
+async fn move_object(
+    engine: &kcl_lib::native_engine::EngineConnection,
+    object_id: uuid::Uuid,
+    offset: &kcmc::shared::Point3d<f64>,
+    times: f64,
+) -> Result<()> {
+    let translate = kcmc::shared::Point3d {
+        x: kcmc::length_unit::LengthUnit(offset.x * times),
+        y: kcmc::length_unit::LengthUnit(offset.y * times),
+        z: kcmc::length_unit::LengthUnit(offset.z * times),
+    };
+    engine
+        .send_modeling_cmd(
+            uuid::Uuid::new_v4(),
+            kcl_lib::SourceRange::default(),
+            kcmc::ModelingCmd::SetObjectTransform(kcmc::SetObjectTransform {
+                object_id,
+                transforms: vec![kcmc::shared::ComponentTransform {
+                    translate: Some(kcmc::shared::TransformBy {
+                        property: translate,
+                        set: false,
+                        is_local: true,
+                    }),
+                    rotate_rpy: None,
+                    rotate_angle_axis: None,
+                    scale: None,
+                }],
+            }),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Parse a point like `50,0,0`, for an offset or a camera position.
+pub(crate) fn parse_offset(s: &str) -> Result<kcmc::shared::Point3d<f64>> {
+    let parts = s
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|parts| parts.len() == 3);
+    let Some(parts) = parts else {
+        anyhow::bail!("invalid point `{}`, expected `x,y,z` like `50,0,0`", s);
+    };
+
+    Ok(kcmc::shared::Point3d {
+        x: parts[0],
+        y: parts[1],
+        z: parts[2],
+    })
+}
+
 /// Determine if buffers[].buffer.uri is a data uri.
 fn is_data_uri(s: &str) -> bool {
     matches!(s.split(':').next(), Some("data"))
@@ -774,13 +1505,17 @@ mod test {
             TestItem {
                     name: "convert input with bad ext".to_string(),
                     cmd: crate::cmd_file::SubCommand::Convert(crate::cmd_file::CmdFileConvert {
-                        input: std::path::PathBuf::from("test/bad_ext.bad_ext"),
+                        input: vec![std::path::PathBuf::from("test/bad_ext.bad_ext")],
                         output_dir: std::path::PathBuf::from("tests/"),
-                        output_format: kittycad::types::FileExportFormat::Obj,
+                        output_format: Some(kittycad::types::FileExportFormat::Obj),
                         src_format: None,
                         format: None,
                         deterministic:false,
-
+                        keep_going: false,
+                        dedupe: false,
+                        preserve_tree: false,
+                        upload_retries: 0,
+                        timeouts: Default::default(),
                     }),
                     stdin: "".to_string(),
                     want_out: "".to_string(),
@@ -789,24 +1524,87 @@ mod test {
                 TestItem {
                     name: "convert: input file does not exist".to_string(),
                     cmd: crate::cmd_file::SubCommand::Convert(crate::cmd_file::CmdFileConvert {
-                        input: std::path::PathBuf::from("test/bad_ext.stp"),
+                        input: vec![std::path::PathBuf::from("test/bad_ext.stp")],
                         output_dir: std::path::PathBuf::from("tests/"),
-                        output_format: kittycad::types::FileExportFormat::Obj,
+                        output_format: Some(kittycad::types::FileExportFormat::Obj),
                         src_format: None,
                         format: None,
                         deterministic:false,
+                        keep_going: false,
+                        dedupe: false,
+                        preserve_tree: false,
+                        upload_retries: 0,
+                        timeouts: Default::default(),
                     }),
                     stdin: "".to_string(),
                     want_out: "".to_string(),
                     want_err: "File 'test/bad_ext.stp' does not exist.".to_string(),
                 },
+                TestItem {
+                    name: "convert: output dir without output format".to_string(),
+                    cmd: crate::cmd_file::SubCommand::Convert(crate::cmd_file::CmdFileConvert {
+                        input: vec![std::path::PathBuf::from("assets/in_obj.obj")],
+                        output_dir: std::path::PathBuf::from("tests/"),
+                        output_format: None,
+                        src_format: None,
+                        format: None,
+                        deterministic:false,
+                        keep_going: false,
+                        dedupe: false,
+                        preserve_tree: false,
+                        upload_retries: 0,
+                        timeouts: Default::default(),
+                    }),
+                    stdin: "".to_string(),
+                    want_out: "".to_string(),
+                    want_err: "`--output-format` is required when the output is a directory".to_string(),
+                },
+                TestItem {
+                    name: "convert: output format does not match the output file".to_string(),
+                    cmd: crate::cmd_file::SubCommand::Convert(crate::cmd_file::CmdFileConvert {
+                        input: vec![std::path::PathBuf::from("assets/in_obj.obj")],
+                        output_dir: std::path::PathBuf::from("tests/out.stl"),
+                        output_format: Some(kittycad::types::FileExportFormat::Obj),
+                        src_format: None,
+                        format: None,
+                        deterministic:false,
+                        keep_going: false,
+                        dedupe: false,
+                        preserve_tree: false,
+                        upload_retries: 0,
+                        timeouts: Default::default(),
+                    }),
+                    stdin: "".to_string(),
+                    want_out: "".to_string(),
+                    want_err: "`--output-format obj` does not match the extension of the output file `tests/out.stl`".to_string(),
+                },
+                TestItem {
+                    name: "convert: output file with bad ext".to_string(),
+                    cmd: crate::cmd_file::SubCommand::Convert(crate::cmd_file::CmdFileConvert {
+                        input: vec![std::path::PathBuf::from("assets/in_obj.obj")],
+                        output_dir: std::path::PathBuf::from("tests/out.bad_ext"),
+                        output_format: None,
+                        src_format: None,
+                        format: None,
+                        deterministic:false,
+                        keep_going: false,
+                        dedupe: false,
+                        preserve_tree: false,
+                        upload_retries: 0,
+                        timeouts: Default::default(),
+                    }),
+                    stdin: "".to_string(),
+                    want_out: "".to_string(),
+                    want_err: "unknown output format for file extension: bad_ext. Try setting the `--output-format` flag explicitly or use a valid format.".to_string(),
+                },
                 TestItem {
                     name: "volume with bad ext".to_string(),
                     cmd: crate::cmd_file::SubCommand::Volume(crate::cmd_file::CmdFileVolume {
                         input: std::path::PathBuf::from("tests/bad_ext.bad_ext"),
                         src_format: None,
                         format: None,
-                        output_unit: kittycad::types::UnitVolume::Cm3,
+                        output_unit: Some(kittycad::types::UnitVolume::Cm3),
+                        timeouts: Default::default(),
                     }),
                     stdin: "".to_string(),
                     want_out: "".to_string(),
@@ -818,7 +1616,8 @@ mod test {
                         input: std::path::PathBuf::from("test/bad_ext.stp"),
                         src_format: None,
                         format: None,
-                        output_unit: kittycad::types::UnitVolume::Cm3,
+                        output_unit: Some(kittycad::types::UnitVolume::Cm3),
+                        timeouts: Default::default(),
                     }),
                     stdin: "".to_string(),
                     want_out: "".to_string(),
@@ -839,11 +1638,7 @@ mod test {
             io.set_color_enabled(false);
             // TODO: we should figure out how to test the prompts.
             io.set_never_prompt(true);
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             let cmd_file = crate::cmd_file::CmdFile { subcmd: t.cmd };
             match cmd_file.run(&mut ctx).await {
@@ -867,4 +1662,181 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_main_output_name() {
+        use crate::cmd_file::main_output_name;
+        use kittycad::types::FileExportFormat;
+
+        let names = ["output.gltf".to_string(), "output.bin".to_string()];
+        assert_eq!(
+            main_output_name(names.iter(), &FileExportFormat::Gltf),
+            Some(&"output.gltf".to_string())
+        );
+        let names = ["output.stl".to_string()];
+        assert_eq!(
+            main_output_name(names.iter(), &FileExportFormat::Step),
+            Some(&"output.stl".to_string())
+        );
+        let names = ["a.bin".to_string(), "b.bin".to_string()];
+        assert_eq!(main_output_name(names.iter(), &FileExportFormat::Gltf), None);
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        use crate::cmd_file::parse_offset;
+
+        let offset = parse_offset("50, 0,-2.5").unwrap();
+        assert_eq!((offset.x, offset.y, offset.z), (50.0, 0.0, -2.5));
+
+        assert!(parse_offset("50,0").is_err());
+        assert!(parse_offset("50,0,0,0").is_err());
+        assert!(parse_offset("a,b,c").is_err());
+    }
+
+    #[test]
+    fn test_preserve_tree() {
+        use std::path::PathBuf;
+
+        use crate::cmd_file::{common_input_root, tree_output_dir};
+
+        let inputs = vec![
+            PathBuf::from("library/gears/spur.step"),
+            PathBuf::from("library/gears/helical/big.step"),
+            PathBuf::from("library/brackets/l.step"),
+            PathBuf::from("-"),
+        ];
+        let root = common_input_root(&inputs);
+        assert_eq!(root, PathBuf::from("library"));
+
+        let out = PathBuf::from("out");
+        assert_eq!(tree_output_dir(&out, &root, &inputs[0]), PathBuf::from("out/gears"));
+        assert_eq!(
+            tree_output_dir(&out, &root, &inputs[1]),
+            PathBuf::from("out/gears/helical")
+        );
+        assert_eq!(tree_output_dir(&out, &root, &inputs[2]), PathBuf::from("out/brackets"));
+        assert_eq!(tree_output_dir(&out, &root, &inputs[3]), out);
+
+        // A single input, or inputs all in one directory, stay flat.
+        let inputs = vec![PathBuf::from("parts/a.step"), PathBuf::from("parts/b.step")];
+        let root = common_input_root(&inputs);
+        assert_eq!(tree_output_dir(&out, &root, &inputs[1]), out);
+        assert_eq!(common_input_root(&[PathBuf::from("a.step")]), PathBuf::new());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        use crate::cmd_file::parse_timeout;
+
+        assert_eq!(parse_timeout("90s").unwrap(), std::time::Duration::from_secs(90));
+        assert_eq!(parse_timeout("10m").unwrap(), std::time::Duration::from_secs(600));
+        assert_eq!(parse_timeout("1h").unwrap(), std::time::Duration::from_secs(3600));
+        assert!(parse_timeout("10").is_err());
+        assert!(parse_timeout("m").is_err());
+        assert!(parse_timeout("1d").is_err());
+    }
+
+    #[test]
+    fn test_upload_backoff() {
+        use crate::cmd_file::upload_backoff;
+
+        assert_eq!(upload_backoff(1), std::time::Duration::from_secs(2));
+        assert_eq!(upload_backoff(3), std::time::Duration::from_secs(8));
+        assert_eq!(upload_backoff(10), std::time::Duration::from_secs(60));
+        assert_eq!(upload_backoff(100), std::time::Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_dedupe_key() {
+        use crate::cmd_file::dedupe_key;
+
+        let step = kittycad::types::FileImportFormat::Step;
+        let obj = kittycad::types::FileImportFormat::Obj;
+        assert_eq!(dedupe_key(b"solid", &step), dedupe_key(b"solid", &step));
+        assert_ne!(dedupe_key(b"solid", &step), dedupe_key(b"solid!", &step));
+        assert_ne!(dedupe_key(b"solid", &step), dedupe_key(b"solid", &obj));
+    }
+
+    #[test]
+    fn test_file_formats() {
+        use crate::cmd_file::{file_formats, get_export_format_from_extension, get_import_format_from_extension};
+
+        let formats = file_formats();
+        let extensions = |direction: &str, format: &str| {
+            formats
+                .iter()
+                .find(|f| f.direction == direction && f.format == format)
+                .map(|f| f.extensions.clone())
+        };
+        assert_eq!(extensions("import", "step"), Some("step, stp".to_string()));
+        assert_eq!(extensions("import", "gltf"), Some("gltf, glb".to_string()));
+        assert_eq!(extensions("export", "glb"), Some("glb".to_string()));
+        assert_eq!(extensions("export", "sldprt"), None);
+
+        // Every extension listed picks the format it is listed for.
+        for format in &formats {
+            for ext in format.extensions.split(", ") {
+                let name = if format.direction == "import" {
+                    get_import_format_from_extension(ext).unwrap().to_string()
+                } else {
+                    get_export_format_from_extension(ext).unwrap().to_string()
+                };
+                assert_eq!(name, format.format, "{}", ext);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sniff_import_format() {
+        use crate::cmd_file::sniff_import_format;
+        use kittycad::types::FileImportFormat;
+
+        assert_eq!(
+            sniff_import_format(b"ISO-10303-21;\nHEADER;\n"),
+            Some(FileImportFormat::Step)
+        );
+        assert_eq!(
+            sniff_import_format(b"\xef\xbb\xbf  ISO-10303-21;\n"),
+            Some(FileImportFormat::Step)
+        );
+        assert_eq!(
+            sniff_import_format(br#"{"asset": {"version": "2.0"}, "scenes": []}"#),
+            Some(FileImportFormat::Gltf)
+        );
+        assert_eq!(sniff_import_format(br#"{"foo": "bar"}"#), None);
+        assert_eq!(
+            sniff_import_format(b"glTF\x02\x00\x00\x00"),
+            Some(FileImportFormat::Gltf)
+        );
+        assert_eq!(
+            sniff_import_format(b"solid cube\n  facet normal 0 0 1\n"),
+            Some(FileImportFormat::Stl)
+        );
+
+        // A binary stl with one triangle.
+        let mut stl = vec![0u8; 80];
+        stl.extend_from_slice(&1u32.to_le_bytes());
+        stl.extend_from_slice(&[0u8; 50]);
+        assert_eq!(sniff_import_format(&stl), Some(FileImportFormat::Stl));
+        // But not if the size doesn't add up.
+        stl.push(0);
+        assert_eq!(sniff_import_format(&stl), None);
+
+        assert_eq!(
+            sniff_import_format(b"ply\nformat ascii 1.0\n"),
+            Some(FileImportFormat::Ply)
+        );
+        assert_eq!(sniff_import_format(b"plywood"), None);
+        assert_eq!(
+            sniff_import_format(b"Kaydara FBX Binary  \x00\x1a\x00"),
+            Some(FileImportFormat::Fbx)
+        );
+        assert_eq!(
+            sniff_import_format(b"; FBX 7.4.0 project file\n"),
+            Some(FileImportFormat::Fbx)
+        );
+        assert_eq!(sniff_import_format(b"v 0 0 0\nf 1 2 3\n"), None);
+        assert_eq!(sniff_import_format(b""), None);
+    }
 }
@@ -158,11 +158,7 @@ mod test {
         let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
 
         let (io, stdout_path, stderr_path) = crate::iostreams::IoStreams::test();
-        let mut ctx = crate::context::Context {
-            config: &mut c,
-            io,
-            debug: false,
-        };
+        let mut ctx = crate::context::Context::test(&mut c, io);
 
         let cmd = crate::cmd_generate::CmdGenerateMarkdown { dir: "".to_string() };
 
@@ -186,11 +182,7 @@ mod test {
         let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
 
         let (io, stdout_path, stderr_path) = crate::iostreams::IoStreams::test();
-        let mut ctx = crate::context::Context {
-            config: &mut c,
-            io,
-            debug: false,
-        };
+        let mut ctx = crate::context::Context::test(&mut c, io);
 
         let cmd = crate::cmd_generate::CmdGenerateMarkdown { dir: "".to_string() };
 
@@ -1,7 +1,8 @@
-use std::{net::SocketAddr, str::FromStr};
+use std::{io::Write, net::SocketAddr, str::FromStr};
 
 use anyhow::Result;
 use clap::Parser;
+use futures::StreamExt;
 use kcmc::format::OutputFormat;
 use kittycad::types as kt;
 use kittycad_modeling_cmds as kcmc;
@@ -10,6 +11,12 @@ use url::Url;
 use crate::iostreams::IoStreams;
 
 /// Perform actions on `kcl` files.
+///
+/// The input file can also be an `http://` or `https://` URL, to read a file that was
+/// shared as a link without downloading it first. Like stdin, a file read from a URL
+/// can't import other files or use a `project.toml`.
+///
+///     $ zoo kcl snapshot https://example.com/gear.kcl gear.png
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdKcl {
@@ -20,32 +27,49 @@ pub struct CmdKcl {
 #[derive(Parser, Debug, Clone)]
 enum SubCommand {
     Export(CmdKclExport),
+    Run(CmdKclRun),
+    Entities(CmdKclEntities),
     #[clap(alias = "fmt")]
     Format(CmdKclFormat),
     Snapshot(CmdKclSnapshot),
     View(CmdKclView),
+    Turntable(CmdKclTurntable),
     Volume(CmdKclVolume),
     Mass(CmdKclMass),
     CenterOfMass(CmdKclCenterOfMass),
     Density(CmdKclDensity),
     SurfaceArea(CmdKclSurfaceArea),
+    #[clap(alias = "bbox")]
+    BoundingBox(CmdKclBoundingBox),
     Lint(CmdKclLint),
+    Deps(CmdKclDeps),
+    Meta(CmdKclMeta),
+    Bench(CmdKclBench),
+    Warm(CmdKclWarm),
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdKcl {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
         match &self.subcmd {
-            SubCommand::Export(cmd) => cmd.run(ctx).await,
+            SubCommand::Export(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Run(cmd) => cmd.run(ctx).await,
+            SubCommand::Entities(cmd) => cmd.run(ctx).await,
             SubCommand::Format(cmd) => cmd.run(ctx).await,
             SubCommand::Snapshot(cmd) => cmd.run(ctx).await,
             SubCommand::View(cmd) => cmd.run(ctx).await,
-            SubCommand::Volume(cmd) => cmd.run(ctx).await,
-            SubCommand::Mass(cmd) => cmd.run(ctx).await,
-            SubCommand::CenterOfMass(cmd) => cmd.run(ctx).await,
-            SubCommand::Density(cmd) => cmd.run(ctx).await,
-            SubCommand::SurfaceArea(cmd) => cmd.run(ctx).await,
+            SubCommand::Turntable(cmd) => cmd.run(ctx).await,
+            SubCommand::Volume(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Mass(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::CenterOfMass(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::Density(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::SurfaceArea(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
+            SubCommand::BoundingBox(cmd) => cmd.timeouts.run_one(&cmd.input, cmd.run(ctx)).await,
             SubCommand::Lint(cmd) => cmd.run(ctx).await,
+            SubCommand::Deps(cmd) => cmd.run(ctx).await,
+            SubCommand::Meta(cmd) => cmd.run(ctx).await,
+            SubCommand::Bench(cmd) => cmd.run(ctx).await,
+            SubCommand::Warm(cmd) => cmd.run(ctx).await,
         }
     }
 }
@@ -61,6 +85,27 @@ impl crate::cmd::Command for CmdKcl {
 ///     # pass a file to convert from stdin
 ///     $ cat my-obj.kcl | zoo kcl export --output-format=step - output_dir
 ///
+///     # only export some of the solids, find their ids with `zoo kcl entities`
+///     $ zoo kcl export --output-format=step --entity-ids=<uuid>,<uuid> my-file.kcl .
+///
+///     # pack all the exported files into a single zip archive
+///     $ zoo kcl export --output-format=gltf --zip my-file.zip my-file.kcl
+///
+///     # override the export options the engine gets for the output format
+///     $ zoo kcl export --output-format=stl --format-options='{"storage": "binary"}' my-file.kcl .
+///
+///     # reuse the last export if none of the files it was made from changed
+///     $ zoo kcl export --output-format=step --cache my-file.kcl .
+///
+///     # make sure the exported files can be imported again
+///     $ zoo kcl export --output-format=step --validate-output my-file.kcl .
+///
+///     # write the mesh in meters, whatever unit the model is in
+///     $ zoo kcl export --output-format=stl --units-out=m my-file.kcl .
+///
+///     # export several formats from a single run of the model
+///     $ zoo kcl export -t step -t gltf my-file.kcl .
+///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
 /// of the input path OR any parent directories above that, the default
@@ -74,21 +119,50 @@ pub struct CmdKclExport {
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
     /// The path to a directory to output the files.
-    #[clap(name = "output-dir", required = true)]
-    pub output_dir: std::path::PathBuf,
+    /// This can also be an `s3://bucket/prefix` or `gs://bucket/prefix` URL to upload the
+    /// files to, if zoo was built with the `cloud-export` feature. Credentials are taken
+    /// from the environment: `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_REGION` and
+    /// friends for S3, and `GOOGLE_OAUTH_ACCESS_TOKEN` for Google Cloud Storage.
+    #[clap(name = "output-dir", required_unless_present = "zip")]
+    pub output_dir: Option<std::path::PathBuf>,
 
     /// A valid output file format.
-    #[clap(short = 't', long = "output-format", value_enum)]
-    output_format: kittycad::types::FileExportFormat,
+    /// This can be given more than once, or as a list like `step,gltf`, to export
+    /// several formats from a single run of the model.
+    #[clap(
+        short = 't',
+        long = "output-format",
+        value_enum,
+        value_delimiter = ',',
+        required = true
+    )]
+    output_format: Vec<kittycad::types::FileExportFormat>,
+
+    /// Write all the exported files into a zip archive at this path, instead of into an
+    /// output directory.
+    /// This is handy for formats that export more than one file, like gltf or obj.
+    #[clap(long, conflicts_with = "output-dir")]
+    pub zip: Option<std::path::PathBuf>,
 
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
+    /// The unit to write the coordinates of an obj, ply or stl export in.
+    /// The model is scaled to it, so a 10mm cube exported with `--units-out=m` has
+    /// sides of 0.01. This defaults to the source unit.
+    #[clap(long, value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub units_out: Option<kittycad::types::UnitLength>,
+
     /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
@@ -102,151 +176,739 @@ pub struct CmdKclExport {
     /// This is useful for when pushing to version control.
     #[clap(long, default_value = "false")]
     pub deterministic: bool,
+
+    /// If true, remove metadata that could identify who made the file, or with what,
+    /// like the glTF generator or the STEP author and organization.
+    /// This is useful for sharing models publicly.
+    #[clap(long, default_value = "false")]
+    pub strip_metadata: bool,
+
+    /// If true, print how long parsing, executing and exporting the model took to
+    /// stderr. This is more detailed than `--show-trace`.
+    #[clap(long, default_value = "false")]
+    pub log_timings: bool,
+
+    /// Only export these entities, instead of the whole model.
+    /// Use `zoo kcl entities` to find the ids.
+    #[clap(long, value_delimiter = ',')]
+    pub entity_ids: Vec<uuid::Uuid>,
+
+    /// How to lay out the JSON of a gltf or glb export.
+    /// This defaults to `pretty` for gltf and `compact` for glb.
+    #[clap(long, value_enum)]
+    pub gltf_presentation: Option<kittycad::types::GltfPresentation>,
+
+    /// Where to store the binary data of a gltf export: `embedded` in the JSON as base64,
+    /// in a separate `standard` file, or as a single `binary` glb.
+    /// This defaults to `embedded` for gltf and `binary` for glb.
+    #[clap(long, value_enum)]
+    pub gltf_storage: Option<kittycad::types::GltfStorage>,
+
+    /// Export options for the output format, as a JSON object.
+    /// These override the options the CLI would otherwise send to the engine.
+    #[clap(long, value_parser = parse_format_options)]
+    pub format_options: Option<serde_json::Map<String, serde_json::Value>>,
+
+    /// If true, reuse the files from the last export when nothing it was made from has
+    /// changed, instead of running the model on the engine again.
+    /// The cache is invalidated by a change to any of: the code of the input file and of
+    /// every file it imports (by path and contents), the settings from `project.toml` and
+    /// `--src-unit`, the output format and its options, `--entity-ids`, the API and
+    /// engine hosts, and the version of zoo.
+    /// `--deterministic` and `--strip-metadata` are applied after the cache, so they
+    /// don't invalidate it.
+    /// Exports are cached in `$XDG_CACHE_HOME/zoo/exports`, or `~/.cache/zoo/exports`.
+    #[clap(long, default_value = "false")]
+    pub cache: bool,
+
+    /// If true, import each exported file back into the engine after it is written, to
+    /// make sure it can be loaded. The command fails if any of them can't be.
+    /// This needs another connection to the engine, so it is off by default.
+    /// Files that can't be imported on their own, like the buffers of a glTF, are skipped.
+    #[clap(long, default_value = "false")]
+    pub validate_output: bool,
+
+    /// Also take a PNG snapshot of the model and write it to this path.
+    /// The snapshot is taken over the same engine session as the export, so the model
+    /// is only run once.
+    #[clap(long, conflicts_with = "cache")]
+    pub thumbnail: Option<std::path::PathBuf>,
+
+    /// If true, skip the export when nothing it is made from has changed since the last
+    /// time it was exported to the same place, and the files from then are still there.
+    /// This looks at the same things as `--cache`, including every file the input
+    /// imports, along with `--deterministic`, `--strip-metadata` and `--thumbnail`.
+    /// A hash of them is stored in a hidden `.zoo-export-hash` file next to the output
+    /// after each export.
+    #[clap(long, default_value = "false")]
+    pub if_changed: bool,
+
+    /// Run the whole export again, up to this many times, if it times out, like it can
+    /// while the engine starts up. Other errors are not retried, see `--engine-retries`.
+    #[clap(long, default_value = "0")]
+    pub retry_on_timeout: u32,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
+}
+
+/// What `--if-changed` stores next to the output of an export.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct ExportStamp {
+    /// The hash of everything the export was made from.
+    hash: String,
+    /// The files that were written.
+    files: Vec<std::path::PathBuf>,
+}
+
+impl ExportStamp {
+    /// If the export the stamp was written for is the same as one with this hash, and
+    /// its files are still there.
+    fn is_current(&self, hash: &str) -> bool {
+        self.hash == hash && self.files.iter().all(|file| file.is_file())
+    }
 }
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdKclExport {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Make sure the output dir is a directory.
-        if !self.output_dir.is_dir() {
-            anyhow::bail!(
-                "output directory `{}` does not exist or is not a directory",
-                self.output_dir.to_str().unwrap_or("")
-            );
+        if self.log_timings {
+            ctx.timings = Some(Default::default());
+        }
+        ctx.timeout_retries = self.retry_on_timeout;
+
+        // Make sure the output dir is a directory, unless we are uploading the files.
+        if let (Some(output_dir), None) = (&self.output_dir, self.remote_output()?) {
+            if !output_dir.is_dir() {
+                anyhow::bail!(
+                    "output directory `{}` does not exist or is not a directory",
+                    output_dir.to_str().unwrap_or("")
+                );
+            }
+        }
+
+        // Make sure the parent directory of the zip exists.
+        if let Some(parent) = self.zip.as_ref().and_then(|zip| zip.parent()) {
+            if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
+                anyhow::bail!(
+                    "directory `{}` does not exist or is not a directory",
+                    parent.to_str().unwrap_or("")
+                );
+            }
+        }
+
+        // Make sure the parent directory of the thumbnail exists.
+        if let Some(parent) = self.thumbnail.as_ref().and_then(|thumbnail| thumbnail.parent()) {
+            if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
+                anyhow::bail!(
+                    "directory `{}` does not exist or is not a directory",
+                    parent.to_str().unwrap_or("")
+                );
+            }
         }
 
         // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        let (code, path) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
 
         // Get the modeling settings from the project.toml if exists.
         let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
         let src_unit = executor_settings.units;
 
-        // Spin up websockets and do the conversion.
-        // This will not return until there are files.
-        let (resp, session_data) = ctx
-            .send_kcl_modeling_cmd(
-                "",
-                input,
-                kittycad_modeling_cmds::ModelingCmd::Export(kittycad_modeling_cmds::Export {
-                    entity_ids: vec![],
-                    format: get_output_format(&self.output_format, src_unit.into()),
-                }),
-                executor_settings,
-            )
-            .await?;
+        let formats = self.output_formats(src_unit.into())?;
+
+        let stamp = if self.if_changed {
+            let stamp_path = self.stamp_path()?;
+            let hash = self.stamp_hash(ctx, &code, &path, &executor_settings, &formats)?;
+            let current = std::fs::read(&stamp_path)
+                .ok()
+                .and_then(|stamp| serde_json::from_slice::<ExportStamp>(&stamp).ok())
+                .is_some_and(|stamp| stamp.is_current(&hash));
+            if current {
+                writeln!(ctx.io.err_out, "Nothing changed since the last export, skipping it")?;
+                return Ok(());
+            }
+            Some((stamp_path, hash))
+        } else {
+            None
+        };
 
-        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Export { files } = resp {
-            // Save the files to our export directory.
-            for file in files {
-                let path = self.output_dir.join(file.name);
-                if self.deterministic {
-                    write_deterministic_export(&path, &file.contents)?;
-                } else {
-                    std::fs::write(&path, file.contents)?;
+        // The cache for each format, and the files in it if there are any.
+        let mut caches = Vec::with_capacity(formats.len());
+        for (output_format, format) in &formats {
+            let cache = if self.cache {
+                let key = self.cache_key(ctx, &code, &path, &executor_settings, format)?;
+                let dir = crate::export_cache::dir()?;
+                let files = crate::export_cache::lookup(&dir, &key)?;
+                if files.is_some() {
+                    if formats.len() == 1 {
+                        writeln!(
+                            ctx.io.err_out,
+                            "Nothing changed since the last export, using the cached files"
+                        )?;
+                    } else {
+                        writeln!(
+                            ctx.io.err_out,
+                            "Nothing changed since the last {} export, using the cached files",
+                            output_format
+                        )?;
+                    }
                 }
-                println!("Wrote file: {}", path.display());
+                Some((dir, key, files))
+            } else {
+                None
+            };
+            caches.push(cache);
+        }
+
+        // Only the formats that aren't cached need the engine.
+        let exports = formats
+            .iter()
+            .zip(&caches)
+            .filter(|(_, cache)| !matches!(cache, Some((_, _, Some(_)))))
+            .map(|((_, format), _)| {
+                kittycad_modeling_cmds::ModelingCmd::Export(kittycad_modeling_cmds::Export {
+                    entity_ids: self.entity_ids.clone(),
+                    format: format.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        if exports.is_empty() {
+            let files = caches
+                .into_iter()
+                .flat_map(|cache| cache.and_then(|(_, _, files)| files).unwrap_or_default())
+                .collect::<Vec<_>>();
+            self.write_files(ctx, &files).await?;
+            self.validate_files(ctx, &files).await?;
+            if let Some((stamp_path, hash)) = &stamp {
+                self.write_stamp(stamp_path, hash, &files)?;
+            }
+            return self.write_timings(ctx);
+        }
+
+        let snapshot = kittycad_modeling_cmds::ModelingCmd::TakeSnapshot(kittycad_modeling_cmds::TakeSnapshot {
+            format: kittycad_modeling_cmds::ImageFormat::Png,
+        });
+
+        // Spin up websockets and do the conversion, with every format over the same
+        // session so the model is only run once.
+        // This will not return until there are files.
+        let (mut responses, thumbnail, session_data) = if self.entity_ids.is_empty() {
+            let mut cmds = exports;
+            if self.thumbnail.is_some() {
+                cmds.push(snapshot);
             }
+            let (mut responses, session_data) = ctx
+                .send_kcl_modeling_cmds_with_setup(
+                    "",
+                    input,
+                    &[],
+                    &cmds,
+                    executor_settings,
+                    Some(crate::context::DEFAULT_ZOOM_PADDING),
+                )
+                .await?;
+            let thumbnail = if self.thumbnail.is_some() {
+                responses.pop()
+            } else {
+                None
+            };
+            (responses, thumbnail, session_data)
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            let (engine_ctx, session_data) = ctx.execute_kcl("", input, executor_settings).await?;
+
+            // Make sure all the entities exist before we try to export them.
+            let existing = get_solid_entity_ids(&engine_ctx).await?;
+            for id in &self.entity_ids {
+                if !existing.contains(id) {
+                    anyhow::bail!(
+                        "entity `{}` was not found in the model, use `zoo kcl entities` to list the entities",
+                        id
+                    );
+                }
+            }
+
+            let mut responses = Vec::with_capacity(exports.len());
+            for export in exports {
+                let start = std::time::Instant::now();
+                let resp = engine_ctx
+                    .engine
+                    .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), export)
+                    .await?;
+                ctx.record_timing("export", start);
+                responses.push(resp);
+            }
+
+            // The whole model is in the picture, not just the exported entities.
+            let thumbnail = match &self.thumbnail {
+                Some(_) => {
+                    let start = std::time::Instant::now();
+                    engine_ctx
+                        .engine
+                        .send_modeling_cmd(
+                            uuid::Uuid::new_v4(),
+                            kcl_lib::SourceRange::default(),
+                            kittycad_modeling_cmds::ModelingCmd::ZoomToFit(kittycad_modeling_cmds::ZoomToFit {
+                                animated: false,
+                                object_ids: Default::default(),
+                                padding: crate::context::DEFAULT_ZOOM_PADDING,
+                            }),
+                        )
+                        .await?;
+                    let resp = engine_ctx
+                        .engine
+                        .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), snapshot)
+                        .await?;
+                    ctx.record_timing("snapshot", start);
+                    Some(resp)
+                }
+                None => None,
+            };
+            (responses, thumbnail, session_data)
+        };
+
+        // The files of every format, in the order they were asked for.
+        let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+        responses.reverse();
+        for cache in caches {
+            let cache = match cache {
+                Some((_, _, Some(cached))) => {
+                    files.extend(cached);
+                    continue;
+                }
+                cache => cache,
+            };
+
+            let resp = responses
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("no response from engine"))?;
+            let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Export { files: exported } = resp else {
+                return Err(crate::engine_response::unexpected("an export", &resp));
+            };
+            let exported: Vec<(String, Vec<u8>)> = exported
+                .into_iter()
+                .map(|file| (file.name, file.contents.to_vec()))
+                .collect();
+
+            // The export worked, so a cache we can't write to shouldn't fail the command.
+            if let Some((dir, key, _)) = &cache {
+                if let Err(err) = crate::export_cache::store(dir, key, &exported) {
+                    writeln!(ctx.io.err_out, "warning: failed to cache the export: {}", err)?;
+                }
+            }
+            files.extend(exported);
+        }
+        let mut names = std::collections::HashSet::new();
+        if let Some((name, _)) = files.iter().find(|(name, _)| !names.insert(name)) {
+            anyhow::bail!("more than one of the output formats exported a file named `{}`", name);
+        }
+
+        self.write_files(ctx, &files).await?;
+        if let (Some(path), Some(resp)) = (&self.thumbnail, &thumbnail) {
+            let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+                modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+            } = resp
+            else {
+                return Err(crate::engine_response::unexpected("a snapshot", resp));
+            };
+            std::fs::write(path, &data.contents.0)?;
+            println!("Wrote thumbnail: {}", path.display());
+        }
+        self.validate_files(ctx, &files).await?;
+        if let Some((stamp_path, hash)) = &stamp {
+            self.write_stamp(stamp_path, hash, &files)?;
         }
 
         if self.show_trace {
             print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
         }
 
-        Ok(())
+        self.write_timings(ctx)
     }
 }
 
-/// Format a `kcl` file.
-///
-///     # Output to stdout by default
-///     $ zoo kcl fmt my-file.kcl
-///
-///     # Overwrite the file
-///     $ zoo kcl fmt -w my-file.kcl
-///
-///     # Pass a file to format from stdin
-///     $ cat my-obj.kcl | zoo kcl fmt
-#[derive(Parser, Debug, Clone)]
-#[clap(verbatim_doc_comment)]
-pub struct CmdKclFormat {
-    /// The path to the input kcl file to format.
-    /// If you pass `-` as the path, the file will be read from stdin.
-    #[clap(name = "input", required = true)]
-    pub input: std::path::PathBuf,
+impl CmdKclExport {
+    /// Write the exported files to the zip archive or output directory, or upload them.
+    async fn write_files(&self, ctx: &mut crate::context::Context, files: &[(String, Vec<u8>)]) -> Result<()> {
+        #[cfg(feature = "cloud-export")]
+        if let Some(location) = self.remote_output()? {
+            return self.upload_files(ctx, &location, files).await;
+        }
 
-    /// Write the output back to the original file.
-    /// This will fail if the input is from stdin.
-    #[clap(short, long)]
-    pub write: bool,
+        let start = std::time::Instant::now();
+        // Show how far along we are in a single line that is rewritten for each file, if
+        // there is a person watching and more than one file.
+        let progress = ctx.io.is_stderr_tty() && files.len() > 1;
+        if let Some(zip) = &self.zip {
+            // Pack the files into a single archive.
+            let mut writer = zip::ZipWriter::new(std::fs::File::create(zip)?);
+            let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+            for (i, (name, contents)) in files.iter().enumerate() {
+                writer.start_file(name.as_str(), options)?;
+                writer.write_all(&self.export_contents(name, contents)?)?;
+                if progress {
+                    write_progress(ctx, i + 1, files.len(), name)?;
+                }
+            }
+            writer.finish()?;
+            if progress {
+                clear_progress(ctx)?;
+            }
+            println!("Wrote file: {}", zip.display());
+        } else if let Some(output_dir) = &self.output_dir {
+            // Save the files to our export directory.
+            let mut written = Vec::with_capacity(files.len());
+            for (i, (name, contents)) in files.iter().enumerate() {
+                let path = output_dir.join(name);
+                std::fs::write(&path, self.export_contents(name, contents)?)?;
+                if progress {
+                    // Wait until the progress is done, so the two don't get mixed up.
+                    write_progress(ctx, i + 1, files.len(), name)?;
+                    written.push(path);
+                } else {
+                    println!("Wrote file: {}", path.display());
+                }
+            }
+            if progress {
+                clear_progress(ctx)?;
+            }
+            for path in written {
+                println!("Wrote file: {}", path.display());
+            }
+        }
+        ctx.record_timing("write", start);
 
-    /// Size of a tab in spaces.
-    #[clap(long, short, default_value = "2")]
-    pub tab_size: usize,
+        Ok(())
+    }
 
-    /// Prefer tabs over spaces.
-    #[clap(long, default_value = "false")]
-    pub use_tabs: bool,
+    /// The object storage to upload the files to, if the output directory is an `s3://`
+    /// or `gs://` URL.
+    #[cfg(feature = "cloud-export")]
+    fn remote_output(&self) -> Result<Option<crate::object_storage::Location>> {
+        let output_dir = self.output_dir.as_ref().and_then(|output_dir| output_dir.to_str());
+        output_dir.and_then(crate::object_storage::Location::parse).transpose()
+    }
 
-    /// How to handle the final newline in the file. If true, ensure file ends with a newline. If false, ensure file does not end with a newline.
-    #[clap(long, default_value = "true")]
-    pub insert_final_newline: bool,
+    /// Without the `cloud-export` feature there is nothing to upload with, so make sure a
+    /// URL isn't mistaken for a directory.
+    #[cfg(not(feature = "cloud-export"))]
+    fn remote_output(&self) -> Result<Option<std::convert::Infallible>> {
+        let output_dir = self.output_dir.as_ref().and_then(|output_dir| output_dir.to_str());
+        if let Some(output_dir) = output_dir.filter(|dir| dir.starts_with("s3://") || dir.starts_with("gs://")) {
+            anyhow::bail!(
+                "exporting to `{}` needs zoo to be built with the `cloud-export` feature",
+                output_dir
+            );
+        }
+        Ok(None)
+    }
 
-    /// Command output format.
-    #[clap(long, short, value_enum)]
-    pub format: Option<crate::types::FormatOutput>,
-}
+    /// Upload the exported files to object storage, one at a time as they are rewritten.
+    #[cfg(feature = "cloud-export")]
+    async fn upload_files(
+        &self,
+        ctx: &mut crate::context::Context,
+        location: &crate::object_storage::Location,
+        files: &[(String, Vec<u8>)],
+    ) -> Result<()> {
+        let start = std::time::Instant::now();
+        let client = reqwest::Client::builder()
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), ".rs/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let progress = ctx.io.is_stderr_tty() && files.len() > 1;
+        let mut uploaded = Vec::with_capacity(files.len());
+        for (i, (name, contents)) in files.iter().enumerate() {
+            location
+                .upload(&client, name, self.export_contents(name, contents)?)
+                .await?;
+            if progress {
+                write_progress(ctx, i + 1, files.len(), name)?;
+                uploaded.push(location.url(name));
+            } else {
+                println!("Wrote file: {}", location.url(name));
+            }
+        }
+        if progress {
+            clear_progress(ctx)?;
+        }
+        for url in uploaded {
+            println!("Wrote file: {}", url);
+        }
+        ctx.record_timing("upload", start);
 
-#[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclFormat {
-    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or("-"))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        Ok(())
+    }
 
-        // Parse the file.
-        let program = kcl_lib::Program::parse_no_errs(input)?;
+    /// Import the exported files back into the engine, if `--validate-output` was given,
+    /// and fail if any of them can't be.
+    async fn validate_files(&self, ctx: &mut crate::context::Context, files: &[(String, Vec<u8>)]) -> Result<()> {
+        if !self.validate_output {
+            return Ok(());
+        }
 
-        // Recast the program to a string.
-        let formatted = program.recast_with_options(&kcl_lib::FormatOptions {
-            tab_size: self.tab_size,
-            use_tabs: self.use_tabs,
-            insert_final_newline: self.insert_final_newline,
-        });
+        let start = std::time::Instant::now();
+        // Check what was written, after any rewriting, not what the engine sent.
+        let written = files
+            .iter()
+            .map(|(name, contents)| Ok((name.clone(), self.export_contents(name, contents)?)))
+            .collect::<Result<std::collections::HashMap<_, _>>>()?;
+
+        let mut failed = Vec::new();
+        let mut engine = None;
+        for (name, contents) in files.iter().map(|(name, _)| (name, &written[name])) {
+            let ext = crate::cmd_file::get_extension(std::path::PathBuf::from(name)).to_lowercase();
+            // Buffers, material libraries and the like are checked with the file that uses them.
+            let Ok(src_format) = crate::cmd_file::get_import_format_from_extension(&ext) else {
+                continue;
+            };
+            // The units only change the scale, not whether the file loads.
+            let src_format = crate::cmd_file::get_input_format(src_format, kittycad::types::UnitLength::Mm)?;
+
+            let import = crate::cmd_file::import_files(name, contents.clone(), &src_format, |uri| {
+                written
+                    .get(uri)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("`{}` refers to `{}`, which was not exported", name, uri))
+            });
+            let result = match import {
+                Ok(import) => {
+                    if engine.is_none() {
+                        engine = Some(ctx.engine("", None).await?);
+                    }
+                    let engine = engine.as_ref().unwrap();
+                    crate::cmd_file::import_into_engine(engine, import, src_format)
+                        .await
+                        .map(|_| ())
+                }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => writeln!(ctx.io.out, "Validated file: {}", name)?,
+                Err(err) => failed.push(format!("{}: {}", name, err)),
+            }
+        }
+        ctx.record_timing("validate", start);
 
-        if self.write {
-            if self.input.to_str().unwrap_or("-") == "-" {
-                anyhow::bail!("cannot write to stdin");
+        if engine.is_none() && failed.is_empty() {
+            writeln!(
+                ctx.io.err_out,
+                "warning: none of the exported files can be imported, so they were not validated"
+            )?;
+        }
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "{} exported file(s) failed validation:\n{}",
+                failed.len(),
+                failed.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Print how long each phase took, if `--log-timings` was given.
+    fn write_timings(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if let Some(timings) = &ctx.timings {
+            timings.write_report(&mut ctx.io.err_out)?;
+        }
+
+        Ok(())
+    }
+
+    /// The formats to export to, without repeats, along with the options the engine gets
+    /// for each. With more than one format, `--units-out` and the gltf options only
+    /// apply to the formats that take them.
+    fn output_formats(&self, src_unit: kcmc::units::UnitLength) -> Result<Vec<(kt::FileExportFormat, OutputFormat)>> {
+        let mut output_formats: Vec<kt::FileExportFormat> = Vec::with_capacity(self.output_format.len());
+        for output_format in &self.output_format {
+            if !output_formats.contains(output_format) {
+                output_formats.push(output_format.clone());
             }
+        }
+        let single = output_formats.len() == 1;
+        if !single && self.format_options.is_some() {
+            anyhow::bail!("`--format-options` can only be used with a single output format");
+        }
 
-            // Write the formatted file back to the original file.
-            std::fs::write(&self.input, formatted)?;
-        } else if let Some(format) = &self.format {
-            if format == &crate::types::FormatOutput::Json {
-                // Print the formatted file to stdout as json.
-                writeln!(ctx.io.out, "{}", serde_json::to_string_pretty(&program)?)?;
+        let mut formats = Vec::with_capacity(output_formats.len());
+        let (mut units_out_used, mut gltf_options_used) = (false, false);
+        for output_format in output_formats {
+            let mut format = get_output_format(&output_format, src_unit);
+            match apply_units_out(format.clone(), &self.units_out) {
+                Ok(with_units) => {
+                    format = with_units;
+                    units_out_used = true;
+                }
+                Err(err) if single => return Err(err),
+                Err(_) => {}
+            }
+            match apply_gltf_options(format.clone(), &self.gltf_presentation, &self.gltf_storage) {
+                Ok(with_options) => {
+                    format = with_options;
+                    gltf_options_used = true;
+                }
+                Err(err) if single => return Err(err),
+                Err(_) => {}
+            }
+            if let Some(format_options) = &self.format_options {
+                format = apply_format_options(format, format_options)?;
+            }
+            formats.push((output_format, format));
+        }
+
+        if self.units_out.is_some() && !units_out_used {
+            anyhow::bail!("`--units-out` can only be used with obj, ply or stl output");
+        }
+        if (self.gltf_presentation.is_some() || self.gltf_storage.is_some()) && !gltf_options_used {
+            anyhow::bail!("`--gltf-presentation` and `--gltf-storage` can only be used with gltf or glb output");
+        }
+        Ok(formats)
+    }
+
+    /// The key for this export in the cache, made from everything that can change what
+    /// the engine sends back. Keep this in sync with the docs for `--cache`.
+    fn cache_key(
+        &self,
+        ctx: &crate::context::Context,
+        code: &str,
+        path: &std::path::Path,
+        executor_settings: &kcl_lib::ExecutorSettings,
+        format: &kittycad_modeling_cmds::format::OutputFormat,
+    ) -> Result<String> {
+        let mut key = crate::export_cache::CacheKey::new();
+        key.add("code", code.as_bytes());
+
+        // Stdin and URLs can't import anything, since imports are relative to the file.
+        if self.input.to_str() != Some("-") && input_url(&self.input).is_none() {
+            let root = std::fs::canonicalize(path)?;
+            let mut deps = Vec::new();
+            collect_kcl_dependencies(&root, &mut vec![root.clone()], &mut Default::default(), &mut deps)?;
+            for dep in deps {
+                key.add("import", dep.path.as_bytes());
+                // A missing import is part of the key too, so creating it is a change.
+                let contents = if dep.missing {
+                    None
+                } else {
+                    Some(std::fs::read(&dep.path)?)
+                };
+                key.add("contents", contents.as_deref().unwrap_or(b"<missing>"));
             }
-        } else {
-            // Print the formatted file to stdout.
-            writeln!(ctx.io.out, "{}", formatted)?;
         }
 
+        key.add("settings", format!("{:?}", executor_settings).as_bytes());
+        key.add("format", &serde_json::to_vec(format)?);
+        for id in &self.entity_ids {
+            key.add("entity", id.as_bytes());
+        }
+        key.add("host", ctx.config.default_host()?.as_bytes());
+        key.add("engine-host", ctx.engine_host.as_deref().unwrap_or_default().as_bytes());
+
+        Ok(key.finish())
+    }
+
+    /// Where `--if-changed` stores the hash of the export: next to the zip archive, or in
+    /// the output directory, named after the input and output formats so different
+    /// exports to the same place don't overwrite each other's.
+    fn stamp_path(&self) -> Result<std::path::PathBuf> {
+        if self.remote_output()?.is_some() {
+            anyhow::bail!("`--if-changed` can only be used when exporting to a local directory or zip archive");
+        }
+        if let Some(zip) = &self.zip {
+            let name = zip.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+            return Ok(zip.with_file_name(format!(".{}.zoo-export-hash", name)));
+        }
+
+        let output_dir = self
+            .output_dir
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no output directory"))?;
+        let stem = match self.input.to_str() {
+            Some("-") => "stdin".to_string(),
+            _ => self
+                .input
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        let formats = self
+            .output_format
+            .iter()
+            .map(|format| format.to_string())
+            .collect::<Vec<_>>()
+            .join("-");
+        Ok(output_dir.join(format!(".{}.{}.zoo-export-hash", stem, formats)))
+    }
+
+    /// The hash `--if-changed` compares, of everything in the cache key, along with the
+    /// options that change the files after they come back from the engine.
+    fn stamp_hash(
+        &self,
+        ctx: &crate::context::Context,
+        code: &str,
+        path: &std::path::Path,
+        executor_settings: &kcl_lib::ExecutorSettings,
+        formats: &[(kt::FileExportFormat, OutputFormat)],
+    ) -> Result<String> {
+        let mut key = crate::export_cache::CacheKey::new();
+        for (_, format) in formats {
+            key.add(
+                "export",
+                self.cache_key(ctx, code, path, executor_settings, format)?.as_bytes(),
+            );
+        }
+        key.add("deterministic", &[self.deterministic as u8]);
+        key.add("strip-metadata", &[self.strip_metadata as u8]);
+        let thumbnail = self.thumbnail.as_ref().map(|thumbnail| thumbnail.to_string_lossy());
+        key.add("thumbnail", thumbnail.unwrap_or_default().as_bytes());
+        Ok(key.finish())
+    }
+
+    /// Store the hash of an export next to it, for `--if-changed`.
+    fn write_stamp(&self, stamp_path: &std::path::Path, hash: &str, files: &[(String, Vec<u8>)]) -> Result<()> {
+        let mut written: Vec<std::path::PathBuf> = match (&self.zip, &self.output_dir) {
+            (Some(zip), _) => vec![zip.clone()],
+            (None, Some(output_dir)) => files.iter().map(|(name, _)| output_dir.join(name)).collect(),
+            (None, None) => Vec::new(),
+        };
+        written.extend(self.thumbnail.clone());
+
+        let stamp = ExportStamp {
+            hash: hash.to_string(),
+            files: written,
+        };
+        std::fs::write(stamp_path, serde_json::to_vec_pretty(&stamp)?)?;
         Ok(())
     }
+
+    /// The contents to write for an exported file, after making it deterministic and
+    /// stripping its metadata, if asked to.
+    fn export_contents(&self, name: &str, contents: &[u8]) -> Result<Vec<u8>> {
+        let mut contents = contents.to_vec();
+        if self.strip_metadata {
+            contents = crate::strip_metadata::strip_metadata(name, &contents)?;
+        }
+        if self.deterministic {
+            contents = deterministic_export_contents(&contents)?;
+        }
+        Ok(contents)
+    }
 }
 
-/// Snapshot a render of a `kcl` file as any supported image format.
+/// Run a `kcl` file on the engine, then send it more modeling commands.
 ///
-///     # snapshot as png
-///     $ zoo kcl snapshot my-file.kcl my-file.png
+///     # check that a file runs
+///     $ zoo kcl run my-file.kcl
 ///
-///     # pass a file to snapshot from stdin
-///     $ cat my-obj.kcl | zoo kcl snapshot --output-format=png - my-file.png
+///     # then send the commands in a file, one JSON modeling command per line
+///     $ zoo kcl run my-file.kcl --cmds cmds.jsonl
+///
+/// The commands are sent in order over the same engine connection the file ran on, and
+/// the response to each is printed as a line of JSON, along with the line of the command:
+///
+///     {"line":1,"response":{"type":"modeling","data":{...}}}
+///
+/// All the commands are checked before the file is run, and the command stops at the
+/// first that fails.
 ///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
@@ -255,151 +917,2764 @@ impl crate::cmd::Command for CmdKclFormat {
 /// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclSnapshot {
-    /// The path to the input kcl file to snapshot.
+pub struct CmdKclRun {
+    /// The path to the input kcl file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// The path to a file to output the image.
-    #[clap(name = "output-file", required = true)]
-    pub output_file: std::path::PathBuf,
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
 
-    /// A valid output image format.
-    #[clap(short = 't', long = "output-format", value_enum)]
-    output_format: Option<kittycad::types::ImageFormat>,
+    /// A file of modeling commands to send after running the file, one JSON object per
+    /// line. Blank lines are skipped.
+    /// If you pass `-` as the path, the commands will be read from stdin.
+    #[clap(long)]
+    pub cmds: Option<std::path::PathBuf>,
 
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
-    /// Command output format.
-    #[clap(long, short, value_enum)]
-    pub format: Option<crate::types::FormatOutput>,
-
-    /// If given, this command will reuse an existing KittyCAD modeling session.
-    /// You can start the session via `zoo session-start --listen-on 0.0.0.0:3333` in this CLI.
-    #[clap(long, default_value = None)]
-    pub session: Option<SocketAddr>,
-
     /// If true, print a link to this request's tracing data.
     #[clap(long, default_value = "false")]
     pub show_trace: bool,
+}
 
-    /// If true, tell engine to store a replay.
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclRun {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let cmds = match &self.cmds {
+            Some(cmds_path) => {
+                if cmds_path.to_str() == Some("-") && self.input.to_str() == Some("-") {
+                    anyhow::bail!("the input and `--cmds` can't both be read from stdin");
+                }
+                let contents = ctx.read_file(cmds_path.to_str().unwrap_or(""))?;
+                parse_modeling_cmds(&String::from_utf8(contents)?, cmds_path)?
+            }
+            None => Vec::new(),
+        };
+
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+
+        // Get the modeling settings from the project.toml if exists.
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        let (engine_ctx, session_data) = ctx.execute_kcl("", &code, executor_settings).await?;
+        for (line, cmd) in cmds {
+            let resp = engine_ctx
+                .engine
+                .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), cmd)
+                .await
+                .map_err(|err| {
+                    anyhow::anyhow!(
+                        "the command on line {} of `{}` failed: {}",
+                        line,
+                        self.cmds.as_deref().unwrap_or(std::path::Path::new("-")).display(),
+                        err
+                    )
+                })?;
+            let resp = serde_json::json!({ "line": line, "response": resp });
+            writeln!(ctx.io.out, "{}", serde_json::to_string(&resp)?)?;
+        }
+
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        }
+        Ok(())
+    }
+}
+
+/// Parse a file of modeling commands, one JSON object per line, returning each with the
+/// line it is on, starting at 1.
+fn parse_modeling_cmds(contents: &str, path: &std::path::Path) -> Result<Vec<(usize, kcmc::ModelingCmd)>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let cmd = serde_json::from_str(line).map_err(|err| {
+                anyhow::anyhow!(
+                    "line {} of `{}` is not a valid modeling command: {}",
+                    i + 1,
+                    path.display(),
+                    err
+                )
+            })?;
+            Ok((i + 1, cmd))
+        })
+        .collect()
+}
+
+/// List the entities in a `kcl` file.
+///
+/// This lists the ids of the solids in the model, which can be passed to
+/// `zoo kcl export --entity-ids` to only export part of a model.
+///
+///     $ zoo kcl entities my-file.kcl
+///
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl entities -
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclEntities {
+    /// The path to the input kcl file.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// If true, print a link to this request's tracing data.
+    #[clap(long, default_value = "false")]
+    pub show_trace: bool,
+}
+
+/// An entity in a `kcl` model.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct KclEntity {
+    /// The id of the entity.
+    pub id: uuid::Uuid,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclEntities {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
+
+        // Get the modeling settings from the project.toml if exists.
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        let (engine_ctx, session_data) = ctx.execute_kcl("", input, executor_settings).await?;
+        let entities = get_solid_entity_ids(&engine_ctx)
+            .await?
+            .into_iter()
+            .map(|id| KclEntity { id })
+            .collect::<Vec<_>>();
+
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, entities)?;
+
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        }
+        Ok(())
+    }
+}
+
+/// Make a solid fully transparent, so only its edges are drawn.
+fn see_through(object_id: uuid::Uuid) -> kcmc::ModelingCmd {
+    kcmc::ModelingCmd::ObjectSetMaterialParamsPbr(kcmc::ObjectSetMaterialParamsPbr {
+        object_id,
+        color: kcmc::shared::Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 0.0,
+        },
+        metalness: 0.0,
+        roughness: 1.0,
+        ambient_occlusion: 0.0,
+    })
+}
+
+/// Get the ids of all the solids in the scene.
+async fn get_solid_entity_ids(engine_ctx: &kcl_lib::ExecutorContext) -> Result<Vec<uuid::Uuid>> {
+    let resp = engine_ctx
+        .engine
+        .send_modeling_cmd(
+            uuid::Uuid::new_v4(),
+            kcl_lib::SourceRange::default(),
+            kcmc::ModelingCmd::SceneGetEntityIds(kcmc::SceneGetEntityIds {
+                filter: vec![kcmc::shared::EntityType::Solid3D],
+                skip: 0,
+                take: u32::MAX,
+            }),
+        )
+        .await?;
+
+    if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+        modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::SceneGetEntityIds(data),
+    } = &resp
+    {
+        Ok(data.entity_ids.iter().flatten().copied().collect())
+    } else {
+        return Err(crate::engine_response::unexpected("the entity ids", &resp));
+    }
+}
+
+/// Format a `kcl` file.
+///
+///     # Output to stdout by default
+///     $ zoo kcl fmt my-file.kcl
+///
+///     # Overwrite the file
+///     $ zoo kcl fmt -w my-file.kcl
+///
+///     # Pass a file to format from stdin
+///     $ cat my-obj.kcl | zoo kcl fmt -
+///
+///     # Pass a file from stdin, but use the settings for where it lives on disk
+///     $ cat my-obj.kcl | zoo kcl fmt --stdin-filepath=src/my-obj.kcl -
+///
+///     # Print every file in a directory formatted, each under a `// === path ===` header
+///     $ zoo kcl fmt my-project | less
+///
+///     # Overwrite every file in a directory
+///     $ zoo kcl fmt -w my-project
+///
+///     # Overwrite the file, but only if formatting it again would not change it
+///     $ zoo kcl fmt -w --verify my-file.kcl
+///
+///     # List the files that are not formatted, and fail if there are any
+///     $ zoo kcl fmt --check main.kcl parts/*.kcl
+///
+///     # Check every kcl file in a project
+///     $ zoo kcl fmt --check my-project
+///
+///     # Check the kcl files staged in git, for a pre-commit hook
+///     $ zoo kcl fmt --check --staged
+///
+///     # Sort the imports too
+///     $ zoo kcl fmt -w --sort-imports main.kcl
+///
+/// Unless they are set on the command line, the indentation and final newline settings
+/// are read from the `.editorconfig` files in the directory of the input file or any parent
+/// directories above that.
+///
+/// Files with CRLF line endings are read fine, and are formatted with LF line endings
+/// unless `.editorconfig` sets `end_of_line = crlf`.
+///
+/// When formatting or checking a directory, hidden files, `target` and `node_modules`
+/// directories, and the paths listed in `.zooignore` files, which are written like
+/// `.gitignore` files, are skipped.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclFormat {
+    /// The path to the input kcl file to format, or a directory to format all the `.kcl`
+    /// files in.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    /// With `--check`, this can be several files or directories.
+    #[clap(name = "input", required_unless_present = "staged")]
+    pub input: Vec<std::path::PathBuf>,
+
+    /// Write the output back to the original file.
+    /// This will fail if the input is from stdin.
+    #[clap(short, long)]
+    pub write: bool,
+
+    /// Don't print or write anything formatted, instead print the files that are not
+    /// formatted, and fail if there are any.
+    #[clap(long, default_value = "false", conflicts_with = "write")]
+    pub check: bool,
+
+    /// Check the `.kcl` files staged in git, instead of the files given.
+    /// The staged contents are checked, not the ones in the working tree.
+    /// This only works inside a git repository.
+    #[clap(long, default_value = "false", requires = "check", conflicts_with = "input")]
+    pub staged: bool,
+
+    /// How many levels of directories to look in for `.kcl` files, when formatting or
+    /// checking a directory. `1` only looks at the files in the directory itself.
+    /// This defaults to no limit.
+    #[clap(long)]
+    pub max_depth: Option<usize>,
+
+    /// Size of a tab in spaces.
+    /// This defaults to 2, if not set and there is no `.editorconfig`.
+    #[clap(long, short)]
+    pub tab_size: Option<usize>,
+
+    /// Prefer tabs over spaces. Use `--use-tabs=false` to indent with spaces even if
+    /// `.editorconfig` asks for tabs.
+    /// This defaults to false, if not set and there is no `.editorconfig`.
+    #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub use_tabs: Option<bool>,
+
+    /// How to handle the final newline in the file. If true, ensure file ends with a newline. If false, ensure file does not end with a newline.
+    /// This defaults to true, if not set and there is no `.editorconfig`.
+    #[clap(long)]
+    pub insert_final_newline: Option<bool>,
+
+    /// The path of the file being read from stdin.
+    /// This is only used to find the formatting settings and in error messages, the file
+    /// itself is not read. This is useful for editor integrations.
+    #[clap(long)]
+    pub stdin_filepath: Option<std::path::PathBuf>,
+
+    /// Format the output a second time and check it does not change.
+    /// If it does, the file is not printed or written, and this fails.
+    #[clap(long, default_value = "false")]
+    pub verify: bool,
+
+    /// Sort the `import` statements by the file they import, and the names in each one.
+    /// Imports separated by a blank line or a comment are sorted as separate groups.
+    /// This is off by default, since the order of imports can matter.
+    #[clap(long, default_value = "false")]
+    pub sort_imports: bool,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclFormat {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if self.check {
+            return self.run_check(ctx).await;
+        }
+
+        let [input_path] = &self.input[..] else {
+            anyhow::bail!("only one file can be formatted at a time, use `--check` to check several");
+        };
+        let is_stdin = input_path.to_str().unwrap_or("-") == "-";
+        if self.stdin_filepath.is_some() && !is_stdin {
+            anyhow::bail!("`--stdin-filepath` can only be used when reading from stdin");
+        }
+        if input_path.is_dir() {
+            return self.run_dir(ctx, input_path);
+        }
+
+        let is_url = input_url(input_path).is_some();
+        if self.write && is_url {
+            anyhow::bail!("cannot write to a URL");
+        }
+
+        // Get the contents of the input file.
+        let input = read_kcl_input(ctx, input_path).await?;
+        // Parse the input as a string.
+        let input = kcl_text(&input, input_path)?;
+
+        // This is the path we use for settings and error messages.
+        let path = self.stdin_filepath.as_ref().unwrap_or(input_path);
+        let (program, formatted, options) =
+            self.format_code(input, path, !is_url && (!is_stdin || self.stdin_filepath.is_some()))?;
+
+        if self.write && is_stdin {
+            anyhow::bail!("cannot write to stdin");
+        }
+
+        if self.verify {
+            if let Err(err) = verify_format_is_stable(&formatted, &options) {
+                anyhow::bail!("`{}`: {}", path.display(), err);
+            }
+        }
+
+        if self.write {
+            // Write the formatted file back to the original file.
+            std::fs::write(input_path, formatted)?;
+        } else if let Some(format) = &self.format {
+            if format == &crate::types::FormatOutput::Json {
+                // Print the formatted file to stdout as json.
+                writeln!(ctx.io.out, "{}", serde_json::to_string_pretty(&program)?)?;
+            }
+        } else {
+            // Print the formatted file to stdout.
+            writeln!(ctx.io.out, "{}", formatted)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CmdKclFormat {
+    /// Parse and format some code, returning the program, the formatted code and the
+    /// options it was formatted with.
+    /// The `path` is used in error messages, and to find `.editorconfig` settings if
+    /// `use_editorconfig` is set.
+    fn format_code(
+        &self,
+        code: &str,
+        path: &std::path::Path,
+        use_editorconfig: bool,
+    ) -> Result<(kcl_lib::Program, String, kcl_lib::FormatOptions)> {
+        // Parse the file.
+        let code = normalize_line_endings(code);
+        let mut program = kcl_lib::Program::parse_no_errs(&code).map_err(|err| {
+            anyhow::anyhow!(
+                "failed to parse `{}`: {}",
+                path.display(),
+                crate::kcl_error_fmt::KclError::new(code.to_string(), err)
+            )
+        })?;
+        if self.sort_imports {
+            program = sort_imports(program)?;
+        }
+
+        // Flags take precedence over any `.editorconfig` settings.
+        let editorconfig = if use_editorconfig {
+            find_editorconfig_settings(path)?
+        } else {
+            EditorConfigSettings::default()
+        };
+
+        // Recast the program to a string.
+        let options = kcl_lib::FormatOptions {
+            tab_size: self.tab_size.or(editorconfig.indent_size).unwrap_or(2),
+            use_tabs: self.use_tabs.or(editorconfig.use_tabs).unwrap_or(false),
+            insert_final_newline: self
+                .insert_final_newline
+                .or(editorconfig.insert_final_newline)
+                .unwrap_or(true),
+        };
+        let formatted = with_line_endings(
+            program.recast_with_options(&options),
+            editorconfig.crlf.unwrap_or(false),
+        );
+
+        Ok((program, formatted, options))
+    }
+
+    /// Format every `.kcl` file in a directory. Without `--write`, the formatted files
+    /// are printed one after another, each under a `// === path ===` header.
+    fn run_dir(&self, ctx: &mut crate::context::Context<'_>, dir: &std::path::Path) -> Result<()> {
+        if self.format.is_some() {
+            anyhow::bail!("`--format` can only be used when formatting a single file");
+        }
+
+        let files = kcl_files_in_dir(dir, self.max_depth)?;
+        let mut changed = 0;
+        let mut unstable = 0;
+        for path in &files {
+            let input = ctx.read_file(path.to_str().unwrap_or(""))?;
+            let input = kcl_text(&input, path)?;
+            let (_, formatted, options) = self.format_code(input, path, true)?;
+
+            // Files whose formatting is not stable are skipped, and fail the command once
+            // the rest are done.
+            if self.verify {
+                if let Err(err) = verify_format_is_stable(&formatted, &options) {
+                    writeln!(ctx.io.err_out, "`{}`: {}", path.display(), err)?;
+                    unstable += 1;
+                    continue;
+                }
+            }
+
+            if !self.write {
+                write!(ctx.io.out, "// === {} ===\n{}", path.display(), formatted)?;
+                if !formatted.ends_with('\n') {
+                    writeln!(ctx.io.out)?;
+                }
+            } else if formatted != input {
+                std::fs::write(path, formatted)?;
+                writeln!(ctx.io.out, "{}", path.display())?;
+                changed += 1;
+            }
+        }
+
+        if self.write {
+            writeln!(
+                ctx.io.out,
+                "Formatted directory `{}`: {} of {} file{} changed",
+                dir.display(),
+                changed,
+                files.len(),
+                if files.len() == 1 { "" } else { "s" }
+            )?;
+        }
+
+        if unstable > 0 {
+            anyhow::bail!(
+                "the formatting of {} file{} is not stable",
+                unstable,
+                if unstable == 1 { "" } else { "s" }
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Print the files that are not formatted, and fail if there are any.
+    async fn run_check(&self, ctx: &mut crate::context::Context<'_>) -> Result<()> {
+        let files = if self.staged {
+            staged_kcl_files()?
+        } else {
+            let mut files = Vec::with_capacity(self.input.len());
+            for input_path in &self.input {
+                let is_stdin = input_path.to_str().unwrap_or("-") == "-";
+                if self.stdin_filepath.is_some() && !is_stdin {
+                    anyhow::bail!("`--stdin-filepath` can only be used when reading from stdin");
+                }
+                if input_path.is_dir() {
+                    for path in kcl_files_in_dir(input_path, self.max_depth)? {
+                        let input = ctx.read_file(path.to_str().unwrap_or(""))?;
+                        files.push((path, input));
+                    }
+                    continue;
+                }
+                let input = read_kcl_input(ctx, input_path).await?;
+                let path = self.stdin_filepath.clone().unwrap_or_else(|| input_path.clone());
+                files.push((path, input));
+            }
+            files
+        };
+
+        let mut unformatted = 0;
+        for (path, input) in files {
+            let input = kcl_text(&input, &path)?;
+            // Only stdin without `--stdin-filepath`, and URLs, have no settings to look for.
+            let use_editorconfig = path.to_str() != Some("-") && input_url(&path).is_none();
+            let (_, formatted, _) = self.format_code(input, &path, use_editorconfig)?;
+            if formatted != input {
+                unformatted += 1;
+                writeln!(ctx.io.out, "{}", path.display())?;
+            }
+        }
+
+        if unformatted > 0 {
+            anyhow::bail!(
+                "{} file{} not formatted, run `zoo kcl fmt -w` to format {}",
+                unformatted,
+                if unformatted == 1 { " is" } else { "s are" },
+                if unformatted == 1 { "it" } else { "them" }
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The name of the files that list paths for directory-wide commands to skip, in the
+/// same format as `.gitignore`.
+const ZOOIGNORE: &str = ".zooignore";
+
+/// Directories of build output or downloaded packages, which directory-wide commands
+/// never look in.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target"];
+
+/// The `.kcl` files in a directory and the directories under it, sorted by path.
+/// Hidden files and directories, like `.git`, are skipped, as are build output
+/// directories and paths matched by a `.zooignore` file in the directory, under it or
+/// above it.
+/// With a `max_depth`, only files that many levels down are collected, so `1` is only
+/// the files in the directory itself.
+/// Symlinks are followed, but each file is only collected once and symlinks that lead
+/// back to a directory above them are skipped.
+fn kcl_files_in_dir(dir: &std::path::Path, max_depth: Option<usize>) -> Result<Vec<std::path::PathBuf>> {
+    let walker = ignore::WalkBuilder::new(dir)
+        .standard_filters(false)
+        .hidden(true)
+        .parents(true)
+        .add_custom_ignore_filename(ZOOIGNORE)
+        .max_depth(max_depth)
+        .follow_links(true)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+            entry.depth() == 0 || !is_dir || !SKIPPED_DIRS.iter().any(|name| entry.file_name() == *name)
+        })
+        .build();
+
+    let mut files = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) if is_symlink_loop(&err) => continue,
+            Err(err) => anyhow::bail!("failed to read directory `{}`: {}", dir.display(), err),
+        };
+        let is_file = entry.file_type().is_some_and(|file_type| file_type.is_file());
+        if !is_file || !entry.path().extension().is_some_and(|ext| ext == "kcl") {
+            continue;
+        }
+        // The same file can be reached through more than one symlink.
+        if seen.insert(std::fs::canonicalize(entry.path())?) {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// If walking a directory failed because a symlink leads back to a directory above it.
+fn is_symlink_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_symlink_loop(err),
+        _ => false,
+    }
+}
+
+/// Get the `.kcl` files staged in git, as their paths in the working tree, and their
+/// staged contents.
+fn staged_kcl_files() -> Result<Vec<(std::path::PathBuf, Vec<u8>)>> {
+    let git = |args: &[&str]| -> Result<Vec<u8>> {
+        let output = std::process::Command::new("git").args(args).output();
+        match output {
+            Ok(output) if output.status.success() => Ok(output.stdout),
+            Ok(output) => anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim()),
+            Err(err) => anyhow::bail!("failed to run git: {}", err),
+        }
+    };
+
+    let root = git(&["rev-parse", "--show-toplevel"]).map_err(|err| {
+        anyhow::anyhow!(
+            "`--staged` only works inside a git repository, pass the files to check instead: {}",
+            err
+        )
+    })?;
+    let root = std::path::PathBuf::from(String::from_utf8(root)?.trim());
+
+    // Deleted files have nothing to check.
+    let names = git(&["diff", "--cached", "--name-only", "--diff-filter=d", "-z"])?;
+    let mut files = Vec::new();
+    for name in String::from_utf8(names)?
+        .split('\0')
+        .filter(|name| name.ends_with(".kcl"))
+    {
+        let contents = git(&["show", &format!(":{}", name)])?;
+        files.push((root.join(name), contents));
+    }
+
+    Ok(files)
+}
+
+/// Format already formatted code again, and make sure it comes out the same.
+/// Formatting should be idempotent, so an error here is a bug in the formatter.
+fn verify_format_is_stable(formatted: &str, options: &kcl_lib::FormatOptions) -> Result<()> {
+    // The line endings were set after formatting, so compare without them.
+    let formatted = normalize_line_endings(formatted);
+    let formatted = formatted.as_str();
+    let program = kcl_lib::Program::parse_no_errs(formatted).map_err(|err| {
+        anyhow::anyhow!(
+            "the formatted code does not parse: {}",
+            crate::kcl_error_fmt::KclError::new(formatted.to_string(), err)
+        )
+    })?;
+
+    let reformatted = program.recast_with_options(options);
+    if reformatted != formatted {
+        let line = formatted
+            .lines()
+            .zip(reformatted.lines())
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| formatted.lines().count().min(reformatted.lines().count()))
+            + 1;
+        anyhow::bail!("formatting is not stable, formatting it again changes line {}", line);
+    }
+
+    Ok(())
+}
+
+/// The formatting settings from `.editorconfig` files that apply to a `kcl` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorConfigSettings {
+    /// If the file should be indented with tabs.
+    pub use_tabs: Option<bool>,
+    /// The size of a tab in spaces.
+    pub indent_size: Option<usize>,
+    /// If the file should end with a newline.
+    pub insert_final_newline: Option<bool>,
+    /// If lines should end with CRLF, instead of LF.
+    pub crlf: Option<bool>,
+}
+
+impl EditorConfigSettings {
+    /// Fill in any settings we don't have yet from `other`.
+    fn or(self, other: EditorConfigSettings) -> EditorConfigSettings {
+        EditorConfigSettings {
+            use_tabs: self.use_tabs.or(other.use_tabs),
+            indent_size: self.indent_size.or(other.indent_size),
+            insert_final_newline: self.insert_final_newline.or(other.insert_final_newline),
+            crlf: self.crlf.or(other.crlf),
+        }
+    }
+}
+
+/// Search the directory of the path and its parents for `.editorconfig` files, and get the
+/// settings for `kcl` files. Files closer to the path take precedence, and we stop at a file
+/// with `root = true`.
+pub fn find_editorconfig_settings(path: &std::path::Path) -> Result<EditorConfigSettings> {
+    let mut dir = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    let mut settings = EditorConfigSettings::default();
+    while dir.pop() {
+        let editorconfig = dir.join(".editorconfig");
+        if !editorconfig.is_file() {
+            continue;
+        }
+
+        let (found, is_root) = parse_editorconfig(&std::fs::read_to_string(&editorconfig)?);
+        settings = settings.or(found);
+        if is_root {
+            break;
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Parse the settings for `kcl` files out of an `.editorconfig` file.
+/// This returns the settings and if the file has `root = true`.
+fn parse_editorconfig(contents: &str) -> (EditorConfigSettings, bool) {
+    let mut settings = EditorConfigSettings::default();
+    let mut is_root = false;
+    // Properties before any section are global, like `root`.
+    let mut section: Option<bool> = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = Some(editorconfig_glob_matches_kcl(glob));
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_lowercase();
+
+        match section {
+            None if key == "root" => is_root = value == "true",
+            Some(true) => match key.as_str() {
+                "indent_style" => settings.use_tabs = Some(value == "tab"),
+                "indent_size" | "tab_width" => {
+                    if let Ok(size) = value.parse() {
+                        settings.indent_size = Some(size);
+                    }
+                }
+                "insert_final_newline" => settings.insert_final_newline = Some(value == "true"),
+                "end_of_line" => settings.crlf = Some(value == "crlf"),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    (settings, is_root)
+}
+
+/// Read the contents of a `kcl` file as text, with a friendly error for files that are
+/// not, like a model with the wrong extension.
+fn kcl_text<'a>(code: &'a [u8], path: &std::path::Path) -> Result<&'a str> {
+    let looks_like = if code.starts_with(b"ISO-10303-21") {
+        Some("a STEP file")
+    } else if code.starts_with(b"glTF") {
+        Some("a glb file")
+    } else if code.starts_with(b"Kaydara FBX Binary") {
+        Some("an FBX file")
+    } else if code.starts_with(b"ply\n") || code.starts_with(b"ply\r\n") {
+        Some("a PLY file")
+    } else {
+        None
+    };
+    let text = std::str::from_utf8(code).ok().filter(|text| !text.contains('\0'));
+
+    let name = match path.to_str() {
+        Some("-") => "the input from stdin".to_string(),
+        _ => format!("`{}`", path.display()),
+    };
+    match (text, looks_like) {
+        (Some(text), None) => Ok(text),
+        (_, Some(looks_like)) => anyhow::bail!(
+            "{} does not look like KCL text, it looks like {}. Did you mean `zoo file`, like `zoo file convert` or `zoo file snapshot`?",
+            name,
+            looks_like
+        ),
+        (None, None) => anyhow::bail!(
+            "{} does not look like KCL text, it is a binary file. Did you mean `zoo file`, like `zoo file convert` or `zoo file snapshot`?",
+            name
+        ),
+    }
+}
+
+/// Turn CRLF line endings into LF, so code written on Windows reads the same as anywhere
+/// else.
+fn normalize_line_endings(code: &str) -> String {
+    code.replace("\r\n", "\n")
+}
+
+/// Give formatted code, which has LF line endings, CRLF ones if asked to.
+fn with_line_endings(formatted: String, crlf: bool) -> String {
+    if crlf {
+        formatted.replace('\n', "\r\n")
+    } else {
+        formatted
+    }
+}
+
+/// If an `.editorconfig` section glob applies to `kcl` files.
+/// We only understand the common cases, `*`, `*.kcl` and `*.{kcl,toml}`.
+fn editorconfig_glob_matches_kcl(glob: &str) -> bool {
+    let glob = glob.trim().trim_start_matches("**/").trim_start_matches('/');
+    if glob == "*" || glob == "*.kcl" {
+        return true;
+    }
+
+    glob.strip_prefix("*.{")
+        .and_then(|g| g.strip_suffix('}'))
+        .map(|exts| exts.split(',').any(|ext| ext.trim() == "kcl"))
+        .unwrap_or(false)
+}
+
+/// Snapshot a render of a `kcl` file as any supported image format.
+///
+///     # snapshot as png
+///     $ zoo kcl snapshot my-file.kcl my-file.png
+///
+///     # pass a file to snapshot from stdin
+///     $ cat my-obj.kcl | zoo kcl snapshot --output-format=png - my-file.png
+///
+///     # write the volume of the model onto the image
+///     $ zoo kcl snapshot --annotate my-file.kcl my-file.png
+///
+///     # write the image to stdout
+///     $ zoo kcl snapshot my-file.kcl - > my-file.png
+///
+///     # fail if more than 1% of the image changed since the baseline
+///     $ zoo kcl snapshot --compare baseline.png --threshold 1 --diff-output diff.png my-file.kcl my-file.png
+///
+///     # snapshot every file listed in files.txt, one path per line, to out/<stem>.png
+///     $ zoo kcl snapshot --from-list files.txt --output-dir out/
+///
+///     # leave more room around the model
+///     $ zoo kcl snapshot --padding 0.3 my-file.kcl my-file.png
+///
+///     # render the model with different values for some of its top level variables
+///     $ zoo kcl snapshot --param width=20 --param 'material="steel"' my-file.kcl my-file.png
+///
+///     # draw only the edges of the model, for a drawing style preview
+///     $ zoo kcl snapshot --wireframe my-file.kcl my-file.png
+///
+///     # render the model from the front, or from a point of your choosing
+///     $ zoo kcl snapshot --camera front my-file.kcl my-file.png
+///     $ zoo kcl snapshot --camera-vantage 200,-300,150 --camera-center 0,0,20 my-file.kcl my-file.png
+///
+///     # render a small thumbnail
+///     $ zoo kcl snapshot --width 320 my-file.kcl my-file.png
+///
+/// `--edges`, `--wireframe` and the camera options work with every image format, but not
+/// with `--session`, since the session renders with its own settings.
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclSnapshot {
+    /// The path to the input kcl file to snapshot.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required_unless_present = "from_list")]
+    pub input: Option<std::path::PathBuf>,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// The path to a file to output the image.
+    /// If you pass `-` as the path, the image will be written to stdout, as a png unless
+    /// `--output-format` says otherwise.
+    #[clap(name = "output-file", required_unless_present = "from_list")]
+    pub output_file: Option<std::path::PathBuf>,
+
+    /// A valid output image format.
+    #[clap(short = 't', long = "output-format", value_enum)]
+    output_format: Option<kittycad::types::ImageFormat>,
+
+    /// Write the image to stdout even if it is a terminal.
+    #[clap(long, default_value = "false")]
+    pub force: bool,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// If given, this command will reuse an existing KittyCAD modeling session.
+    /// You can start the session via `zoo session-start --listen-on 0.0.0.0:3333` in this CLI.
+    #[clap(long, default_value = None)]
+    pub session: Option<SocketAddr>,
+
+    /// If true, print a link to this request's tracing data.
+    #[clap(long, default_value = "false")]
+    pub show_trace: bool,
+
+    /// If true, tell engine to store a replay.
+    #[clap(long, default_value = "false")]
+    pub replay: bool,
+
+    /// If true, write the volume of the model onto the image.
+    /// This is only supported for png images.
+    #[clap(long, default_value = "false", conflicts_with = "session")]
+    pub annotate: bool,
+
+    /// If true, record the input file, the versions of `zoo` and kcl, and when the
+    /// snapshot was taken in the image's metadata, so it can be traced back to its source.
+    /// This is only supported for png images.
+    #[clap(long, default_value = "false")]
+    pub embed_metadata: bool,
+
+    /// If true, leave the time out of `--embed-metadata`, so the same input always gives
+    /// the same image metadata.
+    #[clap(long, default_value = "false", requires = "embed_metadata")]
+    pub deterministic: bool,
+
+    /// A png image to compare the snapshot to, pixel by pixel.
+    /// The percentage of pixels that differ is printed, and the command fails if it is
+    /// more than `--threshold`.
+    #[clap(long, conflicts_with = "annotate")]
+    pub compare: Option<std::path::PathBuf>,
+
+    /// The percentage of pixels that may differ from the `--compare` baseline.
+    #[clap(long, default_value = "0.0", requires = "compare")]
+    pub threshold: f64,
+
+    /// Write an image of where the snapshot differs from the `--compare` baseline
+    /// to this path, as a png.
+    #[clap(long, requires = "compare")]
+    pub diff_output: Option<std::path::PathBuf>,
+
+    /// Snapshot every kcl file listed in this file, instead of a single input.
+    /// The list has one path per line, relative to the current directory. Blank lines
+    /// and lines starting with `#` are skipped.
+    /// Each file is rendered to `<output-dir>/<stem>.<output-format>`, and a summary of
+    /// which files worked is printed at the end.
+    /// If you pass `-` as the path, the list will be read from stdin.
+    #[clap(long, conflicts_with_all = ["input", "output-file", "compare", "session"])]
+    pub from_list: Option<std::path::PathBuf>,
+
+    /// The directory to write the images to, with `--from-list`.
+    /// If not set this will be the current directory.
+    #[clap(long, requires = "from_list")]
+    pub output_dir: Option<std::path::PathBuf>,
+
+    /// The maximum number of files to render at the same time, with `--from-list`.
+    #[clap(long, default_value = "4", requires = "from_list")]
+    pub concurrency: usize,
+
+    /// How much room to leave around the model when zooming to fit it, as a fraction
+    /// of the image.
+    #[clap(long, default_value_t = crate::context::DEFAULT_ZOOM_PADDING, conflicts_with = "session")]
+    pub padding: f32,
+
+    /// Don't zoom to fit the model, and keep the default camera.
+    #[clap(long, default_value = "false", conflicts_with_all = ["padding", "session"])]
+    pub no_zoom: bool,
+
+    /// Render the model with a different value for a variable declared at the top level
+    /// of the file, as `name=value`, where the value is kcl, like `10` or `"steel"`.
+    /// This can be given more than once.
+    #[clap(long = "param", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+
+    /// Take the snapshot again, up to this many times, if it times out, like it can
+    /// while the engine starts up. Other errors are not retried, see `--engine-retries`.
+    #[clap(long, default_value = "0")]
+    pub retry_on_timeout: u32,
+
+    /// Draw the edges of the model over its shaded faces, even if the `project.toml`
+    /// turns off highlighting edges.
+    #[clap(long, default_value = "false", conflicts_with = "session")]
+    pub edges: bool,
+
+    /// Draw only the edges of the model, with see-through faces, like a line drawing.
+    #[clap(long, default_value = "false", conflicts_with_all = ["edges", "session"])]
+    pub wireframe: bool,
+
+    /// The camera view to render the model from.
+    /// This defaults to the engine's default camera.
+    #[clap(long, value_enum, conflicts_with = "session")]
+    pub camera: Option<CameraView>,
+
+    /// Place the camera at this point, as `x,y,z`, instead of using a `--camera` view.
+    /// The model isn't zoomed to fit, so the camera stays where it is put.
+    #[clap(long, value_parser = crate::cmd_file::parse_offset, conflicts_with_all = ["camera", "padding", "session"])]
+    pub camera_vantage: Option<kcmc::shared::Point3d<f64>>,
+
+    /// The point the camera looks at, as `x,y,z`, with `--camera-vantage`.
+    /// This defaults to the origin.
+    #[clap(long, value_parser = crate::cmd_file::parse_offset, requires = "camera_vantage")]
+    pub camera_center: Option<kcmc::shared::Point3d<f64>>,
+
+    /// The width of the image, in pixels, up to 4096.
+    /// If only one of `--width` and `--height` is given, the other keeps a 16:9 aspect
+    /// ratio. This defaults to the engine's resolution.
+    #[clap(long, conflicts_with = "session")]
+    pub width: Option<u32>,
+
+    /// The height of the image, in pixels, up to 4096.
+    #[clap(long, conflicts_with = "session")]
+    pub height: Option<u32>,
+}
+
+/// The result of snapshotting one file with `zoo kcl snapshot --from-list`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct SnapshotResult {
+    /// The input file, as it was listed.
+    pub input: String,
+    /// The image the snapshot was written to.
+    pub output: String,
+    /// If the snapshot worked.
+    pub success: bool,
+    /// Why the snapshot failed, or empty if it worked.
+    pub error: String,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclSnapshot {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        ctx.timeout_retries = self.retry_on_timeout;
+        ctx.video_resolution = video_resolution(self.width, self.height)?;
+        if let Some(list) = &self.from_list {
+            return self.run_batch(ctx, list).await;
+        }
+        let (Some(input), Some(output_file)) = (&self.input, &self.output_file) else {
+            anyhow::bail!("an input and output file are required, unless `--from-list` is given");
+        };
+
+        let to_stdout = output_file.to_str() == Some("-");
+        if to_stdout {
+            // Don't dump a binary image into someone's terminal.
+            if ctx.io.is_stdout_tty() && !self.force {
+                anyhow::bail!("refusing to write an image to a terminal, redirect stdout or use `--force`");
+            }
+            if self.show_trace {
+                anyhow::bail!("`--show-trace` cannot be used when writing the image to stdout");
+            }
+        } else if let Some(parent) = output_file.parent() {
+            // Make sure the parent directory is a directory and exists.
+            if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
+                anyhow::bail!(
+                    "directory `{}` does not exist or is not a directory",
+                    parent.to_str().unwrap_or("")
+                );
+            }
+        }
+
+        // Parse the image format.
+        let output_format = if let Some(output_format) = self.image_format() {
+            output_format
+        } else if to_stdout {
+            kittycad_modeling_cmds::ImageFormat::Png
+        } else {
+            get_image_format_from_extension(&crate::cmd_file::get_extension(output_file.clone()))?
+        };
+        self.check_png_options(output_format)?;
+        if self.compare.is_some() && !matches!(output_format, kittycad_modeling_cmds::ImageFormat::Png) {
+            anyhow::bail!("`--compare` is only supported for png images");
+        }
+        // Read the baseline up front, so we don't render for nothing.
+        let baseline = match &self.compare {
+            Some(path) => Some(
+                std::fs::read(path)
+                    .map_err(|err| anyhow::anyhow!("failed to read the baseline `{}`: {}", path.display(), err))?,
+            ),
+            None => None,
+        };
+
+        // Get the contents of the input file.
+        let (code, filepath) = get_code_and_file_path(ctx, input, self.assembly_main.as_deref()).await?;
+        let (output_file_contents, session_data) = self.render(ctx, input, code, &filepath, output_format).await?;
+        let diff = match &baseline {
+            Some(baseline) => Some(crate::image_diff::compare_png(&output_file_contents, baseline)?),
+            None => None,
+        };
+
+        if to_stdout {
+            // Only the image goes to stdout, so it can be piped somewhere else.
+            ctx.io.out.write_all(&output_file_contents)?;
+            ctx.io.out.flush()?;
+        } else {
+            // Save the snapshot locally.
+            std::fs::write(output_file, output_file_contents)?;
+
+            writeln!(ctx.io.out, "Snapshot saved to `{}`", output_file.to_str().unwrap_or(""))?;
+            if self.show_trace {
+                print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+            }
+        }
+
+        if let Some(diff) = diff {
+            self.report_diff(ctx, &diff, to_stdout)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CmdKclSnapshot {
+    /// The padding to zoom to fit the model with, or `None` with `--no-zoom` or a
+    /// camera placed with `--camera-vantage`.
+    fn zoom_padding(&self) -> Option<f32> {
+        (!self.no_zoom && self.camera_vantage.is_none()).then_some(self.padding)
+    }
+
+    /// The commands to set up the camera and how the model is drawn before we take a
+    /// snapshot.
+    fn setup_cmds(&self) -> Result<Vec<kcmc::ModelingCmd>> {
+        let mut cmds = match (&self.camera_vantage, &self.camera) {
+            (Some(vantage), _) => {
+                let vantage = [vantage.x, vantage.y, vantage.z];
+                let center = self
+                    .camera_center
+                    .as_ref()
+                    .map(|center| [center.x, center.y, center.z])
+                    .unwrap_or_default();
+                let direction = [0, 1, 2].map(|i| vantage[i] - center[i]);
+                if direction == [0.0; 3] {
+                    anyhow::bail!("`--camera-vantage` must not be the same point as `--camera-center`");
+                }
+                // Keep +Z up, unless we're looking straight along it.
+                let up = if direction[0] == 0.0 && direction[1] == 0.0 {
+                    [0.0, 1.0, 0.0]
+                } else {
+                    [0.0, 0.0, 1.0]
+                };
+                vec![look_at(vantage, center, up)]
+            }
+            (None, Some(camera)) => camera.modeling_cmds(),
+            (None, None) => vec![],
+        };
+        if self.edges || self.wireframe {
+            cmds.push(kcmc::ModelingCmd::EdgeLinesVisible(kcmc::EdgeLinesVisible {
+                hidden: false,
+            }));
+        }
+        Ok(cmds)
+    }
+
+    /// The image format from `--output-format`, if it was given.
+    fn image_format(&self) -> Option<kittycad_modeling_cmds::ImageFormat> {
+        self.output_format.as_ref().map(|output_format| match output_format {
+            kittycad::types::ImageFormat::Png => kittycad_modeling_cmds::ImageFormat::Png,
+            kittycad::types::ImageFormat::Jpeg => kittycad_modeling_cmds::ImageFormat::Jpeg,
+        })
+    }
+
+    fn check_png_options(&self, output_format: kittycad_modeling_cmds::ImageFormat) -> Result<()> {
+        if self.annotate && !matches!(output_format, kittycad_modeling_cmds::ImageFormat::Png) {
+            anyhow::bail!("`--annotate` is only supported for png images");
+        }
+        if self.embed_metadata && !matches!(output_format, kittycad_modeling_cmds::ImageFormat::Png) {
+            anyhow::bail!("`--embed-metadata` is only supported for png images");
+        }
+        Ok(())
+    }
+
+    /// Record where a snapshot of `input` came from in its metadata.
+    fn embed_metadata(&self, contents: &[u8], input: &std::path::Path) -> Result<Vec<u8>> {
+        let source = match input.to_str() {
+            Some("-") => "stdin".to_string(),
+            _ => input.display().to_string(),
+        };
+        let software = format!("zoo {}", clap::crate_version!());
+        let creation_time = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let mut entries = vec![
+            ("Source", source.as_str()),
+            ("Software", software.as_str()),
+            ("KCL Version", env!("KCL_LIB_VERSION")),
+        ];
+        if !self.deterministic {
+            entries.push(("Creation Time", creation_time.as_str()));
+        }
+        crate::png_metadata::add_text(contents, &entries)
+    }
+
+    /// Render a snapshot of the code read from `input`, annotated if asked to.
+    async fn render(
+        &self,
+        ctx: &crate::context::Context,
+        input: &std::path::Path,
+        code: String,
+        filepath: &std::path::Path,
+        output_format: kittycad_modeling_cmds::ImageFormat,
+    ) -> Result<(Vec<u8>, Option<kittycad_modeling_cmds::websocket::ModelingSessionData>)> {
+        let filename = filepath
+            .file_name()
+            .map(|b| b.to_string_lossy().to_string())
+            .unwrap_or("unknown".to_string());
+        let code = apply_params(&code, &self.params)?;
+
+        // Get the modeling settings from the project.toml if exists.
+        let mut executor_settings = get_modeling_settings_from_project_toml(input, self.src_unit.clone())?;
+        executor_settings.replay = self.replay.then_some(filename);
+
+        let mut rendered = match self.session {
+            Some(addr) => (
+                run_on_session(addr, code, input.display().to_string()).await?,
+                Default::default(),
+            ),
+            None => {
+                let mut cmds = vec![kittycad_modeling_cmds::ModelingCmd::TakeSnapshot(
+                    kittycad_modeling_cmds::TakeSnapshot { format: output_format },
+                )];
+                if self.annotate {
+                    // Measure over the same session, so the numbers match the picture.
+                    cmds.push(kittycad_modeling_cmds::ModelingCmd::Volume(
+                        kittycad_modeling_cmds::Volume {
+                            entity_ids: vec![], // get whole model
+                            output_unit: volume_unit_for(executor_settings.units.into())?,
+                        },
+                    ));
+                }
+
+                // Spin up websockets and do the conversion.
+                // This will not return until there are files.
+                let (responses, session_data) = if self.wireframe {
+                    // The solids are made see-through one by one, so we need their ids from
+                    // running the code first.
+                    let (engine_ctx, session_data) = ctx.execute_kcl("", &code, executor_settings).await?;
+                    let mut setup_cmds = self.setup_cmds()?;
+                    for id in get_solid_entity_ids(&engine_ctx).await? {
+                        setup_cmds.push(see_through(id));
+                    }
+                    let responses = ctx
+                        .send_modeling_cmds_with_setup(&engine_ctx, &code, &setup_cmds, &cmds, self.zoom_padding())
+                        .await?;
+                    (responses, session_data)
+                } else {
+                    ctx.send_kcl_modeling_cmds_with_setup(
+                        "",
+                        &code,
+                        &self.setup_cmds()?,
+                        &cmds,
+                        executor_settings,
+                        self.zoom_padding(),
+                    )
+                    .await?
+                };
+
+                let mut contents = match responses.first() {
+                    Some(kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+                        modeling_response:
+                            kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+                    }) => data.contents.0.clone(),
+                    resp => return Err(crate::engine_response::unexpected("a snapshot", &resp)),
+                };
+
+                if self.annotate {
+                    match responses.get(1) {
+                        Some(kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+                            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Volume(data),
+                        }) => {
+                            let unit = serde_json::to_value(&data.output_unit)?;
+                            let label = format!("Volume: {:.2} {}", data.volume, unit.as_str().unwrap_or_default());
+                            contents = crate::annotate::annotate_png(&contents, &[label])?;
+                        }
+                        resp => return Err(crate::engine_response::unexpected("a volume", &resp)),
+                    }
+                }
+
+                (contents, session_data)
+            }
+        };
+
+        if self.embed_metadata {
+            rendered.0 = self.embed_metadata(&rendered.0, input)?;
+        }
+
+        Ok(rendered)
+    }
+
+    /// Snapshot every file in the `--from-list` list into the output directory.
+    async fn run_batch(&self, ctx: &mut crate::context::Context, list: &std::path::Path) -> Result<()> {
+        if self.concurrency < 1 {
+            anyhow::bail!("--concurrency must be greater than 0");
+        }
+
+        let output_dir = if let Some(output_dir) = &self.output_dir {
+            output_dir.clone()
+        } else {
+            std::env::current_dir()?
+        };
+        if !output_dir.is_dir() {
+            anyhow::bail!(
+                "output directory `{}` does not exist or is not a directory",
+                output_dir.to_str().unwrap_or("")
+            );
+        }
+
+        let output_format = self.image_format().unwrap_or(kittycad_modeling_cmds::ImageFormat::Png);
+        self.check_png_options(output_format)?;
+
+        let extension = self
+            .output_format
+            .as_ref()
+            .map(|output_format| output_format.to_string())
+            .unwrap_or_else(|| "png".to_string());
+
+        let list = String::from_utf8(ctx.read_file(list.to_str().unwrap_or(""))?)?;
+        let inputs = parse_snapshot_list(&list)?;
+        let outputs = snapshot_list_outputs(&inputs, &output_dir, &extension)?;
+
+        // Read all the files up front, then render them over a bounded pool of connections.
+        let mut results = Vec::with_capacity(inputs.len());
+        let mut jobs = Vec::new();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            let mut result = SnapshotResult {
+                input: input.display().to_string(),
+                output: output.display().to_string(),
+                success: false,
+                error: String::new(),
+            };
+            match get_code_and_file_path(ctx, input, self.assembly_main.as_deref()).await {
+                Ok((code, filepath)) => jobs.push((results.len(), input, code, filepath, output)),
+                Err(err) => result.error = err.to_string(),
+            }
+            results.push(result);
+        }
+
+        let shared_ctx: &crate::context::Context = ctx;
+        let rendered: Vec<(usize, Result<()>)> = futures::stream::iter(jobs)
+            .map(move |(i, input, code, filepath, output)| async move {
+                let result = match self.render(shared_ctx, input, code, &filepath, output_format).await {
+                    Ok((contents, _)) => std::fs::write(output, contents).map_err(anyhow::Error::from),
+                    Err(err) => Err(err),
+                };
+                (i, result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        for (i, result) in rendered {
+            match result {
+                Ok(()) => results[i].success = true,
+                Err(err) => results[i].error = err.to_string(),
+            }
+        }
+
+        let failed = results.iter().filter(|result| !result.success).count();
+        let total = results.len();
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, results)?;
+
+        if failed > 0 {
+            anyhow::bail!("{} of {} snapshots failed", failed, total);
+        }
+
+        Ok(())
+    }
+
+    /// Print how much the snapshot differs from the `--compare` baseline, write the diff
+    /// image, and fail if the difference is over the threshold.
+    fn report_diff(
+        &self,
+        ctx: &mut crate::context::Context,
+        diff: &crate::image_diff::ImageDiff,
+        to_stdout: bool,
+    ) -> Result<()> {
+        let baseline = self.compare.as_deref().unwrap_or(std::path::Path::new("")).display();
+        let message = format!("{:.2}% of pixels differ from `{}`", diff.percent, baseline);
+        // Keep stdout for the image, if that is where it went.
+        if to_stdout {
+            writeln!(ctx.io.err_out, "{}", message)?;
+        } else {
+            writeln!(ctx.io.out, "{}", message)?;
+        }
+
+        if let Some(diff_output) = &self.diff_output {
+            std::fs::write(diff_output, diff.diff_png()?)?;
+            if !to_stdout {
+                writeln!(ctx.io.out, "Diff saved to `{}`", diff_output.display())?;
+            }
+        }
+
+        if diff.percent > self.threshold {
+            anyhow::bail!(
+                "the snapshot differs from `{}` by {:.2}%, more than the threshold of {}%",
+                baseline,
+                diff.percent,
+                self.threshold
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a `--from-list` file into the paths it lists.
+/// There is one path per line, and blank lines and `#` comments are skipped.
+fn parse_snapshot_list(list: &str) -> Result<Vec<std::path::PathBuf>> {
+    let inputs: Vec<std::path::PathBuf> = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(std::path::PathBuf::from)
+        .collect();
+
+    if inputs.iter().any(|input| input.to_str() == Some("-")) {
+        anyhow::bail!("`-` cannot be used as an input in a `--from-list` file");
+    }
+    if inputs.is_empty() {
+        anyhow::bail!("the `--from-list` file does not list any files");
+    }
+
+    Ok(inputs)
+}
+
+/// The image each input in a `--from-list` file is written to, `<output_dir>/<stem>.<extension>`.
+/// Two inputs with the same stem would overwrite each other, so that is an error.
+fn snapshot_list_outputs(
+    inputs: &[std::path::PathBuf],
+    output_dir: &std::path::Path,
+    extension: &str,
+) -> Result<Vec<std::path::PathBuf>> {
+    let mut seen = std::collections::HashMap::new();
+    let mut outputs = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let stem = input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("cannot name an image after `{}`", input.display()))?;
+        if let Some(other) = seen.insert(stem.clone(), input) {
+            anyhow::bail!(
+                "`{}` and `{}` would both be written to `{}.{}`",
+                other.display(),
+                input.display(),
+                stem,
+                extension
+            );
+        }
+        outputs.push(output_dir.join(format!("{}.{}", stem, extension)));
+    }
+
+    Ok(outputs)
+}
+
+/// View a render of a `kcl` file in your terminal.
+///
+///     $ zoo kcl view my-file.kcl
+///
+///     # pass a file to view from stdin
+///     $ cat my-obj.kcl | zoo kcl view -
+///
+///     # use the engine's default camera and a white background
+///     $ zoo kcl view --view default --background "#ffffff" my-file.kcl
+///
+///     # keep the rendered png around, to see what the terminal was given
+///     $ zoo kcl view --keep-temp --temp-dir . my-file.kcl
+///
+///     # orbit, pan and zoom around the model with the keyboard
+///     $ zoo kcl view --interactive my-file.kcl
+///
+///     # view the model with a different value for one of its top level variables
+///     $ zoo kcl view --param width=20 my-file.kcl
+///
+/// By default, the model is shown from an isometric view.
+///
+/// In `--interactive` mode, the arrow keys (or h, j, k, l) orbit the camera, w, a, s, d
+/// pan it, + and - zoom in and out, and r resets it. Press q or Esc to quit.
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclView {
+    /// The path to the input kcl file to view.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// The camera view to render the model from.
+    #[clap(long, value_enum, default_value_t)]
+    pub view: CameraView,
+
+    /// The background color of the render, as a hex color like `#1e1e1e`.
+    /// This defaults to the engine's background color.
+    #[clap(long, value_parser = parse_hex_color)]
+    pub background: Option<kcmc::shared::Color>,
+
+    /// Keep the png the render is written to before it is shown, and print its path.
+    /// This is useful for finding out why a preview looks wrong in a terminal.
+    #[clap(long, default_value = "false")]
+    pub keep_temp: bool,
+
+    /// The directory to write the render to before it is shown.
+    /// This defaults to the system temporary directory.
+    #[clap(long)]
+    pub temp_dir: Option<std::path::PathBuf>,
+
+    /// Keep the engine connection open and move the camera with the keyboard, rendering
+    /// the model again after each key press. This needs a terminal.
+    #[clap(long, default_value = "false", conflicts_with_all = ["keep_temp", "temp_dir"])]
+    pub interactive: bool,
+
+    /// How much room to leave around the model when zooming to fit it, as a fraction
+    /// of the image.
+    #[clap(long, default_value_t = crate::context::DEFAULT_ZOOM_PADDING)]
+    pub padding: f32,
+
+    /// Don't zoom to fit the model, and keep the camera of the `--view`.
+    #[clap(long, default_value = "false", conflicts_with = "padding")]
+    pub no_zoom: bool,
+
+    /// View the model with a different value for a variable declared at the top level
+    /// of the file, as `name=value`, where the value is kcl, like `10` or `"steel"`.
+    /// This can be given more than once.
+    #[clap(long = "param", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+}
+
+/// A temporary file that is removed when it is dropped, so it is cleaned up on the
+/// error paths too, unless it should be kept.
+struct TempFile {
+    path: std::path::PathBuf,
+    keep: bool,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            // It may never have been written, if we failed before that.
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// A camera view to render a model from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CameraView {
+    /// An isometric view of the model.
+    #[default]
+    Iso,
+    /// Looking at the front of the model, from -Y.
+    Front,
+    /// Looking at the back of the model, from +Y.
+    Back,
+    /// Looking down on the model, from +Z.
+    Top,
+    /// Looking up at the model, from -Z.
+    Bottom,
+    /// Looking at the left side of the model, from -X.
+    Left,
+    /// Looking at the right side of the model, from +X.
+    Right,
+    /// The engine's default camera.
+    Default,
+}
+
+impl CameraView {
+    /// The modeling commands to orient the camera for this view.
+    fn modeling_cmds(&self) -> Vec<kcmc::ModelingCmd> {
+        // Views use the Zoo co-ordinate system (forward: -Y, up: +Z), with the top and
+        // bottom turned so the front of the model is towards the bottom and top of the
+        // image. The ZoomToFit after this will fix the distance.
+        let (vantage, up) = match self {
+            CameraView::Iso => ([100.0, -100.0, 100.0], [0.0, 0.0, 1.0]),
+            CameraView::Front => ([0.0, -100.0, 0.0], [0.0, 0.0, 1.0]),
+            CameraView::Back => ([0.0, 100.0, 0.0], [0.0, 0.0, 1.0]),
+            CameraView::Top => ([0.0, 0.0, 100.0], [0.0, 1.0, 0.0]),
+            CameraView::Bottom => ([0.0, 0.0, -100.0], [0.0, -1.0, 0.0]),
+            CameraView::Left => ([-100.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            CameraView::Right => ([100.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            CameraView::Default => return vec![],
+        };
+        vec![look_at(vantage, [0.0, 0.0, 0.0], up)]
+    }
+}
+
+/// The command to point the camera from `vantage` at `center`.
+fn look_at(vantage: [f64; 3], center: [f64; 3], up: [f64; 3]) -> kcmc::ModelingCmd {
+    let point = |[x, y, z]: [f64; 3]| kcmc::shared::Point3d { x, y, z };
+    kcmc::ModelingCmd::DefaultCameraLookAt(kcmc::DefaultCameraLookAt {
+        vantage: point(vantage),
+        center: point(center),
+        up: point(up),
+        sequence: None,
+    })
+}
+
+/// A camera orbiting a point, for `zoo kcl view --interactive`.
+#[derive(Debug, Clone, PartialEq)]
+struct OrbitCamera {
+    /// The point the camera looks at.
+    center: [f64; 3],
+    /// How far the camera is from the center.
+    distance: f64,
+    /// The angle around the z axis, in radians, from the x axis.
+    azimuth: f64,
+    /// The angle above the xy plane, in radians.
+    elevation: f64,
+}
+
+/// What to do after a key press in `zoo kcl view --interactive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewAction {
+    Redraw,
+    Quit,
+    Nothing,
+}
+
+impl OrbitCamera {
+    /// How far one key press orbits the camera.
+    const ORBIT_STEP: f64 = std::f64::consts::PI / 12.0;
+    /// How far one key press pans the camera, as a fraction of the distance to the center.
+    const PAN_STEP: f64 = 0.1;
+    /// How much one key press zooms the camera.
+    const ZOOM_STEP: f64 = 1.25;
+    /// Stop short of looking straight down or up, where "up" for the camera flips.
+    const MAX_ELEVATION: f64 = 85.0 * std::f64::consts::PI / 180.0;
+
+    /// The camera at `position`, looking at `center`.
+    fn new(position: [f64; 3], center: [f64; 3]) -> Self {
+        let offset = [0, 1, 2].map(|i| position[i] - center[i]);
+        let distance = offset.iter().map(|v| v * v).sum::<f64>().sqrt().max(f64::EPSILON);
+        OrbitCamera {
+            center,
+            distance,
+            azimuth: offset[1].atan2(offset[0]),
+            elevation: (offset[2] / distance)
+                .clamp(-1.0, 1.0)
+                .asin()
+                .clamp(-Self::MAX_ELEVATION, Self::MAX_ELEVATION),
+        }
+    }
+
+    /// The camera where the engine has it now.
+    async fn from_engine(engine_ctx: &kcl_lib::ExecutorContext) -> Result<Self> {
+        let resp = engine_ctx
+            .engine
+            .send_modeling_cmd(
+                uuid::Uuid::new_v4(),
+                kcl_lib::SourceRange::default(),
+                kcmc::ModelingCmd::DefaultCameraGetSettings(kcmc::DefaultCameraGetSettings {}),
+            )
+            .await?;
+        let kcmc::websocket::OkWebSocketResponseData::Modeling {
+            modeling_response: kcmc::ok_response::OkModelingCmdResponse::DefaultCameraGetSettings(data),
+        } = &resp
+        else {
+            return Err(crate::engine_response::unexpected("the camera settings", &resp));
+        };
+        let settings = &data.settings;
+        Ok(OrbitCamera::new(
+            [settings.pos.x as f64, settings.pos.y as f64, settings.pos.z as f64],
+            [
+                settings.center.x as f64,
+                settings.center.y as f64,
+                settings.center.z as f64,
+            ],
+        ))
+    }
+
+    /// Where the camera is.
+    fn position(&self) -> [f64; 3] {
+        let (sin_az, cos_az) = self.azimuth.sin_cos();
+        let (sin_el, cos_el) = self.elevation.sin_cos();
+        [
+            self.center[0] + self.distance * cos_el * cos_az,
+            self.center[1] + self.distance * cos_el * sin_az,
+            self.center[2] + self.distance * sin_el,
+        ]
+    }
+
+    fn orbit(&mut self, azimuth: f64, elevation: f64) {
+        self.azimuth += azimuth;
+        self.elevation = (self.elevation + elevation).clamp(-Self::MAX_ELEVATION, Self::MAX_ELEVATION);
+    }
+
+    /// Move the center along the screen, by fractions of the distance to it.
+    fn pan(&mut self, right: f64, up: f64) {
+        let (sin_az, cos_az) = self.azimuth.sin_cos();
+        let (sin_el, cos_el) = self.elevation.sin_cos();
+        let right_dir = [-sin_az, cos_az, 0.0];
+        let up_dir = [-sin_el * cos_az, -sin_el * sin_az, cos_el];
+        for i in 0..3 {
+            self.center[i] += self.distance * (right * right_dir[i] + up * up_dir[i]);
+        }
+    }
+
+    fn zoom(&mut self, factor: f64) {
+        self.distance /= factor;
+    }
+
+    /// Move the camera for a key press, going back to `initial` on `r`.
+    fn handle_key(&mut self, key: crossterm::event::KeyEvent, initial: &OrbitCamera) -> ViewAction {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return ViewAction::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return ViewAction::Quit,
+            KeyCode::Left | KeyCode::Char('h') => self.orbit(-Self::ORBIT_STEP, 0.0),
+            KeyCode::Right | KeyCode::Char('l') => self.orbit(Self::ORBIT_STEP, 0.0),
+            KeyCode::Up | KeyCode::Char('k') => self.orbit(0.0, Self::ORBIT_STEP),
+            KeyCode::Down | KeyCode::Char('j') => self.orbit(0.0, -Self::ORBIT_STEP),
+            KeyCode::Char('a') => self.pan(-Self::PAN_STEP, 0.0),
+            KeyCode::Char('d') => self.pan(Self::PAN_STEP, 0.0),
+            KeyCode::Char('w') => self.pan(0.0, Self::PAN_STEP),
+            KeyCode::Char('s') => self.pan(0.0, -Self::PAN_STEP),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(Self::ZOOM_STEP),
+            KeyCode::Char('-') | KeyCode::Char('_') => self.zoom(1.0 / Self::ZOOM_STEP),
+            KeyCode::Char('r') => *self = initial.clone(),
+            _ => return ViewAction::Nothing,
+        }
+        ViewAction::Redraw
+    }
+
+    /// The modeling command to point the engine's camera like this one.
+    fn look_at_cmd(&self) -> kcmc::ModelingCmd {
+        let [x, y, z] = self.position();
+        let [cx, cy, cz] = self.center;
+        kcmc::ModelingCmd::DefaultCameraLookAt(kcmc::DefaultCameraLookAt {
+            vantage: kcmc::shared::Point3d {
+                x: x as _,
+                y: y as _,
+                z: z as _,
+            },
+            center: kcmc::shared::Point3d {
+                x: cx as _,
+                y: cy as _,
+                z: cz as _,
+            },
+            up: kcmc::shared::Point3d { x: 0.0, y: 0.0, z: 1.0 },
+            sequence: None,
+        })
+    }
+}
+
+/// Puts the terminal in raw mode on an alternate screen, and restores it when dropped,
+/// so it is restored on the error paths too.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        let terminal = RawTerminal;
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::EnterAlternateScreen,
+            crossterm::cursor::Hide
+        )?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            crossterm::cursor::Show,
+            crossterm::terminal::LeaveAlternateScreen
+        );
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+impl CmdKclView {
+    /// The padding to zoom to fit the model with, or `None` with `--no-zoom`.
+    fn zoom_padding(&self) -> Option<f32> {
+        (!self.no_zoom).then_some(self.padding)
+    }
+
+    /// The commands to set up the camera and background before we take a snapshot.
+    fn setup_cmds(&self) -> Vec<kcmc::ModelingCmd> {
+        let mut setup_cmds = self.view.modeling_cmds();
+        if let Some(color) = self.background {
+            setup_cmds.push(kcmc::ModelingCmd::SetBackgroundColor(kcmc::SetBackgroundColor {
+                color,
+            }));
+        }
+        setup_cmds
+    }
+
+    /// Render the model again after every key press that moves the camera, until the
+    /// user quits.
+    async fn run_interactive(
+        &self,
+        ctx: &mut crate::context::Context<'_>,
+        code: &str,
+        executor_settings: kcl_lib::ExecutorSettings,
+        setup_cmds: Vec<kcmc::ModelingCmd>,
+    ) -> Result<()> {
+        if !ctx.io.is_stdout_tty() || !ctx.io.is_stdin_tty() {
+            anyhow::bail!("`--interactive` needs a terminal");
+        }
+
+        let (engine_ctx, _session_data) = ctx.execute_kcl("", code, executor_settings).await?;
+        let send = |cmd: kcmc::ModelingCmd| {
+            engine_ctx
+                .engine
+                .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), cmd)
+        };
+
+        for cmd in setup_cmds {
+            send(cmd).await?;
+        }
+        if let Some(padding) = self.zoom_padding() {
+            send(kcmc::ModelingCmd::ZoomToFit(kcmc::ZoomToFit {
+                animated: false,
+                object_ids: Default::default(),
+                padding,
+            }))
+            .await?;
+        }
+
+        // Start from wherever setting up the view put the camera.
+        let initial = OrbitCamera::from_engine(&engine_ctx).await?;
+        let mut camera = initial.clone();
+
+        let _terminal = RawTerminal::enter()?;
+        loop {
+            send(camera.look_at_cmd()).await?;
+            let resp = send(kcmc::ModelingCmd::TakeSnapshot(kcmc::TakeSnapshot {
+                format: kcmc::ImageFormat::Png,
+            }))
+            .await?;
+            let kcmc::websocket::OkWebSocketResponseData::Modeling {
+                modeling_response: kcmc::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+            } = &resp
+            else {
+                return Err(crate::engine_response::unexpected("a snapshot", &resp));
+            };
+            let image = image::load_from_memory(&data.contents.0)?;
+
+            let (width, height) = (ctx.io.tty_size)()?;
+            // Leave the last row for the help. Some terminals report a height of 0, so
+            // keep at least one row for the image.
+            let image_rows = (height as u32).saturating_sub(1).max(1);
+            let mut stdout = std::io::stdout();
+            crossterm::execute!(
+                stdout,
+                crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+                crossterm::cursor::MoveTo(0, 0)
+            )?;
+            let image_conf = viuer::Config {
+                x: 0,
+                y: 0,
+                width: Some(width as u32),
+                height: Some(image_rows),
+                ..Default::default()
+            };
+            viuer::print(&image, &image_conf)?;
+            crossterm::execute!(
+                stdout,
+                crossterm::cursor::MoveTo(0, image_rows as u16),
+                crossterm::style::Print("arrows/hjkl: orbit  wasd: pan  +/-: zoom  r: reset  q: quit")
+            )?;
+
+            // Wait for a key that moves the camera.
+            loop {
+                match crossterm::event::read()? {
+                    crossterm::event::Event::Key(key) if key.kind == crossterm::event::KeyEventKind::Press => {
+                        match camera.handle_key(key, &initial) {
+                            ViewAction::Redraw => break,
+                            ViewAction::Quit => return Ok(()),
+                            ViewAction::Nothing => {}
+                        }
+                    }
+                    crossterm::event::Event::Resize(..) => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclView {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let code = apply_params(&code, &self.params)?;
+        let input = code.as_str();
+
+        if self.interactive {
+            let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+            return self
+                .run_interactive(ctx, input, executor_settings, self.setup_cmds())
+                .await;
+        }
+
+        // Create a temporary file to write the snapshot to.
+        let tmp_dir = match &self.temp_dir {
+            Some(tmp_dir) => {
+                if !tmp_dir.is_dir() {
+                    anyhow::bail!(
+                        "temp directory `{}` does not exist or is not a directory",
+                        tmp_dir.display()
+                    );
+                }
+                tmp_dir.clone()
+            }
+            None => std::env::temp_dir(),
+        };
+        let tmp_file = TempFile {
+            path: tmp_dir.join(format!("zoo-kcl-view-{}.png", uuid::Uuid::new_v4())),
+            keep: self.keep_temp,
+        };
+
+        // Get the modeling settings from the project.toml if exists.
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        // Spin up websockets and do the conversion.
+        // This will not return until there are files.
+        let (resp, _session_data) = ctx
+            .send_kcl_modeling_cmd_with_setup(
+                "",
+                input,
+                &self.setup_cmds(),
+                kittycad_modeling_cmds::ModelingCmd::TakeSnapshot(kittycad_modeling_cmds::TakeSnapshot {
+                    format: kittycad_modeling_cmds::ImageFormat::Png,
+                }),
+                executor_settings,
+                self.zoom_padding(),
+            )
+            .await?;
+
+        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+        } = &resp
+        {
+            // Save the snapshot locally.
+            std::fs::write(&tmp_file.path, &data.contents.0)?;
+            if self.keep_temp {
+                writeln!(ctx.io.err_out, "Render saved to `{}`", tmp_file.path.display())?;
+            }
+        } else {
+            return Err(crate::engine_response::unexpected("a snapshot", &resp));
+        }
+
+        let (width, height) = (ctx.io.tty_size)()?;
+
+        let offset_x = 0;
+        let offset_y = 0;
+        // Now we setup the terminal viewer.
+        let image_conf = viuer::Config {
+            // set offset
+            x: offset_x,
+            y: offset_y,
+            // set dimensions
+            width: Some(width as u32 - (offset_x * 2) as u32),
+            // Make sure to leave the last row at the bottom for the prompt.
+            // Which is what the +1 is.
+            height: Some(height as u32 - ((offset_y * 2) + 1) as u32),
+            ..Default::default()
+        };
+        viuer::print_from_file(&tmp_file.path, &image_conf)?;
+
+        Ok(())
+    }
+}
+
+/// Render a `kcl` file as an animated gif of the model spinning around.
+///
+/// The model is run once, then the camera is orbited around it one step at a time,
+/// taking a snapshot for each frame, all over the same engine connection.
+///
+///     # render a turntable gif
+///     $ zoo kcl turntable my-file.kcl my-file.gif
+///
+///     # a smoother, slower spin from the front
+///     $ zoo kcl turntable --frames 72 --fps 24 --view front my-file.kcl my-file.gif
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclTurntable {
+    /// The path to the input kcl file to render.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// The path to write the gif to.
+    #[clap(name = "output-file", required = true)]
+    pub output_file: std::path::PathBuf,
+
+    /// How many frames to take in one turn around the model.
+    #[clap(long, default_value = "36")]
+    pub frames: u32,
+
+    /// How many frames to show a second.
+    #[clap(long, default_value = "12")]
+    pub fps: u32,
+
+    /// The camera view to start turning from.
+    #[clap(long, value_enum, default_value_t)]
+    pub view: CameraView,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// How much room to leave around the model when zooming to fit it, as a fraction
+    /// of the image.
+    #[clap(long, default_value_t = crate::context::DEFAULT_ZOOM_PADDING)]
+    pub padding: f32,
+
+    /// The width of the gif, in pixels, up to 4096.
+    /// If only one of `--width` and `--height` is given, the other keeps a 16:9 aspect
+    /// ratio. This defaults to the engine's resolution.
+    #[clap(long)]
+    pub width: Option<u32>,
+
+    /// The height of the gif, in pixels, up to 4096.
+    #[clap(long)]
+    pub height: Option<u32>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclTurntable {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if self.frames < 1 {
+            anyhow::bail!("--frames must be greater than 0");
+        }
+        // Gif frame delays are in hundredths of a second.
+        if self.fps < 1 || self.fps > 100 {
+            anyhow::bail!("--fps must be between 1 and 100");
+        }
+        ctx.video_resolution = video_resolution(self.width, self.height)?;
+
+        // Make sure the parent directory is a directory and exists.
+        if let Some(parent) = self.output_file.parent() {
+            if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
+                anyhow::bail!(
+                    "directory `{}` does not exist or is not a directory",
+                    parent.to_str().unwrap_or("")
+                );
+            }
+        }
+
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        let (engine_ctx, _session_data) = ctx.execute_kcl("", &code, executor_settings).await?;
+        let mut frames = Vec::with_capacity(self.frames as usize);
+        ctx.send_modeling_cmds_with_setup(&engine_ctx, &code, &self.view.modeling_cmds(), &[], Some(self.padding))
+            .await?;
+        let mut camera = OrbitCamera::from_engine(&engine_ctx).await?;
+        for _ in 0..self.frames {
+            let responses = ctx
+                .send_modeling_cmds_with_setup(
+                    &engine_ctx,
+                    &code,
+                    &[],
+                    &[
+                        camera.look_at_cmd(),
+                        kcmc::ModelingCmd::TakeSnapshot(kcmc::TakeSnapshot {
+                            format: kcmc::ImageFormat::Png,
+                        }),
+                    ],
+                    None,
+                )
+                .await?;
+            let resp = responses
+                .last()
+                .ok_or_else(|| anyhow::anyhow!("no response from engine"))?;
+            let kcmc::websocket::OkWebSocketResponseData::Modeling {
+                modeling_response: kcmc::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+            } = resp
+            else {
+                return Err(crate::engine_response::unexpected("a snapshot", resp));
+            };
+            frames.push(image::load_from_memory(&data.contents.0)?.into_rgba8());
+            camera.orbit(std::f64::consts::TAU / self.frames as f64, 0.0);
+        }
+
+        std::fs::write(&self.output_file, encode_gif(frames, self.fps)?)?;
+        writeln!(
+            ctx.io.out,
+            "Turntable saved to `{}`",
+            self.output_file.to_str().unwrap_or("")
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Encode the frames of an animation as a gif that loops forever.
+fn encode_gif(frames: Vec<image::RgbaImage>, fps: u32) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = image::codecs::gif::GifEncoder::new(&mut out);
+        encoder.set_repeat(image::codecs::gif::Repeat::Infinite)?;
+        let delay = image::Delay::from_numer_denom_ms(1000, fps);
+        encoder.encode_frames(
+            frames
+                .into_iter()
+                .map(|frame| image::Frame::from_parts(frame, 0, 0, delay)),
+        )?;
+    }
+    Ok(out)
+}
+
+/// Run a kcl program on a session server from `zoo start-session`, and return the png
+/// snapshot it takes of the model.
+async fn run_on_session(addr: SocketAddr, code: String, test_name: String) -> Result<Vec<u8>> {
+    let client = reqwest::ClientBuilder::new().build()?;
+    let url = Url::parse(&format!("http://{addr}"))?;
+    let resp = client
+        .post(url)
+        .body(serde_json::to_vec(&kcl_lib::test_server::RequestBody {
+            kcl_program: code,
+            test_name,
+        })?)
+        .send()
+        .await?;
+    let status = resp.status();
+    if status.is_success() {
+        Ok(resp.bytes().await?.to_vec())
+    } else {
+        let err_msg = resp.text().await?;
+        anyhow::bail!("{status}: {err_msg}")
+    }
+}
+
+/// Get the  image format from the extension.
+pub fn get_image_format_from_extension(ext: &str) -> Result<kittycad_modeling_cmds::ImageFormat> {
+    match kittycad_modeling_cmds::ImageFormat::from_str(ext) {
+        Ok(format) => Ok(format),
+        Err(_) => {
+            anyhow::bail!(
+                    "unknown source format for file extension: {}. Try setting the `--src-format` flag explicitly or use a valid format.",
+                    ext
+                )
+        }
+    }
+}
+
+/// The largest image, in pixels on either side, we will ask the engine to render.
+const MAX_VIDEO_RESOLUTION: u32 = 4096;
+
+/// The resolution to render at from `--width` and `--height`, or `None` for the engine's
+/// default. If only one is given, the other keeps the engine's 16:9 aspect ratio.
+pub(crate) fn video_resolution(width: Option<u32>, height: Option<u32>) -> Result<Option<(u32, u32)>> {
+    let scale = |size: u32, by: f64| ((size as f64 * by).round() as u32).max(1);
+    let (width, height) = match (width, height) {
+        (None, None) => return Ok(None),
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, scale(width, 9.0 / 16.0)),
+        (None, Some(height)) => (scale(height, 16.0 / 9.0), height),
+    };
+    for (name, size) in [("width", width), ("height", height)] {
+        if size == 0 {
+            anyhow::bail!("the image {} must be more than 0 pixels", name);
+        }
+        if size > MAX_VIDEO_RESOLUTION {
+            anyhow::bail!(
+                "the image {} must be at most {} pixels, not {}",
+                name,
+                MAX_VIDEO_RESOLUTION,
+                size
+            );
+        }
+    }
+    Ok(Some((width, height)))
+}
+
+/// The volume unit to report measurements of a model in the given length unit in.
+/// There is no cubic millimeters unit, so millimeter models use cubic centimeters.
+fn volume_unit_for(unit: kcmc::units::UnitLength) -> Result<kcmc::units::UnitVolume> {
+    let volume_unit = match unit {
+        kcmc::units::UnitLength::Millimeters | kcmc::units::UnitLength::Centimeters => "cm3",
+        kcmc::units::UnitLength::Meters => "m3",
+        kcmc::units::UnitLength::Inches => "in3",
+        kcmc::units::UnitLength::Feet => "ft3",
+        kcmc::units::UnitLength::Yards => "yd3",
+    };
+    Ok(serde_json::from_value(serde_json::Value::String(
+        volume_unit.to_string(),
+    ))?)
+}
+
+/// Parse a hex color like `#1e1e1e` or `1e1e1eff` into a color the engine understands.
+pub fn parse_hex_color(s: &str) -> Result<kcmc::shared::Color> {
+    let hex = s.trim().trim_start_matches('#');
+    if (hex.len() != 6 && hex.len() != 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!(
+            "invalid color `{}`, expected a hex color like `#1e1e1e` or `#1e1e1eff`",
+            s
+        );
+    }
+
+    let channel = |i: usize| -> Result<f32> { Ok(u8::from_str_radix(&hex[i..i + 2], 16)? as f32 / 255.0) };
+
+    Ok(kcmc::shared::Color {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+        a: if hex.len() == 8 { channel(6)? } else { 1.0 },
+    })
+}
+
+/// Rewrite the progress line on stderr to say the `i`th of `n` files was written.
+fn write_progress(ctx: &mut crate::context::Context, i: usize, n: usize, name: &str) -> Result<()> {
+    write!(ctx.io.err_out, "\r\x1b[K[{}/{}] wrote {}", i, n, name)?;
+    ctx.io.err_out.flush()?;
+    Ok(())
+}
+
+/// Clear the progress line on stderr.
+fn clear_progress(ctx: &mut crate::context::Context) -> Result<()> {
+    write!(ctx.io.err_out, "\r\x1b[K")?;
+    ctx.io.err_out.flush()?;
+    Ok(())
+}
+
+pub(crate) fn get_output_format(
+    format: &kittycad::types::FileExportFormat,
+    src_unit: kittycad_modeling_cmds::units::UnitLength,
+) -> kittycad_modeling_cmds::format::OutputFormat {
+    // Zoo co-ordinate system.
+    //
+    // * Forward: -Y
+    // * Up: +Z
+    // * Handedness: Right
+    let coords = kcmc::coord::System {
+        forward: kcmc::coord::AxisDirectionPair {
+            axis: kcmc::coord::Axis::Y,
+            direction: kcmc::coord::Direction::Negative,
+        },
+        up: kcmc::coord::AxisDirectionPair {
+            axis: kcmc::coord::Axis::Z,
+            direction: kcmc::coord::Direction::Positive,
+        },
+    };
+
+    match format {
+        kt::FileExportFormat::Fbx => OutputFormat::Fbx(kcmc::format::fbx::export::Options {
+            storage: kcmc::format::fbx::export::Storage::Binary,
+            created: None,
+        }),
+        kt::FileExportFormat::Glb => OutputFormat::Gltf(kcmc::format::gltf::export::Options {
+            storage: kcmc::format::gltf::export::Storage::Binary,
+            presentation: kcmc::format::gltf::export::Presentation::Compact,
+        }),
+        kt::FileExportFormat::Gltf => OutputFormat::Gltf(kcmc::format::gltf::export::Options {
+            storage: kcmc::format::gltf::export::Storage::Embedded,
+            presentation: kcmc::format::gltf::export::Presentation::Pretty,
+        }),
+        kt::FileExportFormat::Obj => OutputFormat::Obj(kcmc::format::obj::export::Options {
+            coords,
+            units: src_unit,
+        }),
+        kt::FileExportFormat::Ply => OutputFormat::Ply(kcmc::format::ply::export::Options {
+            storage: kcmc::format::ply::export::Storage::Ascii,
+            coords,
+            selection: kcmc::format::Selection::DefaultScene,
+            units: src_unit,
+        }),
+        kt::FileExportFormat::Step => OutputFormat::Step(kcmc::format::step::export::Options { coords, created: None }),
+        kt::FileExportFormat::Stl => OutputFormat::Stl(kcmc::format::stl::export::Options {
+            storage: kcmc::format::stl::export::Storage::Ascii,
+            coords,
+            units: src_unit,
+            selection: kcmc::format::Selection::DefaultScene,
+        }),
+    }
+}
+
+/// Override the unit a mesh output format is written in with the one given by the user.
+fn apply_units_out(
+    format: kittycad_modeling_cmds::format::OutputFormat,
+    units_out: &Option<kittycad::types::UnitLength>,
+) -> Result<kittycad_modeling_cmds::format::OutputFormat> {
+    let Some(units_out) = units_out else {
+        return Ok(format);
+    };
+    let unit: kcl_lib::UnitLength = units_out.clone().into();
+    let unit: kcmc::units::UnitLength = unit.into();
+
+    Ok(match format {
+        OutputFormat::Obj(options) => OutputFormat::Obj(kcmc::format::obj::export::Options { units: unit, ..options }),
+        OutputFormat::Ply(options) => OutputFormat::Ply(kcmc::format::ply::export::Options { units: unit, ..options }),
+        OutputFormat::Stl(options) => OutputFormat::Stl(kcmc::format::stl::export::Options { units: unit, ..options }),
+        _ => anyhow::bail!("`--units-out` can only be used with obj, ply or stl output"),
+    })
+}
+
+/// Override the presentation and storage of a gltf output format with the ones given by
+/// the user.
+fn apply_gltf_options(
+    format: kittycad_modeling_cmds::format::OutputFormat,
+    presentation: &Option<kittycad::types::GltfPresentation>,
+    storage: &Option<kittycad::types::GltfStorage>,
+) -> Result<kittycad_modeling_cmds::format::OutputFormat> {
+    if presentation.is_none() && storage.is_none() {
+        return Ok(format);
+    }
+    let OutputFormat::Gltf(mut options) = format else {
+        anyhow::bail!("`--gltf-presentation` and `--gltf-storage` can only be used with gltf or glb output");
+    };
+
+    if let Some(presentation) = presentation {
+        options.presentation = match presentation {
+            kt::GltfPresentation::Compact => kcmc::format::gltf::export::Presentation::Compact,
+            kt::GltfPresentation::Pretty => kcmc::format::gltf::export::Presentation::Pretty,
+        };
+    }
+    if let Some(storage) = storage {
+        options.storage = match storage {
+            kt::GltfStorage::Binary => kcmc::format::gltf::export::Storage::Binary,
+            kt::GltfStorage::Standard => kcmc::format::gltf::export::Storage::Standard,
+            kt::GltfStorage::Embedded => kcmc::format::gltf::export::Storage::Embedded,
+        };
+    }
+
+    Ok(OutputFormat::Gltf(options))
+}
+
+/// Parse the `--format-options` JSON object.
+fn parse_format_options(s: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+    match serde_json::from_str(s)? {
+        serde_json::Value::Object(options) => Ok(options),
+        _ => anyhow::bail!("format options must be a JSON object"),
+    }
+}
+
+/// Override the options of an output format with the ones given by the user.
+fn apply_format_options(
+    format: kittycad_modeling_cmds::format::OutputFormat,
+    options: &serde_json::Map<String, serde_json::Value>,
+) -> Result<kittycad_modeling_cmds::format::OutputFormat> {
+    let serde_json::Value::Object(mut merged) = serde_json::to_value(&format)? else {
+        anyhow::bail!("output format `{:?}` has no options", format);
+    };
+    let type_ = merged
+        .get("type")
+        .and_then(|type_| type_.as_str())
+        .unwrap_or_default()
+        .to_string();
+    // Optional options are left out when they are not set, so check against the schema
+    // rather than the options we already have.
+    let valid = format_option_names(&type_)?;
+
+    for (key, value) in options {
+        if key == "type" {
+            anyhow::bail!("format options cannot change the output format, use `--output-format` instead");
+        }
+
+        if !valid.contains(key) {
+            anyhow::bail!(
+                "unknown format option `{}` for {}, valid options are: {}",
+                key,
+                type_,
+                valid.iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        merged.insert(key.to_string(), value.clone());
+    }
+
+    serde_json::from_value(serde_json::Value::Object(merged))
+        .map_err(|err| anyhow::anyhow!("invalid format options for {}: {}", type_, err))
+}
+
+/// The names of the options an output format takes, like `storage`, from the JSON
+/// schema of the output formats.
+fn format_option_names(type_: &str) -> Result<std::collections::BTreeSet<String>> {
+    let root = serde_json::to_value(schemars::schema_for!(kittycad_modeling_cmds::format::OutputFormat))?;
+    for variant in root["oneOf"].as_array().into_iter().flatten() {
+        let mut properties = serde_json::Map::new();
+        collect_schema_properties(&root, variant, &mut properties);
+        let tag = &properties.get("type").cloned().unwrap_or_default();
+        let is_type = tag["const"] == type_
+            || tag["enum"]
+                .as_array()
+                .is_some_and(|tags| tags.iter().any(|tag| tag == type_));
+        if is_type {
+            return Ok(properties.keys().filter(|name| *name != "type").cloned().collect());
+        }
+    }
+    anyhow::bail!("output format `{}` has no options", type_)
+}
+
+/// Gather the properties of an object schema, following `$ref`s and `allOf`s, since the
+/// options of a format can be split between the format's own schema and the tag.
+fn collect_schema_properties(
+    root: &serde_json::Value,
+    schema: &serde_json::Value,
+    properties: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        if let Some(name) = reference.strip_prefix("#/definitions/") {
+            collect_schema_properties(root, &root["definitions"][name], properties);
+        }
+    }
+    if let Some(own) = schema["properties"].as_object() {
+        properties.extend(own.clone());
+    }
+    for part in schema["allOf"].as_array().into_iter().flatten() {
+        collect_schema_properties(root, part, properties);
+    }
+}
+
+/// Get the volume of an object in a kcl file.
+///
+///     # get the volume of a file
+///     $ zoo kcl volume --src-unit=m my-file.kcl
+///
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl volume --src-unit=m
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclVolume {
+    /// The path to the input file.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// Output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Output unit.
+    /// Defaults to the unit set with `zoo config set-default-unit volume`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitVolume>::new())]
+    pub output_unit: Option<kittycad::types::UnitVolume>,
+
+    /// If true, print a link to this request's tracing data.
+    #[clap(long, default_value = "false")]
+    pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclVolume {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Volume)?;
+
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
+
+        // Get the modeling settings from the project.toml if exists.
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        // Spin up websockets and do the conversion.
+        // This will not return until there are files.
+        let (resp, session_data) = ctx
+            .send_kcl_modeling_cmd(
+                "",
+                input,
+                kittycad_modeling_cmds::ModelingCmd::Volume(kittycad_modeling_cmds::Volume {
+                    entity_ids: vec![], // get whole model
+                    output_unit: output_unit.into(),
+                }),
+                executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
+            )
+            .await?;
+
+        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Volume(data),
+        } = &resp
+        {
+            // Print the output.
+            let format = ctx.format(&self.format)?;
+            ctx.io.write_output(&format, &data)?;
+        } else {
+            return Err(crate::engine_response::unexpected("a volume", &resp));
+        }
+
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        }
+        Ok(())
+    }
+}
+
+/// Get the mass of objects in a kcl file.
+///
+///     # get the mass of a file
+///     $ zoo kcl mass --src-unit=m my-file.kcl
+///
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl mass --src-unit=m
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclMass {
+    /// The path to the input file.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// Material density.
+    #[clap(short = 'm', long = "material-density")]
+    material_density: f32,
+
+    /// Material density unit.
+    #[clap(long = "material-density-unit", value_parser = crate::units::UnitParser::<kittycad::types::UnitDensity>::new())]
+    material_density_unit: kittycad::types::UnitDensity,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// Output unit.
+    /// Defaults to the unit set with `zoo config set-default-unit mass`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitMass>::new())]
+    pub output_unit: Option<kittycad::types::UnitMass>,
+
+    /// If true, print a link to this request's tracing data.
     #[clap(long, default_value = "false")]
-    pub replay: bool,
+    pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclSnapshot {
+impl crate::cmd::Command for CmdKclMass {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Make sure the parent directory is a directory and exists.
-        if let Some(parent) = self.output_file.parent() {
-            if !parent.is_dir() && parent.to_str().unwrap_or("") != "" {
-                anyhow::bail!(
-                    "directory `{}` does not exist or is not a directory",
-                    parent.to_str().unwrap_or("")
-                );
-            }
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Mass)?;
+
+        if self.material_density == 0.0 {
+            anyhow::bail!("`--material-density` must not be 0.0");
         }
 
-        // Parse the image format.
-        let output_format = if let Some(output_format) = &self.output_format {
-            match output_format {
-                kittycad::types::ImageFormat::Png => kittycad_modeling_cmds::ImageFormat::Png,
-                kittycad::types::ImageFormat::Jpeg => kittycad_modeling_cmds::ImageFormat::Jpeg,
-            }
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
+
+        // Get the modeling settings from the project.toml if exists.
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+
+        // Spin up websockets and do the conversion.
+        // This will not return until there are files.
+        let (resp, session_data) = ctx
+            .send_kcl_modeling_cmd(
+                "",
+                input,
+                kittycad_modeling_cmds::ModelingCmd::Mass(kittycad_modeling_cmds::Mass {
+                    entity_ids: vec![], // get whole model
+                    material_density: self.material_density.into(),
+                    material_density_unit: self.material_density_unit.clone().into(),
+                    output_unit: output_unit.into(),
+                }),
+                executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
+            )
+            .await?;
+
+        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Mass(data),
+        } = &resp
+        {
+            // Print the output.
+            let format = ctx.format(&self.format)?;
+            ctx.io.write_output(&format, &data)?;
         } else {
-            get_image_format_from_extension(&crate::cmd_file::get_extension(self.output_file.clone()))?
-        };
+            return Err(crate::engine_response::unexpected("a mass", &resp));
+        }
 
-        // Get the contents of the input file.
-        let filename = self
-            .input
-            .file_name()
-            .map(|b| b.to_string_lossy().to_string())
-            .unwrap_or("unknown".to_string());
-        let filepath = self.input.display().to_string();
-        let input = ctx.read_file(&filepath)?;
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        }
+        Ok(())
+    }
+}
 
-        // Parse the input as a string.
-        let input = String::from_utf8(input)?;
+/// Get the center of mass of objects in a kcl file.
+///
+///     # get the mass of a file
+///     $ zoo kcl center-of-mass --src-unit=m my-file.kcl
+///
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl center-of-mass --src-unit=m
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclCenterOfMass {
+    /// The path to the input file.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
+
+    /// Output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
+    /// Output unit.
+    /// Defaults to the unit set with `zoo config set-default-unit length`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub output_unit: Option<kittycad::types::UnitLength>,
+
+    /// If true, print a link to this request's tracing data.
+    #[clap(long, default_value = "false")]
+    pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclCenterOfMass {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Length)?;
+
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
 
         // Get the modeling settings from the project.toml if exists.
-        let mut executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
-        executor_settings.replay = self.replay.then_some(filename);
+        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
 
-        let (output_file_contents, session_data) = match self.session {
-            Some(addr) => {
-                // TODO
-                let client = reqwest::ClientBuilder::new().build()?;
-                let url = Url::parse(&format!("http://{addr}"))?;
-                let resp = client
-                    .post(url)
-                    .body(serde_json::to_vec(&kcl_lib::test_server::RequestBody {
-                        kcl_program: input,
-                        test_name: self.input.display().to_string(),
-                    })?)
-                    .send()
-                    .await?;
-                let status = resp.status();
-                if status.is_success() {
-                    (resp.bytes().await?.to_vec(), Default::default())
-                } else {
-                    let err_msg = resp.text().await?;
-                    anyhow::bail!("{status}: {err_msg}")
-                }
-            }
-            None => {
-                // Spin up websockets and do the conversion.
-                // This will not return until there are files.
-                let (resp, session_data) = ctx
-                    .send_kcl_modeling_cmd(
-                        "",
-                        &input,
-                        kittycad_modeling_cmds::ModelingCmd::TakeSnapshot(kittycad_modeling_cmds::TakeSnapshot {
-                            format: output_format,
-                        }),
-                        executor_settings,
-                    )
-                    .await?;
+        // Spin up websockets and do the conversion.
+        // This will not return until there are files.
+        let (resp, session_data) = ctx
+            .send_kcl_modeling_cmd(
+                "",
+                input,
+                kittycad_modeling_cmds::ModelingCmd::CenterOfMass(kittycad_modeling_cmds::CenterOfMass {
+                    entity_ids: vec![], // get whole model
+                    output_unit: output_unit.into(),
+                }),
+                executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
+            )
+            .await?;
 
-                if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-                    modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
-                } = &resp
-                {
-                    (data.contents.0.clone(), session_data)
-                } else {
-                    anyhow::bail!("Unexpected response from engine: {:?}", resp);
-                }
-            }
-        };
-        // Save the snapshot locally.
-        std::fs::write(&self.output_file, output_file_contents)?;
+        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::CenterOfMass(data),
+        } = &resp
+        {
+            // Print the output.
+            let format = ctx.format(&self.format)?;
+            ctx.io.write_output(&format, &data)?;
+        } else {
+            return Err(crate::engine_response::unexpected("a center of mass", &resp));
+        }
 
-        writeln!(
-            ctx.io.out,
-            "Snapshot saved to `{}`",
-            self.output_file.to_str().unwrap_or("")
-        )?;
         if self.show_trace {
             print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
         }
-
         Ok(())
     }
 }
 
-/// View a render of a `kcl` file in your terminal.
+/// Get the density of objects in a kcl file.
 ///
-///     $ zoo kcl view my-file.kcl
+///     # get the density of a file
+///     $ zoo kcl density --src-unit=m my-file.kcl
 ///
-///     # pass a file to view from stdin
-///     $ cat my-obj.kcl | zoo kcl view -
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl density --src-unit=m
 ///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
@@ -408,160 +3683,107 @@ impl crate::cmd::Command for CmdKclSnapshot {
 /// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclView {
-    /// The path to the input kcl file to view.
+pub struct CmdKclDensity {
+    /// The path to the input file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
-    /// Command output format.
+    /// Material mass.
+    #[clap(short = 'm', long = "material-mass")]
+    material_mass: f32,
+
+    /// The unit of the material mass.
+    #[clap(long = "material-mass-unit", value_parser = crate::units::UnitParser::<kittycad::types::UnitMass>::new())]
+    material_mass_unit: kittycad::types::UnitMass,
+
+    /// Output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+
+    /// Output unit.
+    /// Defaults to the unit set with `zoo config set-default-unit density`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitDensity>::new())]
+    pub output_unit: Option<kittycad::types::UnitDensity>,
+
+    /// If true, print a link to this request's tracing data.
+    #[clap(long, default_value = "false")]
+    pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclView {
+impl crate::cmd::Command for CmdKclDensity {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Density)?;
 
-        // Create a temporary file to write the snapshot to.
-        let mut tmp_file = std::env::temp_dir();
-        tmp_file.push(format!("zoo-kcl-view-{}.png", uuid::Uuid::new_v4()));
+        if self.material_mass == 0.0 {
+            anyhow::bail!("`--material-mass` must not be 0.0");
+        }
+
+        // Get the contents of the input file.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
 
         // Get the modeling settings from the project.toml if exists.
         let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
 
         // Spin up websockets and do the conversion.
         // This will not return until there are files.
-        let (resp, _session_data) = ctx
+        let (resp, session_data) = ctx
             .send_kcl_modeling_cmd(
                 "",
                 input,
-                kittycad_modeling_cmds::ModelingCmd::TakeSnapshot(kittycad_modeling_cmds::TakeSnapshot {
-                    format: kittycad_modeling_cmds::ImageFormat::Png,
+                kittycad_modeling_cmds::ModelingCmd::Density(kittycad_modeling_cmds::Density {
+                    entity_ids: vec![], // get whole model
+                    material_mass: self.material_mass.into(),
+                    material_mass_unit: self.material_mass_unit.clone().into(),
+                    output_unit: output_unit.into(),
                 }),
                 executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
             )
             .await?;
 
         if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::TakeSnapshot(data),
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Density(data),
         } = &resp
         {
-            // Save the snapshot locally.
-            std::fs::write(&tmp_file, &data.contents.0)?;
+            // Print the output.
+            let format = ctx.format(&self.format)?;
+            ctx.io.write_output(&format, &data)?;
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            return Err(crate::engine_response::unexpected("a density", &resp));
         }
 
-        let (width, height) = (ctx.io.tty_size)()?;
-
-        let offset_x = 0;
-        let offset_y = 0;
-        // Now we setup the terminal viewer.
-        let image_conf = viuer::Config {
-            // set offset
-            x: offset_x,
-            y: offset_y,
-            // set dimensions
-            width: Some(width as u32 - (offset_x * 2) as u32),
-            // Make sure to leave the last row at the bottom for the prompt.
-            // Which is what the +1 is.
-            height: Some(height as u32 - ((offset_y * 2) + 1) as u32),
-            ..Default::default()
-        };
-        viuer::print_from_file(&tmp_file, &image_conf)?;
-
-        // Remove the temporary file.
-        std::fs::remove_file(&tmp_file)?;
-
-        Ok(())
-    }
-}
-
-/// Get the  image format from the extension.
-pub fn get_image_format_from_extension(ext: &str) -> Result<kittycad_modeling_cmds::ImageFormat> {
-    match kittycad_modeling_cmds::ImageFormat::from_str(ext) {
-        Ok(format) => Ok(format),
-        Err(_) => {
-            anyhow::bail!(
-                    "unknown source format for file extension: {}. Try setting the `--src-format` flag explicitly or use a valid format.",
-                    ext
-                )
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
         }
+        Ok(())
     }
 }
 
-fn get_output_format(
-    format: &kittycad::types::FileExportFormat,
-    src_unit: kittycad_modeling_cmds::units::UnitLength,
-) -> kittycad_modeling_cmds::format::OutputFormat {
-    // Zoo co-ordinate system.
-    //
-    // * Forward: -Y
-    // * Up: +Z
-    // * Handedness: Right
-    let coords = kcmc::coord::System {
-        forward: kcmc::coord::AxisDirectionPair {
-            axis: kcmc::coord::Axis::Y,
-            direction: kcmc::coord::Direction::Negative,
-        },
-        up: kcmc::coord::AxisDirectionPair {
-            axis: kcmc::coord::Axis::Z,
-            direction: kcmc::coord::Direction::Positive,
-        },
-    };
-
-    match format {
-        kt::FileExportFormat::Fbx => OutputFormat::Fbx(kcmc::format::fbx::export::Options {
-            storage: kcmc::format::fbx::export::Storage::Binary,
-            created: None,
-        }),
-        kt::FileExportFormat::Glb => OutputFormat::Gltf(kcmc::format::gltf::export::Options {
-            storage: kcmc::format::gltf::export::Storage::Binary,
-            presentation: kcmc::format::gltf::export::Presentation::Compact,
-        }),
-        kt::FileExportFormat::Gltf => OutputFormat::Gltf(kcmc::format::gltf::export::Options {
-            storage: kcmc::format::gltf::export::Storage::Embedded,
-            presentation: kcmc::format::gltf::export::Presentation::Pretty,
-        }),
-        kt::FileExportFormat::Obj => OutputFormat::Obj(kcmc::format::obj::export::Options {
-            coords,
-            units: src_unit,
-        }),
-        kt::FileExportFormat::Ply => OutputFormat::Ply(kcmc::format::ply::export::Options {
-            storage: kcmc::format::ply::export::Storage::Ascii,
-            coords,
-            selection: kcmc::format::Selection::DefaultScene,
-            units: src_unit,
-        }),
-        kt::FileExportFormat::Step => OutputFormat::Step(kcmc::format::step::export::Options { coords, created: None }),
-        kt::FileExportFormat::Stl => OutputFormat::Stl(kcmc::format::stl::export::Options {
-            storage: kcmc::format::stl::export::Storage::Ascii,
-            coords,
-            units: src_unit,
-            selection: kcmc::format::Selection::DefaultScene,
-        }),
-    }
-}
-
-/// Get the volume of an object in a kcl file.
+/// Get the surface area of objects in a kcl file.
 ///
-///     # get the volume of a file
-///     $ zoo kcl volume --src-unit=m my-file.kcl
+///     # get the surface-area of a file
+///     $ zoo kcl surface-area --src-unit=m my-file.kcl
 ///
 ///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl volume --src-unit=m
+///     $ cat my-file.kcl | zoo kcl surface-area --src-unit=m
 ///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
@@ -570,39 +3792,49 @@ fn get_output_format(
 /// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclVolume {
+pub struct CmdKclSurfaceArea {
     /// The path to the input file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// Output format.
-    #[clap(long, short, value_enum)]
-    pub format: Option<crate::types::FormatOutput>,
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
 
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
+    /// Output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitVolume,
+    /// Defaults to the unit set with `zoo config set-default-unit area`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitArea>::new())]
+    pub output_unit: Option<kittycad::types::UnitArea>,
 
     /// If true, print a link to this request's tracing data.
     #[clap(long, default_value = "false")]
     pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclVolume {
+impl crate::cmd::Command for CmdKclSurfaceArea {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Area)?;
+
         // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
 
         // Get the modeling settings from the project.toml if exists.
         let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
@@ -613,23 +3845,24 @@ impl crate::cmd::Command for CmdKclVolume {
             .send_kcl_modeling_cmd(
                 "",
                 input,
-                kittycad_modeling_cmds::ModelingCmd::Volume(kittycad_modeling_cmds::Volume {
+                kittycad_modeling_cmds::ModelingCmd::SurfaceArea(kittycad_modeling_cmds::SurfaceArea {
                     entity_ids: vec![], // get whole model
-                    output_unit: self.output_unit.clone().into(),
+                    output_unit: output_unit.into(),
                 }),
                 executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
             )
             .await?;
 
         if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Volume(data),
+            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::SurfaceArea(data),
         } = &resp
         {
             // Print the output.
             let format = ctx.format(&self.format)?;
             ctx.io.write_output(&format, &data)?;
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            return Err(crate::engine_response::unexpected("a surface area", &resp));
         }
 
         if self.show_trace {
@@ -639,13 +3872,17 @@ impl crate::cmd::Command for CmdKclVolume {
     }
 }
 
-/// Get the mass of objects in a kcl file.
+/// Get the axis-aligned bounding box of the objects in a kcl file.
 ///
-///     # get the mass of a file
-///     $ zoo kcl mass --src-unit=m my-file.kcl
+///     # get the bounding box of a file
+///     $ zoo kcl bbox --src-unit=m my-file.kcl
 ///
 ///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl mass --src-unit=m
+///     $ cat my-file.kcl | zoo kcl bbox --src-unit=m -
+///
+/// This prints the corners with the smallest and largest coordinates, and the width
+/// (along x), depth (along y) and height (along z) of the box. The box is measured on
+/// the triangle mesh of the model, to single precision.
 ///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
@@ -654,25 +3891,22 @@ impl crate::cmd::Command for CmdKclVolume {
 /// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclMass {
+pub struct CmdKclBoundingBox {
     /// The path to the input file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// Material density.
-    #[clap(short = 'm', long = "material-density")]
-    material_density: f32,
-
-    /// Material density unit.
-    #[clap(long = "material-density-unit", value_enum)]
-    material_density_unit: kittycad::types::UnitDensity,
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
 
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
     /// Output format.
@@ -680,252 +3914,910 @@ pub struct CmdKclMass {
     pub format: Option<crate::types::FormatOutput>,
 
     /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitMass,
+    /// Defaults to the unit set with `zoo config set-default-unit length`.
+    #[clap(long = "output-unit", short = 'u', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub output_unit: Option<kittycad::types::UnitLength>,
 
     /// If true, print a link to this request's tracing data.
     #[clap(long, default_value = "false")]
     pub show_trace: bool,
+
+    #[clap(flatten)]
+    pub timeouts: crate::cmd_file::Timeouts,
+}
+
+/// The axis-aligned bounding box of a model.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct KclBoundingBox {
+    /// The corner with the smallest coordinates.
+    #[tabled(display_with = "display_point")]
+    pub min: kt::Point3D,
+    /// The corner with the largest coordinates.
+    #[tabled(display_with = "display_point")]
+    pub max: kt::Point3D,
+    /// The size of the box along the x axis.
+    pub width: f64,
+    /// The size of the box along the y axis.
+    pub depth: f64,
+    /// The size of the box along the z axis, which is up.
+    pub height: f64,
+    /// The unit of all the lengths.
+    pub output_unit: kt::UnitLength,
+}
+
+fn display_point(point: &kt::Point3D) -> String {
+    format!("{:?}", point)
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclMass {
+impl crate::cmd::Command for CmdKclBoundingBox {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        if self.material_density == 0.0 {
-            anyhow::bail!("`--material-density` must not be 0.0");
-        }
+        let output_unit = ctx.output_unit(&self.output_unit, crate::config::Measurement::Length)?;
 
         // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+        let input = code.as_str();
 
         // Get the modeling settings from the project.toml if exists.
         let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
 
-        // Spin up websockets and do the conversion.
-        // This will not return until there are files.
+        // There is no modeling command for the bounds of the scene, so measure them on a
+        // mesh of it, exported in the output unit.
+        let format = match get_output_format(&kt::FileExportFormat::Stl, output_unit.clone().into()) {
+            OutputFormat::Stl(options) => OutputFormat::Stl(kcmc::format::stl::export::Options {
+                storage: kcmc::format::stl::export::Storage::Binary,
+                ..options
+            }),
+            format => format,
+        };
         let (resp, session_data) = ctx
             .send_kcl_modeling_cmd(
                 "",
                 input,
-                kittycad_modeling_cmds::ModelingCmd::Mass(kittycad_modeling_cmds::Mass {
+                kittycad_modeling_cmds::ModelingCmd::Export(kittycad_modeling_cmds::Export {
                     entity_ids: vec![], // get whole model
-                    material_density: self.material_density.into(),
-                    material_density_unit: self.material_density_unit.clone().into(),
-                    output_unit: self.output_unit.clone().into(),
+                    format,
                 }),
                 executor_settings,
+                Some(crate::context::DEFAULT_ZOOM_PADDING),
             )
             .await?;
 
-        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Mass(data),
-        } = &resp
-        {
-            // Print the output.
-            let format = ctx.format(&self.format)?;
-            ctx.io.write_output(&format, &data)?;
+        let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Export { files } = &resp else {
+            return Err(crate::engine_response::unexpected("an export", &resp));
+        };
+        let mut bounds: Option<(kt::Point3D, kt::Point3D)> = None;
+        for file in files {
+            if let Some((min, max)) = stl_bounds(&file.contents.0)? {
+                bounds = Some(match bounds {
+                    Some((all_min, all_max)) => (
+                        kt::Point3D {
+                            x: all_min.x.min(min.x),
+                            y: all_min.y.min(min.y),
+                            z: all_min.z.min(min.z),
+                        },
+                        kt::Point3D {
+                            x: all_max.x.max(max.x),
+                            y: all_max.y.max(max.y),
+                            z: all_max.z.max(max.z),
+                        },
+                    ),
+                    None => (min, max),
+                });
+            }
+        }
+        let Some((min, max)) = bounds else {
+            anyhow::bail!("the model is empty, there is nothing to measure");
+        };
+
+        // Print the output.
+        let bounding_box = KclBoundingBox {
+            width: max.x - min.x,
+            depth: max.y - min.y,
+            height: max.z - min.z,
+            min,
+            max,
+            output_unit,
+        };
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output(&format, &bounding_box)?;
+
+        if self.show_trace {
+            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        }
+        Ok(())
+    }
+}
+
+/// The smallest and largest corners of the triangles in a binary STL file, or `None`
+/// if it has no triangles.
+fn stl_bounds(contents: &[u8]) -> Result<Option<(kt::Point3D, kt::Point3D)>> {
+    let count = contents
+        .get(80..84)
+        .map(|count| u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize)
+        .ok_or_else(|| anyhow::anyhow!("the exported mesh is not a binary stl"))?;
+    // Each triangle is a normal, three vertices, and a two byte attribute count.
+    let triangles = contents
+        .get(84..84 + count * 50)
+        .ok_or_else(|| anyhow::anyhow!("the exported mesh is truncated"))?;
+    if count == 0 {
+        return Ok(None);
+    }
+
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for triangle in triangles.chunks_exact(50) {
+        for vertex in triangle[12..48].chunks_exact(12) {
+            for (axis, value) in vertex.chunks_exact(4).enumerate() {
+                let value = f32::from_le_bytes([value[0], value[1], value[2], value[3]]) as f64;
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+    }
+
+    let point = |[x, y, z]: [f64; 3]| kt::Point3D { x, y, z };
+    Ok(Some((point(min), point(max))))
+}
+
+/// Lint a KCL file for style issues.
+///
+///     # check a file for issues
+///     $ zoo kcl lint my-file.kcl
+///
+///     # pass a file from stdin
+///     $ cat my-file.kcl | zoo kcl lint -
+///
+///     # check every file in a project
+///     $ zoo kcl lint my-project
+///
+///     # only check some rules, or skip some rules
+///     $ zoo kcl lint --select Z0001,Z0002 my-file.kcl
+///     $ zoo kcl lint --ignore Z0003 my-file.kcl
+///
+///     # annotate the findings on a pull request in GitHub Actions
+///     $ zoo kcl lint --format github my-file.kcl
+///
+/// After the findings, a summary of how many there were for each rule, like
+/// `3 findings: Z0001=2, Z0003=1`, is printed to stderr, unless `--quiet` is given.
+///
+/// The rules can also be set for a whole project in the `project.toml` file, the
+/// command line flags take precedence over it:
+///
+///     [lint]
+///     select = ["Z0001", "Z0002"]
+///     ignore = ["Z0003"]
+///
+/// When linting a directory, hidden files, `target` and `node_modules` directories, and
+/// the paths listed in `.zooignore` files, which are written like `.gitignore` files,
+/// are skipped.
+#[derive(Parser, Debug, Clone)]
+#[clap(verbatim_doc_comment)]
+pub struct CmdKclLint {
+    /// The path to the input file, or a directory to lint all the `.kcl` files in.
+    /// If you pass `-` as the path, the file will be read from stdin.
+    #[clap(name = "input", required = true)]
+    pub input: std::path::PathBuf,
+
+    /// How many levels of directories to look in for `.kcl` files, when linting a
+    /// directory. `1` only lints the files in the directory itself.
+    /// This defaults to no limit.
+    #[clap(long)]
+    pub max_depth: Option<usize>,
+
+    /// Print a long-form description of what the issue is, and the rational
+    /// behind why.
+    #[clap(long, default_value = "false")]
+    pub descriptions: bool,
+
+    /// Show where the offending KCL source code is.
+    #[clap(long, short, default_value = "false")]
+    pub show_code: bool,
+
+    /// Only report findings for these rule codes.
+    #[clap(long, value_delimiter = ',')]
+    pub select: Vec<String>,
+
+    /// Do not report findings for these rule codes.
+    #[clap(long, value_delimiter = ',')]
+    pub ignore: Vec<String>,
+
+    /// How to print the findings.
+    #[clap(long, short, value_enum, default_value_t)]
+    pub format: LintFormat,
+
+    /// If true, reuse the findings from the last time the same code was linted, instead
+    /// of linting it again.
+    /// The cache is invalidated by a change to the code, or to the version of zoo or of
+    /// the KCL linter. Use `--debug` to see when the cache is used.
+    /// Findings are cached in `$XDG_CACHE_HOME/zoo/lint`, or `~/.cache/zoo/lint`.
+    #[clap(long, default_value = "false")]
+    pub cache: bool,
+
+    /// If true, don't print the summary of how many findings there were for each rule
+    /// to stderr after the findings.
+    #[clap(long, short, default_value = "false")]
+    pub quiet: bool,
+}
+
+/// How to print lint findings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LintFormat {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// A JSON array of findings.
+    Json,
+    /// GitHub Actions workflow commands, which show up as annotations on pull requests.
+    Github,
+}
+
+/// A lint finding in a `kcl` file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LintFinding {
+    /// The path of the file.
+    pub path: String,
+    /// The line the finding starts on, starting at 1.
+    pub line: u32,
+    /// The column the finding starts on, starting at 1.
+    pub column: u32,
+    /// The line the finding ends on, starting at 1.
+    pub end_line: u32,
+    /// The column the finding ends on, starting at 1.
+    pub end_column: u32,
+    /// The rule code.
+    pub code: String,
+    /// The title of the finding.
+    pub title: String,
+    /// A long-form description of the rule.
+    pub description: String,
+}
+
+/// A line like `3 findings: Z0001=2, Z0014=1`, counting the findings for each rule, or
+/// `None` if there are none.
+fn lint_summary<'a>(findings: impl IntoIterator<Item = &'a LintFinding>) -> Option<String> {
+    let mut counts = std::collections::BTreeMap::<&str, usize>::new();
+    for finding in findings {
+        *counts.entry(finding.code.as_str()).or_default() += 1;
+    }
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let by_code = counts
+        .iter()
+        .map(|(code, count)| format!("{}={}", code, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!(
+        "{} finding{}: {}",
+        total,
+        if total == 1 { "" } else { "s" },
+        by_code
+    ))
+}
+
+impl LintFinding {
+    /// Format the finding as a GitHub Actions workflow command.
+    /// See https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions
+    fn to_github_annotation(&self) -> String {
+        format!(
+            "::error file={},line={},col={},endLine={},endColumn={},title={}::{}",
+            escape_github_property(&self.path),
+            self.line,
+            self.column,
+            self.end_line,
+            self.end_column,
+            escape_github_property(&self.code),
+            escape_github_data(&self.title),
+        )
+    }
+}
+
+/// Escape the message of a GitHub Actions workflow command.
+fn escape_github_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escape a property of a GitHub Actions workflow command.
+fn escape_github_property(s: &str) -> String {
+    escape_github_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// The `[lint]` section of a `project.toml` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct LintSettings {
+    /// Only report findings for these rule codes.
+    #[serde(default)]
+    pub select: Vec<String>,
+    /// Do not report findings for these rule codes.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+impl LintSettings {
+    /// If findings for the rule code should be reported.
+    pub fn is_enabled(&self, code: &str) -> bool {
+        (self.select.is_empty() || self.select.iter().any(|c| c.eq_ignore_ascii_case(code)))
+            && !self.ignore.iter().any(|c| c.eq_ignore_ascii_case(code))
+    }
+}
+
+/// Get the lint settings from the `project.toml` for the input, if there is one.
+fn get_lint_settings_from_project_toml(input: &std::path::Path) -> Result<LintSettings> {
+    #[derive(serde::Deserialize)]
+    struct ProjectToml {
+        #[serde(default)]
+        lint: LintSettings,
+    }
+
+    // Check if the path was stdin or a URL.
+    if input.to_str() == Some("-") || input_url(input).is_some() {
+        return Ok(Default::default());
+    }
+
+    let Some(dir) = input.parent() else {
+        return Ok(Default::default());
+    };
+    match find_project_toml(dir)? {
+        Some(project_toml) => {
+            let project_toml: ProjectToml = toml::from_str(&std::fs::read_to_string(&project_toml)?)?;
+            Ok(project_toml.lint)
+        }
+        None => Ok(Default::default()),
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclLint {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let files = if self.input.is_dir() {
+            let mut files = Vec::new();
+            for path in kcl_files_in_dir(&self.input, self.max_depth)? {
+                let input = ctx.read_file(path.to_str().unwrap_or(""))?;
+                files.push((path, input));
+            }
+            files
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            vec![(self.input.clone(), read_kcl_input(ctx, &self.input).await?)]
+        };
+
+        // The findings for each file, with the code they were found in.
+        let mut results = Vec::with_capacity(files.len());
+        for (path, input) in &files {
+            let input = kcl_text(input, path)?;
+            let findings = self.lint_file(ctx, path, input)?;
+            results.push((input, findings));
+        }
+
+        let summary = lint_summary(results.iter().flat_map(|(_, findings)| findings));
+        self.write_findings(ctx, results)?;
+        if let Some(summary) = summary.filter(|_| !self.quiet) {
+            writeln!(ctx.io.err_out, "{}", summary)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CmdKclLint {
+    /// Print the findings for each file, with the code they were found in.
+    fn write_findings(
+        &self,
+        ctx: &mut crate::context::Context<'_>,
+        results: Vec<(&str, Vec<LintFinding>)>,
+    ) -> Result<()> {
+        match self.format {
+            LintFormat::Json => {
+                let findings = results
+                    .into_iter()
+                    .flat_map(|(_, findings)| findings)
+                    .collect::<Vec<_>>();
+                ctx.io.write_output_json(&serde_json::to_value(&findings)?)?;
+                return Ok(());
+            }
+            LintFormat::Github => {
+                for finding in results.into_iter().flat_map(|(_, findings)| findings) {
+                    writeln!(ctx.io.out, "{}", finding.to_github_annotation())?;
+                }
+                return Ok(());
+            }
+            LintFormat::Text => {}
         }
 
-        if self.show_trace {
-            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        for (input, findings) in results {
+            for finding in findings {
+                println!(
+                    "{}:{}:{}: [{}] {}",
+                    finding.path, finding.line, finding.column, finding.code, finding.title,
+                );
+
+                if self.descriptions {
+                    println!("\n{}", finding.description);
+                }
+
+                if self.show_code {
+                    if finding.line != finding.end_line {
+                        unimplemented!()
+                    }
+                    let start = (finding.column - 1) as usize;
+                    let end = (finding.end_column - 1) as usize;
+                    let printable_line = input.lines().collect::<Vec<&str>>()[(finding.line - 1) as usize];
+                    println!(
+                        "\n\x1b[38;5;248m{}\x1b[38;5;208;1m{}\x1b[38;5;248m{}\x1b[0m",
+                        &printable_line[..start],
+                        &printable_line[start..end],
+                        &printable_line[end..],
+                    );
+                    println!("{}{} ↖ right here", " ".repeat(start), "▔".repeat(end - start));
+                    println!("\n");
+                }
+            }
         }
+
         Ok(())
     }
+
+    /// Lint the code of one file, with the settings for where it lives.
+    fn lint_file(
+        &self,
+        ctx: &mut crate::context::Context<'_>,
+        path: &std::path::Path,
+        input: &str,
+    ) -> Result<Vec<LintFinding>> {
+        // The command line flags take precedence over the project.toml.
+        let mut lint_settings = get_lint_settings_from_project_toml(path)?;
+        if !self.select.is_empty() {
+            lint_settings.select = self.select.clone();
+        }
+        if !self.ignore.is_empty() {
+            lint_settings.ignore = self.ignore.clone();
+        }
+
+        let path = path.to_str().unwrap_or("");
+        let cache = if self.cache {
+            let dir = crate::lint_cache::dir()?;
+            let key = crate::lint_cache::key(input);
+            let findings = crate::lint_cache::lookup(&dir, &key);
+            Some((dir, key, findings))
+        } else {
+            None
+        };
+        let findings = match &cache {
+            Some((_, _, Some(findings))) => {
+                if ctx.debug {
+                    writeln!(ctx.io.err_out, "{} is unchanged, using the cached findings", path)?;
+                }
+                findings.clone()
+            }
+            _ => {
+                let findings = lint(input)?;
+                // Linting worked, so a cache we can't write to shouldn't fail the command.
+                if let Some((dir, key, _)) = &cache {
+                    if let Err(err) = crate::lint_cache::store(dir, key, &findings) {
+                        writeln!(ctx.io.err_out, "warning: failed to cache the lint findings: {}", err)?;
+                    }
+                }
+                findings
+            }
+        };
+
+        Ok(findings
+            .into_iter()
+            .filter(|f| lint_settings.is_enabled(&f.code))
+            .map(|f| LintFinding {
+                path: path.to_string(),
+                ..f
+            })
+            .collect())
+    }
 }
 
-/// Get the center of mass of objects in a kcl file.
+/// Parse and lint some code, returning every finding, without a path.
+fn lint(input: &str) -> Result<Vec<LintFinding>> {
+    let program = kcl_lib::Program::parse_no_errs(input)?;
+
+    let findings = program
+        .lint_all()?
+        .into_iter()
+        .map(|discovered_finding| {
+            let finding_range = discovered_finding.pos.to_lsp_range(input);
+            LintFinding {
+                path: String::new(),
+                line: finding_range.start.line + 1,
+                column: finding_range.start.character + 1,
+                end_line: finding_range.end.line + 1,
+                end_column: finding_range.end.character + 1,
+                code: discovered_finding.finding.code.to_string(),
+                title: if discovered_finding.description.is_empty() {
+                    discovered_finding.finding.title.to_owned()
+                } else {
+                    format!(
+                        "{} ({})",
+                        discovered_finding.finding.title, discovered_finding.description
+                    )
+                },
+                description: discovered_finding.finding.description.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(findings)
+}
+
+/// List the files a `kcl` file imports, and the files those import.
 ///
-///     # get the mass of a file
-///     $ zoo kcl center-of-mass --src-unit=m my-file.kcl
+///     $ zoo kcl deps main.kcl
 ///
-///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl center-of-mass --src-unit=m
+///     # as json, for build tools
+///     $ zoo kcl deps --format json main.kcl
 ///
-/// By default, this will search the input path for a `project.toml` file to determine the source
-/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
-/// of the input path OR any parent directories above that, the default
-/// source unit will be millimeters. You can also specify the source unit with the
-/// `--src-unit`/`-s` command line flag.
+/// The first entry is always the input file itself. Every import after that has the
+/// import string as written in the file, and the absolute path it resolves to.
+/// Imports that do not exist are marked as missing, and imports that lead back to a file
+/// that is already being imported are marked as a cycle and not followed.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclCenterOfMass {
-    /// The path to the input file.
-    /// If you pass `-` as the path, the file will be read from stdin.
+pub struct CmdKclDeps {
+    /// The path to the input kcl file.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// The source unit to use for the kcl file.
-    /// This defaults to millimeters, if not set and there is no project.toml.
-    /// If there is a project.toml file, the default unit will be the one set in the project.toml
-    /// file.
-    #[clap(long, short = 's', value_enum)]
-    pub src_unit: Option<kittycad::types::UnitLength>,
-
-    /// Output format.
+    /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+}
 
-    /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitLength,
-
-    /// If true, print a link to this request's tracing data.
-    #[clap(long, default_value = "false")]
-    pub show_trace: bool,
+/// A dependency of a `kcl` file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct KclDependency {
+    /// The import string, as written in the file that imports it.
+    /// This is the path given on the command line for the input file.
+    pub import: String,
+    /// The absolute path of the file that imports it.
+    /// This is empty for the input file.
+    pub imported_by: String,
+    /// The absolute path the import resolves to.
+    pub path: String,
+    /// If the file does not exist.
+    pub missing: bool,
+    /// If the import leads back to a file that is already being imported.
+    pub cycle: bool,
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclCenterOfMass {
+impl crate::cmd::Command for CmdKclDeps {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        if self.input.to_str() == Some("-") {
+            anyhow::bail!("cannot find the dependencies of stdin, imports are relative to the file");
+        }
+        if !self.input.is_file() {
+            anyhow::bail!("File '{}' does not exist.", self.input.display());
+        }
 
-        // Get the modeling settings from the project.toml if exists.
-        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+        let root = std::fs::canonicalize(&self.input)?;
+        let mut deps = vec![KclDependency {
+            import: self.input.display().to_string(),
+            imported_by: String::new(),
+            path: root.display().to_string(),
+            missing: false,
+            cycle: false,
+        }];
+        collect_kcl_dependencies(&root, &mut vec![root.clone()], &mut Default::default(), &mut deps)?;
+
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, deps)?;
+        Ok(())
+    }
+}
 
-        // Spin up websockets and do the conversion.
-        // This will not return until there are files.
-        let (resp, session_data) = ctx
-            .send_kcl_modeling_cmd(
-                "",
-                input,
-                kittycad_modeling_cmds::ModelingCmd::CenterOfMass(kittycad_modeling_cmds::CenterOfMass {
-                    entity_ids: vec![], // get whole model
-                    output_unit: self.output_unit.clone().into(),
-                }),
-                executor_settings,
-            )
-            .await?;
+/// Walk the imports of a `kcl` file, depth first.
+/// `stack` is the chain of files that led us here, which is how we spot cycles, and `visited`
+/// makes sure we only list the imports of each file once.
+fn collect_kcl_dependencies(
+    file: &std::path::Path,
+    stack: &mut Vec<std::path::PathBuf>,
+    visited: &mut std::collections::HashSet<std::path::PathBuf>,
+    deps: &mut Vec<KclDependency>,
+) -> Result<()> {
+    if !visited.insert(file.to_path_buf()) {
+        return Ok(());
+    }
 
-        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::CenterOfMass(data),
-        } = &resp
-        {
-            // Print the output.
-            let format = ctx.format(&self.format)?;
-            ctx.io.write_output(&format, &data)?;
+    let contents = std::fs::read_to_string(file)?;
+    let imports = get_kcl_imports(&normalize_line_endings(&contents))
+        .map_err(|err| anyhow::anyhow!("failed to parse `{}`: {}", file.display(), err))?;
+    let dir = file.parent().unwrap_or(std::path::Path::new(""));
+    for import in imports {
+        let resolved = dir.join(&import);
+        let missing = !resolved.is_file();
+        let path = if missing {
+            resolved
         } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            std::fs::canonicalize(&resolved)?
+        };
+        let cycle = stack.contains(&path);
+
+        deps.push(KclDependency {
+            import,
+            imported_by: file.display().to_string(),
+            path: path.display().to_string(),
+            missing,
+            cycle,
+        });
+
+        // Other files, like `.step` models, are only listed, they can't import anything.
+        let is_kcl = path.extension().is_some_and(|ext| ext == "kcl");
+        if is_kcl && !missing && !cycle {
+            stack.push(path.clone());
+            collect_kcl_dependencies(&path, stack, visited, deps)?;
+            stack.pop();
         }
+    }
 
-        if self.show_trace {
-            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+    Ok(())
+}
+
+/// Get the paths of the files imported by some `kcl` code, in order.
+fn get_kcl_imports(code: &str) -> Result<Vec<String>> {
+    let program = kcl_lib::Program::parse_no_errs(code)
+        .map_err(|err| crate::kcl_error_fmt::KclError::new(code.to_string(), err))?;
+
+    // Imports can only be at the top level, so there is no need to look any deeper.
+    let ast = serde_json::to_value(&program)?;
+    Ok(ast
+        .get("body")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("type").and_then(serde_json::Value::as_str) == Some("ImportStatement"))
+        .filter_map(|item| item.get("path").and_then(serde_json::Value::as_str))
+        .map(|path| path.to_string())
+        .collect())
+}
+
+/// Sort the groups of `import` statements in a program by the file they import, and the
+/// names each one imports by name.
+/// A group is a run of imports with no blank lines or comments between them. The
+/// comments and blank lines after a group stay where they are, since the formatter keeps
+/// them by the position of the statement they follow.
+fn sort_imports(program: kcl_lib::Program) -> Result<kcl_lib::Program> {
+    use serde_json::Value;
+
+    let mut ast = serde_json::to_value(&program)?;
+    let after_statement: std::collections::HashSet<usize> = ast
+        .pointer("/nonCodeMeta/nonCodeNodes")
+        .and_then(Value::as_object)
+        .map(|nodes| nodes.keys().filter_map(|i| i.parse().ok()).collect())
+        .unwrap_or_default();
+    let Some(body) = ast.get_mut("body").and_then(Value::as_array_mut) else {
+        anyhow::bail!("the program has no body");
+    };
+
+    let is_import = |item: &Value| item.get("type").and_then(Value::as_str) == Some("ImportStatement");
+    let name = |item: &Value| {
+        item.pointer("/name/name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let path = |item: &Value| match item.get("path") {
+        Some(Value::String(path)) => path.clone(),
+        path => path.map(Value::to_string).unwrap_or_default(),
+    };
+
+    for item in body.iter_mut().filter(|item| is_import(item)) {
+        if let Some(items) = item.get_mut("items").and_then(Value::as_array_mut) {
+            items.sort_by_key(name);
         }
-        Ok(())
     }
+
+    let mut start = 0;
+    while start < body.len() {
+        if !is_import(&body[start]) {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < body.len() && is_import(&body[end]) {
+            end += 1;
+            if after_statement.contains(&(end - 1)) {
+                break;
+            }
+        }
+        body[start..end].sort_by_key(path);
+        start = end;
+    }
+
+    Ok(serde_json::from_value(ast)?)
 }
 
-/// Get the density of objects in a kcl file.
+/// Print the metadata and parameters of a `kcl` file.
 ///
-///     # get the density of a file
-///     $ zoo kcl density --src-unit=m my-file.kcl
+///     $ zoo kcl meta my-file.kcl
 ///
-///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl density --src-unit=m
+///     # as json, for tools that set the parameters with `--param`
+///     $ zoo kcl meta --format json my-file.kcl
 ///
-/// By default, this will search the input path for a `project.toml` file to determine the source
-/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
-/// of the input path OR any parent directories above that, the default
-/// source unit will be millimeters. You can also specify the source unit with the
-/// `--src-unit`/`-s` command line flag.
+/// The metadata is the comment block at the top of the file, before the first blank
+/// line, where the first line is the title and the rest is the description, and the
+/// units from a `project.toml` file, if there is one.
+///
+/// The parameters are the variables declared at the top level of the file with a plain
+/// number, string or boolean value, which are the ones that make sense to change with
+/// `--param`. The description of a parameter is the comment at the end of its line, or
+/// the comment lines right above it.
+///
+/// Nothing is printed if the file has no metadata or parameters.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclDensity {
-    /// The path to the input file.
+pub struct CmdKclMeta {
+    /// The path to the input kcl file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// The source unit to use for the kcl file.
-    /// This defaults to millimeters, if not set and there is no project.toml.
-    /// If there is a project.toml file, the default unit will be the one set in the project.toml
-    /// file.
-    #[clap(long, short = 's', value_enum)]
-    pub src_unit: Option<kittycad::types::UnitLength>,
-
-    /// Material mass.
-    #[clap(short = 'm', long = "material-mass")]
-    material_mass: f32,
-
-    /// The unit of the material mass.
-    #[clap(long = "material-mass-unit", value_enum)]
-    material_mass_unit: kittycad::types::UnitMass,
+    /// If the input is a directory, the kcl file in it to read.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
 
-    /// Output format.
+    /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+}
 
-    /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitDensity,
+/// What a `KclMetaItem` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, serde::Serialize)]
+#[display(style = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum KclMetaKind {
+    /// About the whole file, like its title.
+    Metadata,
+    /// A variable that can be set with `--param`.
+    Parameter,
+}
 
-    /// If true, print a link to this request's tracing data.
-    #[clap(long, default_value = "false")]
-    pub show_trace: bool,
+/// A piece of metadata or a parameter of a `kcl` file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, tabled::Tabled)]
+pub struct KclMetaItem {
+    /// Whether this is metadata or a parameter.
+    pub kind: KclMetaKind,
+    /// The name of the metadata or parameter.
+    pub name: String,
+    /// The value, as written in the file for a parameter.
+    pub value: String,
+    /// What the parameter is for, from its comments.
+    pub description: String,
+    /// The line of the parameter, starting at 1, or 0 for metadata.
+    pub line: usize,
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclDensity {
+impl crate::cmd::Command for CmdKclMeta {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        if self.material_mass == 0.0 {
-            anyhow::bail!("`--material-mass` must not be 0.0");
-        }
-
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
-
-        // Get the modeling settings from the project.toml if exists.
-        let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
-
-        // Spin up websockets and do the conversion.
-        // This will not return until there are files.
-        let (resp, session_data) = ctx
-            .send_kcl_modeling_cmd(
-                "",
-                input,
-                kittycad_modeling_cmds::ModelingCmd::Density(kittycad_modeling_cmds::Density {
-                    entity_ids: vec![], // get whole model
-                    material_mass: self.material_mass.into(),
-                    material_mass_unit: self.material_mass_unit.clone().into(),
-                    output_unit: self.output_unit.clone().into(),
-                }),
-                executor_settings,
+        let (code, path) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
+
+        // Only look at files that parse, so the parameters are real declarations.
+        kcl_lib::Program::parse_no_errs(&code).map_err(|err| {
+            anyhow::anyhow!(
+                "failed to parse `{}`: {}",
+                path.display(),
+                crate::kcl_error_fmt::KclError::new(code.to_string(), err)
             )
-            .await?;
+        })?;
 
-        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::Density(data),
-        } = &resp
+        let mut items = kcl_meta(&code);
+
+        // The units are only metadata when a `project.toml` sets them, not the default.
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        if path.to_str() != Some("-")
+            && input_url(&path).is_none()
+            && find_project_toml(dir.unwrap_or(std::path::Path::new(".")))?.is_some()
         {
-            // Print the output.
-            let format = ctx.format(&self.format)?;
-            ctx.io.write_output(&format, &data)?;
-        } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+            let settings = get_modeling_settings_from_project_toml(&path, None)?;
+            let units: kittycad::types::UnitLength = settings.units.into();
+            let position = items
+                .iter()
+                .position(|item| item.kind == KclMetaKind::Parameter)
+                .unwrap_or(items.len());
+            items.insert(
+                position,
+                KclMetaItem {
+                    kind: KclMetaKind::Metadata,
+                    name: "units".to_string(),
+                    value: units.to_string(),
+                    description: String::new(),
+                    line: 0,
+                },
+            );
         }
 
-        if self.show_trace {
-            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        if items.is_empty() {
+            return Ok(());
         }
+
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, items)?;
         Ok(())
     }
 }
 
-/// Get the surface area of objects in a kcl file.
+/// Get the title, description and parameters of some `kcl` code.
+fn kcl_meta(code: &str) -> Vec<KclMetaItem> {
+    let lines: Vec<&str> = code.lines().collect();
+    let comment = |line: &str| line.trim().strip_prefix("//").map(|c| c.trim().to_string());
+    let metadata = |name: &str, value: String| KclMetaItem {
+        kind: KclMetaKind::Metadata,
+        name: name.to_string(),
+        value,
+        description: String::new(),
+        line: 0,
+    };
+
+    let mut items = Vec::new();
+
+    // The comment block at the top is only about the file if a blank line ends it,
+    // otherwise it is about the first statement.
+    let mut header_len = lines.iter().take_while(|line| comment(line).is_some()).count();
+    if lines.get(header_len).filter(|line| !line.trim().is_empty()).is_some() {
+        header_len = 0;
+    }
+    if header_len > 0 {
+        let mut header = lines[..header_len].iter().filter_map(|line| comment(line));
+        if let Some(title) = header.next().filter(|title| !title.is_empty()) {
+            items.push(metadata("title", title));
+        }
+        let description = header.filter(|c| !c.is_empty()).collect::<Vec<_>>().join(" ");
+        if !description.is_empty() {
+            items.push(metadata("description", description));
+        }
+    }
+
+    let re = regex::Regex::new(
+        r#"^(?:export\s+)?(?:(?:let|const|var)\s+)?([A-Za-z_][A-Za-z0-9_]*)\s*=\s*(-?\d+(?:\.\d+)?|"[^"]*"|true|false)\s*(?://(.*))?$"#,
+    )
+    .unwrap();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(captures) = re.captures(line) else {
+            continue;
+        };
+
+        let description = match captures.get(3) {
+            Some(trailing) => trailing.as_str().trim().to_string(),
+            None => {
+                let above = lines[header_len.min(i)..i]
+                    .iter()
+                    .rev()
+                    .map_while(|line| comment(line))
+                    .collect::<Vec<_>>();
+                above.into_iter().rev().collect::<Vec<_>>().join(" ")
+            }
+        };
+
+        items.push(KclMetaItem {
+            kind: KclMetaKind::Parameter,
+            name: captures[1].to_string(),
+            value: captures[2].to_string(),
+            description,
+            line: i + 1,
+        });
+    }
+
+    items
+}
+
+/// Measure how long the engine takes to run a `kcl` file.
 ///
-///     # get the surface-area of a file
-///     $ zoo kcl surface-area --src-unit=m my-file.kcl
+/// This runs the file and exports it (or takes a snapshot of it) again and again, then
+/// prints how long the runs took. Each run includes connecting to the engine, unless
+/// `--reuse-connection` is given, in which case only the first one does.
 ///
-///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl surface-area --src-unit=m
+///     $ zoo kcl bench --iterations 10 my-file.kcl
+///
+///     # time snapshots over a single connection, and keep the results
+///     $ zoo kcl bench --mode snapshot --reuse-connection --format json my-file.kcl > bench.json
 ///
 /// By default, this will search the input path for a `project.toml` file to determine the source
 /// unit and any specific execution settings. If no `project.toml` file is found, in the directory
@@ -934,156 +4826,279 @@ impl crate::cmd::Command for CmdKclDensity {
 /// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclSurfaceArea {
-    /// The path to the input file.
+pub struct CmdKclBench {
+    /// The path to the input kcl file.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
+
     /// The source unit to use for the kcl file.
     /// This defaults to millimeters, if not set and there is no project.toml.
     /// If there is a project.toml file, the default unit will be the one set in the project.toml
     /// file.
-    #[clap(long, short = 's', value_enum)]
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
     pub src_unit: Option<kittycad::types::UnitLength>,
 
-    /// Output format.
+    /// How many times to run the file.
+    #[clap(long, short = 'n', default_value = "10")]
+    pub iterations: usize,
+
+    /// What to do with the model after running the file, each time.
+    #[clap(long, value_enum, default_value_t)]
+    pub mode: BenchMode,
+
+    /// The file format to export to, with `--mode export`.
+    #[clap(short = 't', long = "output-format", value_enum, default_value = "step")]
+    output_format: kittycad::types::FileExportFormat,
+
+    /// Run every iteration over the same engine connection, clearing the scene in
+    /// between, instead of connecting again each time.
+    #[clap(long, default_value = "false")]
+    pub reuse_connection: bool,
+
+    /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+}
 
-    /// Output unit.
-    #[clap(long = "output-unit", short = 'u', value_enum)]
-    pub output_unit: kittycad::types::UnitArea,
+/// What `zoo kcl bench` does with the model after running the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BenchMode {
+    /// Export the model.
+    #[default]
+    Export,
+    /// Take a snapshot of the model, as a png.
+    Snapshot,
+}
 
-    /// If true, print a link to this request's tracing data.
-    #[clap(long, default_value = "false")]
-    pub show_trace: bool,
+/// How long the runs of `zoo kcl bench` took, in milliseconds.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct BenchSummary {
+    /// The number of runs.
+    pub iterations: usize,
+    /// The fastest run.
+    pub min_ms: f64,
+    /// The median run.
+    pub median_ms: f64,
+    /// The run that 95% of runs were at least as fast as.
+    pub p95_ms: f64,
+    /// The slowest run.
+    pub max_ms: f64,
+    /// The average run.
+    pub mean_ms: f64,
+    /// How many runs could be done in a minute, at the average speed.
+    pub runs_per_minute: f64,
+    /// Every run, in the order they were made.
+    #[tabled(skip)]
+    pub runs_ms: Vec<f64>,
+}
+
+impl BenchSummary {
+    /// Summarize the durations of some runs. There must be at least one.
+    fn new(runs: &[std::time::Duration]) -> Self {
+        let runs_ms: Vec<f64> = runs.iter().map(|run| run.as_secs_f64() * 1000.0).collect();
+        let mut sorted = runs_ms.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let n = sorted.len();
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        } else {
+            sorted[n / 2]
+        };
+        // The nearest rank percentile.
+        let p95 = sorted[((n as f64 * 0.95).ceil() as usize).clamp(1, n) - 1];
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let round = |ms: f64| (ms * 100.0).round() / 100.0;
+
+        BenchSummary {
+            iterations: n,
+            min_ms: round(sorted[0]),
+            median_ms: round(median),
+            p95_ms: round(p95),
+            max_ms: round(sorted[n - 1]),
+            mean_ms: round(mean),
+            runs_per_minute: if mean > 0.0 { round(60_000.0 / mean) } else { 0.0 },
+            runs_ms: runs_ms.into_iter().map(round).collect(),
+        }
+    }
 }
 
 #[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclSurfaceArea {
+impl crate::cmd::Command for CmdKclBench {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        // Get the contents of the input file.
-        let input = ctx.read_file(self.input.to_str().unwrap_or(""))?;
-        // Parse the input as a string.
-        let input = std::str::from_utf8(&input)?;
+        if self.iterations < 1 {
+            anyhow::bail!("--iterations must be greater than 0");
+        }
 
-        // Get the modeling settings from the project.toml if exists.
+        let (code, _) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
         let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
 
-        // Spin up websockets and do the conversion.
-        // This will not return until there are files.
-        let (resp, session_data) = ctx
-            .send_kcl_modeling_cmd(
-                "",
-                input,
-                kittycad_modeling_cmds::ModelingCmd::SurfaceArea(kittycad_modeling_cmds::SurfaceArea {
-                    entity_ids: vec![], // get whole model
-                    output_unit: self.output_unit.clone().into(),
-                }),
-                executor_settings,
-            )
-            .await?;
+        let cmd = match self.mode {
+            BenchMode::Export => kcmc::ModelingCmd::Export(kcmc::Export {
+                entity_ids: vec![],
+                format: get_output_format(&self.output_format, executor_settings.units.into()),
+            }),
+            BenchMode::Snapshot => kcmc::ModelingCmd::TakeSnapshot(kcmc::TakeSnapshot {
+                format: kcmc::ImageFormat::Png,
+            }),
+        };
 
-        if let kittycad_modeling_cmds::websocket::OkWebSocketResponseData::Modeling {
-            modeling_response: kittycad_modeling_cmds::ok_response::OkModelingCmdResponse::SurfaceArea(data),
-        } = &resp
-        {
-            // Print the output.
-            let format = ctx.format(&self.format)?;
-            ctx.io.write_output(&format, &data)?;
-        } else {
-            anyhow::bail!("Unexpected response from engine: {:?}", resp);
+        let mut runs = Vec::with_capacity(self.iterations);
+        let mut engine_ctx = None;
+        for i in 0..self.iterations {
+            let start = std::time::Instant::now();
+            if !self.reuse_connection {
+                ctx.send_kcl_modeling_cmd(
+                    "",
+                    &code,
+                    cmd.clone(),
+                    executor_settings.clone(),
+                    Some(crate::context::DEFAULT_ZOOM_PADDING),
+                )
+                .await?;
+            } else if let Some(engine_ctx) = &engine_ctx {
+                ctx.reexecute_kcl(engine_ctx, &code).await?;
+                self.send_cmd(engine_ctx, &cmd).await?;
+            } else {
+                let (new_ctx, _) = ctx.execute_kcl("", &code, executor_settings.clone()).await?;
+                self.send_cmd(&new_ctx, &cmd).await?;
+                engine_ctx = Some(new_ctx);
+            }
+            runs.push(start.elapsed());
+            log::debug!("bench run {}/{} took {:?}", i + 1, self.iterations, runs[i]);
         }
 
-        if self.show_trace {
-            print_trace_link(&mut ctx.io, &session_data.map(kt::ModelingSessionData::from))
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output(&format, &BenchSummary::new(&runs))?;
+
+        Ok(())
+    }
+}
+
+impl CmdKclBench {
+    /// Send the command being timed over a connection that has already run the file.
+    async fn send_cmd(&self, engine_ctx: &kcl_lib::ExecutorContext, cmd: &kcmc::ModelingCmd) -> Result<()> {
+        if self.mode == BenchMode::Snapshot {
+            // Frame the model the same way `send_kcl_modeling_cmd` does.
+            engine_ctx
+                .engine
+                .send_modeling_cmd(
+                    uuid::Uuid::new_v4(),
+                    kcl_lib::SourceRange::default(),
+                    kcmc::ModelingCmd::ZoomToFit(kcmc::ZoomToFit {
+                        animated: false,
+                        object_ids: Default::default(),
+                        padding: crate::context::DEFAULT_ZOOM_PADDING,
+                    }),
+                )
+                .await?;
         }
+        engine_ctx
+            .engine
+            .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), cmd.clone())
+            .await?;
         Ok(())
     }
 }
 
-/// Lint a KCL file for style issues.
+/// Run a `kcl` project once, to warm up the engine before a batch of renders.
 ///
-///     # check a file for issues
-///     $ zoo kcl lint my-file.kcl
+/// With `--session`, the project is run on a session server from `zoo start-session`,
+/// so its engine connection is warm for the `zoo kcl snapshot --session` runs after it.
+/// Without it, the project is run over a new connection, which is closed after, to check
+/// it runs and see how long a cold start takes.
 ///
-///     # pass a file from stdin
-///     $ cat my-file.kcl | zoo kcl lint -
+/// Either way, any errors in the project are reported up front, and how long each
+/// part of the run took is printed, in milliseconds.
+///
+///     # warm up a session server for a project
+///     $ zoo start-session 127.0.0.1:3333 &
+///     $ zoo kcl warm --session 127.0.0.1:3333 my-project/
+///     $ zoo kcl snapshot --session 127.0.0.1:3333 my-project/ my-project.png
+///
+///     # see how long a cold start takes
+///     $ zoo kcl warm my-file.kcl
+///
+/// By default, this will search the input path for a `project.toml` file to determine the source
+/// unit and any specific execution settings. If no `project.toml` file is found, in the directory
+/// of the input path OR any parent directories above that, the default
+/// source unit will be millimeters. You can also specify the source unit with the
+/// `--src-unit`/`-s` command line flag.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdKclLint {
-    /// The path to the input file.
+pub struct CmdKclWarm {
+    /// The path to the input kcl file, or a project directory.
     /// If you pass `-` as the path, the file will be read from stdin.
     #[clap(name = "input", required = true)]
     pub input: std::path::PathBuf,
 
-    /// Print a long-form description of what the issue is, and the rational
-    /// behind why.
-    #[clap(long, default_value = "false")]
-    pub descriptions: bool,
-
-    /// Show where the offending KCL source code is.
-    #[clap(long, short, default_value = "false")]
-    pub show_code: bool,
-}
+    /// If the input is a directory, the kcl file in it to run.
+    /// This defaults to `main.kcl`.
+    #[clap(long)]
+    pub assembly_main: Option<String>,
 
-#[async_trait::async_trait(?Send)]
-impl crate::cmd::Command for CmdKclLint {
-    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
-        let path = self.input.to_str().unwrap_or("");
-        let input = ctx.read_file(path)?;
-        let input = std::str::from_utf8(&input)?;
+    /// The source unit to use for the kcl file.
+    /// This defaults to millimeters, if not set and there is no project.toml.
+    /// If there is a project.toml file, the default unit will be the one set in the project.toml
+    /// file.
+    #[clap(long, short = 's', value_parser = crate::units::UnitParser::<kittycad::types::UnitLength>::new())]
+    pub src_unit: Option<kittycad::types::UnitLength>,
 
-        // Parse the file.
-        let program = kcl_lib::Program::parse_no_errs(input)?;
+    /// The session server to warm, started with `zoo start-session`.
+    #[clap(long, default_value = None)]
+    pub session: Option<SocketAddr>,
 
-        for discovered_finding in program.lint_all()? {
-            let finding_range = discovered_finding.pos.to_lsp_range(input);
-            let start = finding_range.start;
-            let end = finding_range.end;
+    /// Command output format.
+    #[clap(long, short, value_enum)]
+    pub format: Option<crate::types::FormatOutput>,
+}
 
-            let title = if discovered_finding.description.is_empty() {
-                discovered_finding.finding.title.to_owned()
-            } else {
-                format!(
-                    "{} ({})",
-                    discovered_finding.finding.title, discovered_finding.description
-                )
-            };
+/// How long a part of `zoo kcl warm` took.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, tabled::Tabled)]
+pub struct WarmTiming {
+    /// The part of the run, like `connect` or `execute`, or `total` for all of it.
+    pub phase: String,
+    /// How long it took, in milliseconds.
+    pub ms: f64,
+}
 
-            println!(
-                "{}:{}:{}: [{}] {}",
-                path,
-                start.line + 1,
-                start.character + 1,
-                discovered_finding.finding.code,
-                title,
-            );
+#[async_trait::async_trait(?Send)]
+impl crate::cmd::Command for CmdKclWarm {
+    async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        let start = std::time::Instant::now();
+        let (code, path) = get_code_and_file_path(ctx, &self.input, self.assembly_main.as_deref()).await?;
 
-            if self.descriptions {
-                println!("\n{}", discovered_finding.finding.description);
+        let mut phases = match self.session {
+            Some(addr) => {
+                let start = std::time::Instant::now();
+                run_on_session(addr, code, path.display().to_string()).await?;
+                vec![("session".to_string(), start.elapsed())]
             }
-
-            if self.show_code {
-                if start.line != end.line {
-                    unimplemented!()
-                }
-                let printable_line = input.lines().collect::<Vec<&str>>()[start.line as usize];
-                println!(
-                    "\n\x1b[38;5;248m{}\x1b[38;5;208;1m{}\x1b[38;5;248m{}\x1b[0m",
-                    &printable_line[..(start.character as usize)],
-                    &printable_line[(start.character as usize)..(end.character as usize)],
-                    &printable_line[(end.character as usize)..],
-                );
-                println!(
-                    "{}{} ↖ right here",
-                    " ".repeat(start.character as usize),
-                    "▔".repeat((end.character - start.character) as usize)
-                );
-                println!("\n");
+            None => {
+                let executor_settings = get_modeling_settings_from_project_toml(&self.input, self.src_unit.clone())?;
+                // Connecting and executing record how long they took in the timings.
+                ctx.timings = Some(Default::default());
+                ctx.execute_kcl("", &code, executor_settings).await?;
+                ctx.timings.take().map(|timings| timings.phases()).unwrap_or_default()
             }
-        }
+        };
+        phases.push(("total".to_string(), start.elapsed()));
+
+        let timings: Vec<WarmTiming> = phases
+            .into_iter()
+            .map(|(phase, duration)| WarmTiming {
+                phase,
+                ms: (duration.as_secs_f64() * 100_000.0).round() / 100.0,
+            })
+            .collect();
+        let format = ctx.format(&self.format)?;
+        ctx.io.write_output_for_vec(&format, timings)?;
 
         Ok(())
     }
@@ -1111,6 +5126,137 @@ fn print_trace_link(io: &mut IoStreams, session_data: &Option<kittycad::types::M
     );
 }
 
+/// Read the kcl code for an input, and return it with the path of the file it came from.
+/// If the input is a directory, the code is read from `assembly_main` in it, or `main.kcl`
+/// if that isn't given.
+async fn get_code_and_file_path(
+    ctx: &mut crate::context::Context<'_>,
+    input: &std::path::Path,
+    assembly_main: Option<&str>,
+) -> Result<(String, std::path::PathBuf)> {
+    let path = if input.is_dir() {
+        let main = assembly_main.unwrap_or("main.kcl");
+        let path = input.join(main);
+        if !path.is_file() {
+            anyhow::bail!(
+                "`{}` does not exist in directory `{}`{}",
+                main,
+                input.display(),
+                if assembly_main.is_none() {
+                    ", use `--assembly-main` to pick the file to run"
+                } else {
+                    ""
+                }
+            );
+        }
+        path
+    } else {
+        if assembly_main.is_some() {
+            anyhow::bail!("`--assembly-main` can only be used when the input is a directory");
+        }
+        input.to_path_buf()
+    };
+
+    // Get the contents of the input file.
+    let code = read_kcl_input(ctx, &path).await?;
+    // Parse the input as a string.
+    let code = normalize_line_endings(kcl_text(&code, &path)?);
+
+    Ok((code, path))
+}
+
+/// Read an input file, which can also be `-` for stdin or an `http://` or `https://` URL
+/// to download it from.
+async fn read_kcl_input(ctx: &mut crate::context::Context<'_>, path: &std::path::Path) -> Result<Vec<u8>> {
+    match input_url(path) {
+        Some(url) => ctx.read_url(url).await,
+        None => ctx.read_file(path.to_str().unwrap_or("")),
+    }
+}
+
+/// The URL of an input, if it is one rather than a path.
+/// There is nothing next to a URL to import or to take settings from, so these inputs
+/// are treated like stdin for those.
+fn input_url(input: &std::path::Path) -> Option<&str> {
+    input
+        .to_str()
+        .filter(|input| input.starts_with("http://") || input.starts_with("https://"))
+}
+
+/// Parse a `--param` as `name=value`.
+fn parse_param(s: &str) -> Result<(String, String)> {
+    let Some((name, value)) = s.split_once('=') else {
+        anyhow::bail!("`{}` should be `name=value`", s);
+    };
+    let (name, value) = (name.trim(), value.trim());
+    if name.is_empty() || value.is_empty() {
+        anyhow::bail!("`{}` should be `name=value`", s);
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Replace the values of variables declared at the top level of the code with the
+/// `--param` values, in order, so a later value for the same name wins.
+/// A declaration ends before the next line that starts a new statement or comment at
+/// the start of the line, so values can span several lines.
+fn apply_params(code: &str, params: &[(String, String)]) -> Result<String> {
+    if params.is_empty() {
+        return Ok(code.to_string());
+    }
+
+    let mut lines: Vec<String> = code.lines().map(|line| line.to_string()).collect();
+    for (name, value) in params {
+        let Some((start, equals)) = lines
+            .iter()
+            .enumerate()
+            .find_map(|(i, line)| declaration_value_start(line, name).map(|equals| (i, equals)))
+        else {
+            anyhow::bail!("`{}` is not a variable declared at the top level of the file", name);
+        };
+
+        let mut end = (start + 1..lines.len())
+            .find(|&i| lines[i].starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '/'))
+            .unwrap_or(lines.len());
+        // Keep the blank lines between it and the next statement.
+        while end > start + 1 && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+
+        let declaration = format!("{} {}", &lines[start][..equals], value);
+        lines.splice(start..end, [declaration]);
+    }
+
+    let mut code_with_params = lines.join("\n");
+    if code.ends_with('\n') {
+        code_with_params.push('\n');
+    }
+
+    // Catch a bad value here, rather than with a confusing error about the whole file.
+    kcl_lib::Program::parse_no_errs(&code_with_params).map_err(|err| {
+        anyhow::anyhow!(
+            "the file does not parse with the `--param` values: {}",
+            crate::kcl_error_fmt::KclError::new(code_with_params.clone(), err)
+        )
+    })?;
+
+    Ok(code_with_params)
+}
+
+/// If the line declares the variable at the top level, where its value starts, just
+/// after the `=`.
+fn declaration_value_start(line: &str, name: &str) -> Option<usize> {
+    let mut rest = line.strip_prefix("export ").unwrap_or(line);
+    for keyword in ["let ", "const ", "var "] {
+        rest = rest.strip_prefix(keyword).unwrap_or(rest);
+    }
+    let rest = rest.strip_prefix(name)?;
+    let after_name = rest.trim_start();
+    if !after_name.starts_with('=') || after_name.starts_with("==") {
+        return None;
+    }
+    Some(line.len() - after_name.len() + 1)
+}
+
 /// Look for a `project.toml` file the same directory as the input file.
 /// Use that for the engine settings.
 fn get_modeling_settings_from_project_toml(
@@ -1124,8 +5270,8 @@ fn get_modeling_settings_from_project_toml(
         ..Default::default()
     };
 
-    // Check if the path was stdin.
-    if input.to_str() == Some("-") {
+    // Check if the path was stdin or a URL.
+    if input.to_str() == Some("-") || input_url(input).is_some() {
         return Ok(default_settings);
     }
 
@@ -1184,21 +5330,834 @@ pub fn find_project_toml(path: &std::path::Path) -> Result<Option<std::path::Pat
 
 /// Make the exported file have a deterministic date for git and version control etc.
 pub fn write_deterministic_export(file_path: &std::path::Path, file_contents: &[u8]) -> Result<()> {
-    if let Ok(contents) = std::str::from_utf8(file_contents) {
-        let mut content = contents.to_string();
+    std::fs::write(file_path, deterministic_export_contents(file_contents)?)?;
+
+    Ok(())
+}
 
+/// Replace any dates in the exported file contents with a fixed date.
+fn deterministic_export_contents(file_contents: &[u8]) -> Result<Vec<u8>> {
+    if let Ok(contents) = std::str::from_utf8(file_contents) {
         // Create a regex pattern for finding the date.
         let re = regex::Regex::new(r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d+\+\d{2}:\d{2}")?;
 
         // Replace all occurrences.
-        content = re.replace_all(&content, "1970-01-01T00:00:00.0+00:00").to_string();
-
-        // Write the modified content back to the file.
-        std::fs::write(file_path, content)?;
+        Ok(re
+            .replace_all(contents, "1970-01-01T00:00:00.0+00:00")
+            .to_string()
+            .into_bytes())
     } else {
-        // Write the content back to the file.
-        std::fs::write(file_path, file_contents)?;
+        Ok(file_contents.to_vec())
     }
+}
 
-    Ok(())
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        let color = parse_hex_color("#ff0000").unwrap();
+        assert_eq!((color.r, color.g, color.b, color.a), (1.0, 0.0, 0.0, 1.0));
+
+        let color = parse_hex_color("00ff0000").unwrap();
+        assert_eq!((color.r, color.g, color.b, color.a), (0.0, 1.0, 0.0, 0.0));
+
+        assert!(parse_hex_color("#fff").is_err());
+        assert!(parse_hex_color("#gggggg").is_err());
+    }
+
+    #[test]
+    fn test_apply_params() {
+        let code = r#"// A plate
+width = 10
+height = [
+  1,
+  2
+]
+
+depth = 3
+widthTimesTwo = width * 2
+"#;
+        let params = [
+            ("height".to_string(), "[5, 6]".to_string()),
+            ("width".to_string(), "20".to_string()),
+        ];
+        assert_eq!(
+            apply_params(code, &params).unwrap(),
+            r#"// A plate
+width = 20
+height = [5, 6]
+
+depth = 3
+widthTimesTwo = width * 2
+"#
+        );
+
+        assert_eq!(apply_params(code, &[]).unwrap(), code);
+
+        // Only whole names declared at the top level.
+        let err = apply_params(code, &[("widthTimes".to_string(), "1".to_string())]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`widthTimes` is not a variable declared at the top level of the file"
+        );
+        assert!(apply_params(code, &[("depth".to_string(), "(".to_string())]).is_err());
+
+        assert_eq!(
+            parse_param(" width = 20").unwrap(),
+            ("width".to_string(), "20".to_string())
+        );
+        assert!(parse_param("width").is_err());
+        assert!(parse_param("=20").is_err());
+    }
+
+    #[test]
+    fn test_format_crlf() {
+        let crlf = include_str!("../tests/crlf.kcl");
+        assert!(crlf.contains("\r\n"), "the fixture lost its CRLF line endings");
+        let lf = crlf.replace("\r\n", "\n");
+
+        for insert_final_newline in [true, false] {
+            let options = kcl_lib::FormatOptions {
+                tab_size: 2,
+                use_tabs: false,
+                insert_final_newline,
+            };
+            let format = |code: &str| {
+                kcl_lib::Program::parse_no_errs(&normalize_line_endings(code))
+                    .unwrap()
+                    .recast_with_options(&options)
+            };
+
+            // The line endings of the input don't change the output.
+            let formatted = format(crlf);
+            assert_eq!(formatted, format(&lf));
+            assert!(!formatted.contains('\r'));
+            assert_eq!(formatted.ends_with('\n'), insert_final_newline);
+
+            let formatted = with_line_endings(formatted, true);
+            assert_eq!(formatted.matches('\n').count(), formatted.matches("\r\n").count());
+            assert_eq!(formatted.ends_with("\r\n"), insert_final_newline);
+            verify_format_is_stable(&formatted, &options).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_editorconfig() {
+        let (settings, is_root) = parse_editorconfig(
+            r#"root = true
+
+[*]
+indent_style = space
+indent_size = 4
+insert_final_newline = true
+
+[*.{kcl,toml}]
+indent_style = tab
+
+[*.md]
+indent_size = 8
+"#,
+        );
+        assert!(is_root);
+        assert_eq!(
+            settings,
+            EditorConfigSettings {
+                use_tabs: Some(true),
+                indent_size: Some(4),
+                insert_final_newline: Some(true),
+                crlf: None,
+            }
+        );
+
+        let (settings, is_root) = parse_editorconfig("[*.rs]\nindent_size = 4\n");
+        assert!(!is_root);
+        assert_eq!(settings, EditorConfigSettings::default());
+    }
+
+    #[test]
+    fn test_use_tabs_overrides_editorconfig() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".editorconfig"), "[*.kcl]\nindent_style = tab\n").unwrap();
+        let path = dir.path().join("main.kcl");
+
+        for (args, want) in [
+            (&[][..], true),
+            (&["--use-tabs"][..], true),
+            (&["--use-tabs=false"][..], false),
+        ] {
+            let cmd = CmdKclFormat::parse_from(["fmt"].iter().chain(args).chain(&[path.to_str().unwrap()]));
+            assert_eq!(cmd.input, vec![path.clone()], "{:?}", args);
+            let (_, _, options) = cmd.format_code("x = 1\n", &path, true).unwrap();
+            assert_eq!(options.use_tabs, want, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_get_kcl_imports() {
+        let code = r#"import thing from "thing.kcl"
+import a, b from "parts/other.kcl"
+// import c from "commented.kcl"
+/*
+import d from "also_commented.kcl"
+*/
+x = 1
+"#;
+        assert_eq!(
+            get_kcl_imports(code).unwrap(),
+            vec!["thing.kcl".to_string(), "parts/other.kcl".to_string()]
+        );
+        assert!(get_kcl_imports("import from").is_err());
+    }
+
+    #[test]
+    fn test_lint_settings_is_enabled() {
+        let settings = LintSettings::default();
+        assert!(settings.is_enabled("Z0001"));
+
+        let settings = LintSettings {
+            select: vec!["Z0001".to_string(), "z0002".to_string()],
+            ignore: vec!["Z0002".to_string()],
+        };
+        assert!(settings.is_enabled("Z0001"));
+        assert!(!settings.is_enabled("Z0002"));
+        assert!(!settings.is_enabled("Z0003"));
+    }
+
+    #[test]
+    fn test_apply_gltf_options() {
+        let gltf = get_output_format(
+            &kt::FileExportFormat::Gltf,
+            kittycad_modeling_cmds::units::UnitLength::Millimeters,
+        );
+
+        // Nothing given keeps the defaults for the format.
+        match apply_gltf_options(gltf.clone(), &None, &None).unwrap() {
+            OutputFormat::Gltf(options) => {
+                assert_eq!(options.storage, kcmc::format::gltf::export::Storage::Embedded);
+                assert_eq!(options.presentation, kcmc::format::gltf::export::Presentation::Pretty);
+            }
+            result => panic!("expected gltf, got {:?}", result),
+        }
+
+        match apply_gltf_options(
+            gltf,
+            &Some(kt::GltfPresentation::Compact),
+            &Some(kt::GltfStorage::Standard),
+        )
+        .unwrap()
+        {
+            OutputFormat::Gltf(options) => {
+                assert_eq!(options.storage, kcmc::format::gltf::export::Storage::Standard);
+                assert_eq!(options.presentation, kcmc::format::gltf::export::Presentation::Compact);
+            }
+            result => panic!("expected gltf, got {:?}", result),
+        }
+
+        let step = get_output_format(
+            &kt::FileExportFormat::Step,
+            kittycad_modeling_cmds::units::UnitLength::Millimeters,
+        );
+        assert!(apply_gltf_options(step, &Some(kt::GltfPresentation::Pretty), &None).is_err());
+    }
+
+    #[test]
+    fn test_input_url() {
+        let url = std::path::Path::new("https://example.com/gear.kcl");
+        assert_eq!(input_url(url), Some("https://example.com/gear.kcl"));
+        assert_eq!(
+            input_url(std::path::Path::new("http://localhost:8080/gear.kcl")),
+            Some("http://localhost:8080/gear.kcl")
+        );
+        assert_eq!(input_url(std::path::Path::new("gear.kcl")), None);
+        assert_eq!(input_url(std::path::Path::new("-")), None);
+        assert_eq!(input_url(std::path::Path::new("s3://bucket/gear.kcl")), None);
+    }
+
+    #[test]
+    fn test_kcl_meta() {
+        let code = r#"// Bracket
+// A bracket for a shelf.
+// It holds up to 10kg.
+
+// The size of the bracket
+width = 20
+height = 3.5 // mm, on the wall
+label = "shelf"
+export const fillet = true
+hole = -1
+holeDiameter = width / 4
+// Not a parameter
+fn f = (x) => {
+  inner = 2
+  return x
+}
+"#;
+        let items = kcl_meta(code);
+        let item = |kind, name: &str, value: &str, description: &str, line| KclMetaItem {
+            kind,
+            name: name.to_string(),
+            value: value.to_string(),
+            description: description.to_string(),
+            line,
+        };
+        assert_eq!(
+            items,
+            vec![
+                item(KclMetaKind::Metadata, "title", "Bracket", "", 0),
+                item(
+                    KclMetaKind::Metadata,
+                    "description",
+                    "A bracket for a shelf. It holds up to 10kg.",
+                    "",
+                    0
+                ),
+                item(KclMetaKind::Parameter, "width", "20", "The size of the bracket", 6),
+                item(KclMetaKind::Parameter, "height", "3.5", "mm, on the wall", 7),
+                item(KclMetaKind::Parameter, "label", "\"shelf\"", "", 8),
+                item(KclMetaKind::Parameter, "fillet", "true", "", 9),
+                item(KclMetaKind::Parameter, "hole", "-1", "", 10),
+            ]
+        );
+
+        // A comment on the first statement is not a title.
+        let items = kcl_meta("// The width\nwidth = 2\n");
+        assert_eq!(items, vec![item(KclMetaKind::Parameter, "width", "2", "The width", 2)]);
+
+        assert_eq!(kcl_meta("part = startSketchOn('XY')\n"), vec![]);
+    }
+
+    #[test]
+    fn test_parse_modeling_cmds() {
+        let path = std::path::Path::new("cmds.jsonl");
+        let cmds = parse_modeling_cmds(
+            "{\"type\": \"scene_clear_all\"}\n\n{\"type\": \"scene_clear_all\"}\n",
+            path,
+        )
+        .unwrap();
+        assert_eq!(cmds.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![1, 3]);
+        assert!(matches!(cmds[0].1, kcmc::ModelingCmd::SceneClearAll(_)));
+
+        let err =
+            parse_modeling_cmds("{\"type\": \"scene_clear_all\"}\n{\"type\": \"not_a_command\"}\n", path).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("line 2 of `cmds.jsonl` is not a valid modeling command: "),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_export_stamp() {
+        let cmd = CmdKclExport::parse_from(["export", "--output-format=step", "parts/gear.kcl", "out"]);
+        assert_eq!(
+            cmd.stamp_path().unwrap(),
+            std::path::PathBuf::from("out/.gear.step.zoo-export-hash")
+        );
+        let cmd = CmdKclExport::parse_from(["export", "--output-format=gltf", "--zip=out/gear.zip", "gear.kcl"]);
+        assert_eq!(
+            cmd.stamp_path().unwrap(),
+            std::path::PathBuf::from("out/.gear.zip.zoo-export-hash")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gear.step");
+        let stamp = ExportStamp {
+            hash: "abc".to_string(),
+            files: vec![file.clone()],
+        };
+        // The files have to still be there.
+        assert!(!stamp.is_current("abc"));
+        std::fs::write(&file, "ISO-10303-21;").unwrap();
+        assert!(stamp.is_current("abc"));
+        assert!(!stamp.is_current("def"));
+    }
+
+    #[test]
+    fn test_kcl_files_in_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("parts/screws")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        for file in [
+            "main.kcl",
+            "notes.txt",
+            "parts/gear.kcl",
+            "parts/screws/m3.kcl",
+            ".git/old.kcl",
+            "target/built.kcl",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+        let files = |max_depth| {
+            kcl_files_in_dir(root, max_depth)
+                .unwrap()
+                .iter()
+                .map(|file| file.strip_prefix(root).unwrap().to_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(files(None), vec!["main.kcl", "parts/gear.kcl", "parts/screws/m3.kcl"]);
+        assert_eq!(files(Some(2)), vec!["main.kcl", "parts/gear.kcl"]);
+        assert_eq!(files(Some(1)), vec!["main.kcl"]);
+
+        // A symlink back up the tree is not followed round and round.
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root, root.join("parts/screws/up")).unwrap();
+            assert_eq!(files(None), vec!["main.kcl", "parts/gear.kcl", "parts/screws/m3.kcl"]);
+        }
+
+        std::fs::write(root.join("parts/.zooignore"), "screws/\n").unwrap();
+        assert_eq!(files(None), vec!["main.kcl", "parts/gear.kcl"]);
+    }
+
+    #[test]
+    fn test_snapshot_setup_cmds() {
+        let cmd = CmdKclSnapshot::parse_from(["snapshot", "gear.kcl", "gear.png"]);
+        assert!(cmd.setup_cmds().unwrap().is_empty());
+        for flag in ["--edges", "--wireframe"] {
+            let cmd = CmdKclSnapshot::parse_from(["snapshot", flag, "gear.kcl", "gear.png"]);
+            assert!(matches!(
+                cmd.setup_cmds().unwrap()[..],
+                [kcmc::ModelingCmd::EdgeLinesVisible(kcmc::EdgeLinesVisible {
+                    hidden: false,
+                    ..
+                })]
+            ));
+        }
+
+        assert!(
+            CmdKclSnapshot::try_parse_from(["snapshot", "--edges", "--wireframe", "gear.kcl", "gear.png"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_camera() {
+        let look_at = |args: &[&str]| {
+            let cmd = CmdKclSnapshot::parse_from(["snapshot"].iter().chain(args).chain(&["gear.kcl", "gear.png"]));
+            match &cmd.setup_cmds().unwrap()[..] {
+                [kcmc::ModelingCmd::DefaultCameraLookAt(look_at)] => {
+                    let point = |p: &kcmc::shared::Point3d<f64>| [p.x, p.y, p.z];
+                    (point(&look_at.vantage), point(&look_at.center), point(&look_at.up))
+                }
+                cmds => panic!("expected a camera command, got {:?}", cmds),
+            }
+        };
+
+        assert_eq!(
+            look_at(&["--camera", "front"]),
+            ([0.0, -100.0, 0.0], [0.0; 3], [0.0, 0.0, 1.0])
+        );
+        assert_eq!(
+            look_at(&["--camera", "top"]),
+            ([0.0, 0.0, 100.0], [0.0; 3], [0.0, 1.0, 0.0])
+        );
+        assert_eq!(
+            look_at(&["--camera-vantage", "10,-20,30", "--camera-center", "0,0,5"]),
+            ([10.0, -20.0, 30.0], [0.0, 0.0, 5.0], [0.0, 0.0, 1.0])
+        );
+        // Looking straight down, +Z can't be up.
+        assert_eq!(
+            look_at(&["--camera-vantage", "0,0,50"]),
+            ([0.0, 0.0, 50.0], [0.0; 3], [0.0, 1.0, 0.0])
+        );
+
+        let cmd = CmdKclSnapshot::parse_from(["snapshot", "--camera-vantage", "1,2,3", "gear.kcl", "gear.png"]);
+        assert_eq!(cmd.zoom_padding(), None);
+        let cmd = CmdKclSnapshot::parse_from([
+            "snapshot",
+            "--camera-vantage",
+            "1,2,3",
+            "--camera-center",
+            "1,2,3",
+            "gear.kcl",
+            "gear.png",
+        ]);
+        assert!(cmd.setup_cmds().is_err());
+        for args in [
+            &["--camera", "iso", "--camera-vantage", "1,2,3"][..],
+            &["--camera-center", "1,2,3"],
+            &["--camera", "sideways"],
+        ] {
+            let args = ["snapshot"].iter().chain(args).chain(&["gear.kcl", "gear.png"]);
+            assert!(CmdKclSnapshot::try_parse_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_video_resolution() {
+        assert_eq!(video_resolution(None, None).unwrap(), None);
+        assert_eq!(video_resolution(Some(640), Some(480)).unwrap(), Some((640, 480)));
+        assert_eq!(video_resolution(Some(320), None).unwrap(), Some((320, 180)));
+        assert_eq!(video_resolution(None, Some(1080)).unwrap(), Some((1920, 1080)));
+
+        assert!(video_resolution(Some(0), Some(480)).is_err());
+        assert!(video_resolution(Some(5000), None).is_err());
+        // The width this would need is too big.
+        assert!(video_resolution(None, Some(4000)).is_err());
+    }
+
+    #[test]
+    fn test_encode_gif() {
+        let frames = vec![
+            image::RgbaImage::from_pixel(4, 3, image::Rgba([255, 0, 0, 255])),
+            image::RgbaImage::from_pixel(4, 3, image::Rgba([0, 0, 255, 255])),
+        ];
+        let gif = encode_gif(frames, 10).unwrap();
+
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(gif)).unwrap();
+        let frames = image::AnimationDecoder::into_frames(decoder).collect_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            std::time::Duration::from(frames[0].delay()),
+            std::time::Duration::from_millis(100)
+        );
+        assert_eq!(frames[1].buffer().dimensions(), (4, 3));
+    }
+
+    #[test]
+    fn test_stl_bounds() {
+        let mut stl = vec![0u8; 80];
+        stl.extend_from_slice(&2u32.to_le_bytes());
+        for triangle in [
+            [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 3.0, 0.0]],
+            [[-1.0, 0.0, 0.5], [0.0, 1.0, 4.0], [1.0, 1.0, 1.0]],
+        ] {
+            stl.extend_from_slice(&[0; 12]);
+            for value in triangle.iter().flatten() {
+                stl.extend_from_slice(&f32::to_le_bytes(*value));
+            }
+            stl.extend_from_slice(&[0; 2]);
+        }
+
+        let (min, max) = stl_bounds(&stl).unwrap().unwrap();
+        assert_eq!((min.x, min.y, min.z), (-1.0, 0.0, 0.0));
+        assert_eq!((max.x, max.y, max.z), (2.0, 3.0, 4.0));
+
+        // An empty mesh has no bounds, and a short one is an error.
+        assert!(stl_bounds(&stl[..84]).is_err());
+        let mut empty = vec![0u8; 80];
+        empty.extend_from_slice(&0u32.to_le_bytes());
+        assert!(stl_bounds(&empty).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_apply_units_out() {
+        let stl = get_output_format(
+            &kt::FileExportFormat::Stl,
+            kittycad_modeling_cmds::units::UnitLength::Millimeters,
+        );
+
+        // Nothing given keeps the source unit.
+        match apply_units_out(stl.clone(), &None).unwrap() {
+            OutputFormat::Stl(options) => {
+                assert_eq!(options.units, kittycad_modeling_cmds::units::UnitLength::Millimeters);
+            }
+            result => panic!("expected stl, got {:?}", result),
+        }
+
+        match apply_units_out(stl, &Some(kt::UnitLength::M)).unwrap() {
+            OutputFormat::Stl(options) => {
+                assert_eq!(options.units, kittycad_modeling_cmds::units::UnitLength::Meters);
+            }
+            result => panic!("expected stl, got {:?}", result),
+        }
+
+        let step = get_output_format(
+            &kt::FileExportFormat::Step,
+            kittycad_modeling_cmds::units::UnitLength::Millimeters,
+        );
+        assert!(apply_units_out(step, &Some(kt::UnitLength::M)).is_err());
+    }
+
+    #[test]
+    fn test_apply_format_options() {
+        let format = get_output_format(
+            &kt::FileExportFormat::Stl,
+            kittycad_modeling_cmds::units::UnitLength::Millimeters,
+        );
+
+        let options = parse_format_options(r#"{"storage": "binary"}"#).unwrap();
+        let result = apply_format_options(format.clone(), &options).unwrap();
+        match result {
+            OutputFormat::Stl(options) => {
+                assert_eq!(options.storage, kcmc::format::stl::export::Storage::Binary);
+                assert_eq!(options.units, kittycad_modeling_cmds::units::UnitLength::Millimeters);
+            }
+            _ => panic!("expected stl, got {:?}", result),
+        }
+
+        let options = parse_format_options(r#"{"foo": true}"#).unwrap();
+        let err = apply_format_options(format.clone(), &options).unwrap_err();
+        assert!(err.to_string().starts_with("unknown format option `foo`"), "{err}");
+
+        // Every option in the schema is allowed, not just the ones set by default.
+        let names = format_option_names("stl").unwrap();
+        for name in ["coords", "selection", "storage", "units"] {
+            assert!(names.contains(name), "{name} is missing from {names:?}");
+        }
+        assert!(!names.contains("type"));
+        assert!(format_option_names("nope").is_err());
+
+        let options = parse_format_options(r#"{"storage": "nope"}"#).unwrap();
+        assert!(apply_format_options(format.clone(), &options).is_err());
+
+        let options = parse_format_options(r#"{"type": "step"}"#).unwrap();
+        assert!(apply_format_options(format, &options).is_err());
+
+        assert!(parse_format_options("[]").is_err());
+    }
+
+    #[test]
+    fn test_sort_imports() {
+        let code = include_str!("../tests/unsorted-imports.kcl");
+        let options = kcl_lib::FormatOptions {
+            tab_size: 2,
+            use_tabs: false,
+            insert_final_newline: true,
+        };
+
+        let program = kcl_lib::Program::parse_no_errs(code).unwrap();
+        let sorted = sort_imports(program).unwrap().recast_with_options(&options);
+        assert_eq!(
+            sorted,
+            r#"// Parts of the assembly
+import bolt from "bolt.kcl"
+import axle, frame from "frame.kcl"
+import wheel from "wheel.kcl"
+
+// Made elsewhere
+import bell from "vendor/bell.kcl"
+import seat from "vendor/seat.kcl"
+
+width = 10
+"#
+        );
+        verify_format_is_stable(&sorted, &options).unwrap();
+
+        // Without sorting, the imports stay where they are.
+        let unsorted = kcl_lib::Program::parse_no_errs(code)
+            .unwrap()
+            .recast_with_options(&options);
+        assert!(
+            unsorted.starts_with("// Parts of the assembly\nimport wheel from"),
+            "{unsorted}"
+        );
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let code = std::fs::read_to_string("tests/gear.kcl").unwrap();
+        let options = kcl_lib::FormatOptions {
+            tab_size: 2,
+            use_tabs: false,
+            insert_final_newline: true,
+        };
+
+        let formatted = kcl_lib::Program::parse_no_errs(&code)
+            .unwrap()
+            .recast_with_options(&options);
+        verify_format_is_stable(&formatted, &options).unwrap();
+
+        // Code that is not already formatted is not stable.
+        let err = verify_format_is_stable("x   =  1\n", &options).unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{err}");
+    }
+
+    #[test]
+    fn test_temp_file_is_removed_unless_kept() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let removed = dir.path().join("removed.png");
+        std::fs::write(&removed, b"png").unwrap();
+        drop(TempFile {
+            path: removed.clone(),
+            keep: false,
+        });
+        assert!(!removed.exists());
+
+        let kept = dir.path().join("kept.png");
+        std::fs::write(&kept, b"png").unwrap();
+        drop(TempFile {
+            path: kept.clone(),
+            keep: true,
+        });
+        assert!(kept.exists());
+
+        // A file that was never written is fine too.
+        drop(TempFile {
+            path: dir.path().join("never-written.png"),
+            keep: false,
+        });
+    }
+
+    #[test]
+    fn test_orbit_camera() {
+        let close = |a: [f64; 3], b: [f64; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-9);
+
+        let initial = OrbitCamera::new([10.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        assert!(close(initial.position(), [10.0, 0.0, 0.0]));
+
+        // A quarter turn around z.
+        let mut camera = initial.clone();
+        camera.orbit(std::f64::consts::FRAC_PI_2, 0.0);
+        assert!(close(camera.position(), [0.0, 10.0, 0.0]));
+
+        // Zooming in halves the distance.
+        camera.zoom(2.0);
+        assert!(close(camera.position(), [0.0, 5.0, 0.0]));
+
+        // Panning right, when looking along -y, moves towards -x.
+        camera.pan(0.1, 0.0);
+        assert!(close(camera.center, [-0.5, 0.0, 0.0]));
+
+        // The camera never goes over the top.
+        camera.orbit(0.0, 10.0);
+        assert_eq!(camera.elevation, OrbitCamera::MAX_ELEVATION);
+
+        let key = |code| crossterm::event::KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+        assert_eq!(
+            camera.handle_key(key(crossterm::event::KeyCode::Char('r')), &initial),
+            ViewAction::Redraw
+        );
+        assert_eq!(camera, initial);
+        assert_eq!(
+            camera.handle_key(key(crossterm::event::KeyCode::Char('z')), &initial),
+            ViewAction::Nothing
+        );
+        assert_eq!(
+            camera.handle_key(key(crossterm::event::KeyCode::Esc), &initial),
+            ViewAction::Quit
+        );
+    }
+
+    #[test]
+    fn test_bench_summary() {
+        let runs: Vec<std::time::Duration> = [
+            30, 10, 20, 40, 50, 60, 70, 80, 90, 100, 110, 120, 130, 140, 150, 160, 170, 180, 190, 200,
+        ]
+        .iter()
+        .map(|ms| std::time::Duration::from_millis(*ms))
+        .collect();
+        let summary = BenchSummary::new(&runs);
+        assert_eq!(summary.iterations, 20);
+        assert_eq!(summary.min_ms, 10.0);
+        assert_eq!(summary.median_ms, 105.0);
+        assert_eq!(summary.p95_ms, 190.0);
+        assert_eq!(summary.max_ms, 200.0);
+        assert_eq!(summary.mean_ms, 105.0);
+        assert_eq!(summary.runs_per_minute, 571.43);
+        // The runs are kept in the order they were made.
+        assert_eq!(&summary.runs_ms[..3], &[30.0, 10.0, 20.0]);
+
+        let summary = BenchSummary::new(&[std::time::Duration::from_millis(5)]);
+        assert_eq!(summary.median_ms, 5.0);
+        assert_eq!(summary.p95_ms, 5.0);
+    }
+
+    #[test]
+    fn test_parse_snapshot_list() {
+        let list = "parts/gear.kcl\n\n# the brackets\n  parts/bracket.kcl  \nassembly/\n";
+        assert_eq!(
+            parse_snapshot_list(list).unwrap(),
+            vec![
+                std::path::PathBuf::from("parts/gear.kcl"),
+                std::path::PathBuf::from("parts/bracket.kcl"),
+                std::path::PathBuf::from("assembly/"),
+            ]
+        );
+
+        assert!(parse_snapshot_list("# nothing\n\n").is_err());
+        assert!(parse_snapshot_list("a.kcl\n-\n").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_list_outputs() {
+        let inputs = parse_snapshot_list("parts/gear.kcl\nassembly/\n").unwrap();
+        assert_eq!(
+            snapshot_list_outputs(&inputs, std::path::Path::new("out"), "png").unwrap(),
+            vec![
+                std::path::PathBuf::from("out/gear.png"),
+                std::path::PathBuf::from("out/assembly.png"),
+            ]
+        );
+
+        let inputs = parse_snapshot_list("a/gear.kcl\nb/gear.kcl\n").unwrap();
+        assert_eq!(
+            snapshot_list_outputs(&inputs, std::path::Path::new("out"), "png")
+                .unwrap_err()
+                .to_string(),
+            "`a/gear.kcl` and `b/gear.kcl` would both be written to `gear.png`"
+        );
+    }
+
+    #[test]
+    fn test_lint_finding_to_github_annotation() {
+        let finding = LintFinding {
+            path: "parts/my,file.kcl".to_string(),
+            line: 3,
+            column: 1,
+            end_line: 3,
+            end_column: 12,
+            code: "Z0001".to_string(),
+            title: "Identifiers must be lowerCamelCase (found `my_var`: 100%)".to_string(),
+            description: "".to_string(),
+        };
+        assert_eq!(
+            finding.to_github_annotation(),
+            "::error file=parts/my%2Cfile.kcl,line=3,col=1,endLine=3,endColumn=12,title=Z0001::Identifiers must be lowerCamelCase (found `my_var`: 100%25)"
+        );
+    }
+
+    #[test]
+    fn test_export_output_formats() {
+        let formats = |args: &[&str]| {
+            let cmd = CmdKclExport::parse_from(["export"].iter().chain(args).chain(&["gear.kcl", "out"]));
+            cmd.output_formats(kcl_lib::ExecutorSettings::default().units.into())
+        };
+
+        let got = formats(&["-t", "step", "-t", "gltf,step", "--gltf-storage", "binary"]).unwrap();
+        assert_eq!(
+            got.iter().map(|(format, _)| format.clone()).collect::<Vec<_>>(),
+            vec![
+                kittycad::types::FileExportFormat::Step,
+                kittycad::types::FileExportFormat::Gltf
+            ]
+        );
+        assert!(matches!(
+            &got[1].1,
+            OutputFormat::Gltf(options) if matches!(options.storage, kcmc::format::gltf::export::Storage::Binary)
+        ));
+
+        // The flags for some formats are fine with more than one format, as long as
+        // one of them takes them.
+        assert!(formats(&["-t", "step", "-t", "stl", "--units-out", "m"]).is_ok());
+        assert!(formats(&["-t", "step", "-t", "gltf", "--units-out", "m"]).is_err());
+        assert!(formats(&["-t", "stl", "--units-out", "m", "--gltf-storage", "binary"]).is_err());
+        assert!(formats(&["-t", "step", "-t", "stl", "--format-options", "{}"]).is_err());
+    }
+
+    #[test]
+    fn test_lint_summary() {
+        let finding = |code: &str| LintFinding {
+            path: "gear.kcl".to_string(),
+            line: 1,
+            column: 1,
+            end_line: 1,
+            end_column: 2,
+            code: code.to_string(),
+            title: "".to_string(),
+            description: "".to_string(),
+        };
+        let findings = ["Z0014", "Z0001", "Z0001"].map(finding);
+        assert_eq!(lint_summary(&findings).as_deref(), Some("3 findings: Z0001=2, Z0014=1"));
+        assert_eq!(lint_summary(&findings[..1]).as_deref(), Some("1 finding: Z0014=1"));
+        assert_eq!(lint_summary(&[]), None);
+    }
 }
@@ -44,6 +44,12 @@ pub struct CmdKclEdit {
     /// If you don't pass this, the entire file will be edited.
     #[clap(name = "source_range", long, short = 'r')]
     pub source_range: Option<String>,
+
+    /// A standing instruction to send ahead of your prompt, like "always use metric,
+    /// prefer parametric dimensions". This defaults to the `system_prompt` config value,
+    /// pass an empty string to send none.
+    #[clap(long, visible_alias = "prompt-prefix")]
+    pub system: Option<String>,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -59,6 +65,7 @@ impl crate::cmd::Command for CmdKclEdit {
         if prompt.is_empty() {
             anyhow::bail!("prompt cannot be empty");
         }
+        let prompt = ctx.ml_prompt(&self.system, &prompt);
 
         let source_ranges = if let Some(source_range) = &self.source_range {
             vec![kittycad::types::SourceRangePrompt {
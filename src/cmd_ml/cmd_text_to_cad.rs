@@ -90,6 +90,18 @@ impl TryFrom<FileExportFormat> for kittycad::types::FileExportFormat {
 /// Run a Text-to-CAD prompt and export it as any other supported CAD file format.
 ///
 ///     $ zoo ml text-to-cad export --output-format=obj A 2x4 lego brick
+///
+///     # open the result as soon as it is written
+///     $ zoo ml text-to-cad export --output-format=stl --open A 2x4 lego brick
+///
+///     # send a standing instruction ahead of the prompt
+///     $ zoo ml text-to-cad export --output-format=step --system "always use metric" A 2x4 lego brick
+///
+///     # get the same model in more than one format
+///     $ zoo ml text-to-cad export --output-format=obj,step A 2x4 lego brick
+///
+/// With more than one format, the model is only generated once, in the first format. The
+/// other formats are exported from the KCL code of that model.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
 pub struct CmdTextToCadExport {
@@ -103,12 +115,32 @@ pub struct CmdTextToCadExport {
     pub output_dir: Option<std::path::PathBuf>,
 
     /// A valid output file format.
-    #[clap(short = 't', long = "output-format", value_enum)]
-    output_format: FileExportFormat,
+    /// This can be given more than once, or as a list like `obj,step`, to get several
+    /// formats of the same generated model.
+    #[clap(
+        short = 't',
+        long = "output-format",
+        value_enum,
+        value_delimiter = ',',
+        required = true
+    )]
+    output_format: Vec<FileExportFormat>,
 
     /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+
+    /// A standing instruction to send ahead of your prompt, like "always use metric,
+    /// prefer parametric dimensions". This defaults to the `system_prompt` config value,
+    /// pass an empty string to send none.
+    #[clap(long, visible_alias = "prompt-prefix")]
+    pub system: Option<String>,
+
+    /// Open the exported file once it is written, with the program your system uses
+    /// for that file type. If there is no such program, or for kcl, a render is shown
+    /// in the terminal instead. If neither works, the path to the file is printed.
+    #[clap(long)]
+    pub open: bool,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -134,51 +166,107 @@ impl crate::cmd::Command for CmdTextToCadExport {
             anyhow::bail!("prompt cannot be empty");
         }
 
+        let mut formats: Vec<FileExportFormat> = Vec::with_capacity(self.output_format.len());
+        for format in &self.output_format {
+            if !formats.contains(format) {
+                formats.push(format.clone());
+            }
+        }
+        let wants_kcl = formats.contains(&FileExportFormat::Kcl);
+        let mut other_formats = formats
+            .iter()
+            .filter(|format| **format != FileExportFormat::Kcl)
+            .map(|format| kittycad::types::FileExportFormat::try_from(format.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        // The model is generated in one format, and the rest are exported from its code.
+        let wants_generated = !other_formats.is_empty();
+        let generated_format = if wants_generated {
+            other_formats.remove(0)
+        } else {
+            kittycad::types::FileExportFormat::Gltf
+        };
+
         let mut model = ctx
             .get_model_for_prompt(
                 "",
-                &prompt,
-                self.output_format == FileExportFormat::Kcl,
-                if self.output_format == FileExportFormat::Kcl {
-                    kittycad::types::FileExportFormat::Gltf
-                } else {
-                    self.output_format.clone().try_into()?
-                },
+                &ctx.ml_prompt(&self.system, &prompt),
+                wants_kcl || !other_formats.is_empty(),
+                generated_format,
             )
             .await?;
 
-        if self.output_format != FileExportFormat::Kcl {
-            if let Some(outputs) = model.outputs {
+        let mut generated_path = None;
+        if wants_generated {
+            if let Some(outputs) = &model.outputs {
                 // Write the contents of the files to the output directory.
                 for (filename, data) in outputs.iter() {
-                    let path = output_dir.clone().join(filename);
-                    std::fs::write(&path, data)?;
-                    writeln!(
-                        ctx.io.out,
-                        "wrote file `{}` to {}",
-                        filename,
-                        path.to_str().unwrap_or("")
-                    )?;
+                    let path = write_output_file(ctx, &output_dir, filename, data)?;
+                    generated_path.get_or_insert(path);
                 }
             } else {
                 anyhow::bail!(
-                "no output was generated! (this is probably a bug in the API) you should report it to support@zoo.dev"
-            );
+                    "no output was generated! (this is probably a bug in the API) you should report it to support@zoo.dev"
+                );
+            }
+        }
+
+        let mut kcl_path = None;
+        if wants_kcl || !other_formats.is_empty() {
+            let Some(code) = &model.code else {
+                anyhow::bail!(
+                    "no code was generated! (this is probably a bug in the API) you should report it to support@zoo.dev"
+                );
+            };
+            // The files are named after the prompt, like the kcl file.
+            let stem = prompt.replace(' ', "_").to_lowercase();
+            if wants_kcl {
+                let filename = stem.clone() + ".kcl";
+                kcl_path = Some(write_output_file(ctx, &output_dir, &filename, code.as_bytes())?);
+            }
+
+            if !other_formats.is_empty() {
+                // Export in the units the code is run with, so the sizes match the model.
+                let settings = kcl_lib::ExecutorSettings::default();
+                let exports = other_formats
+                    .iter()
+                    .map(|format| {
+                        ModelingCmd::Export(kcmc::Export {
+                            entity_ids: vec![],
+                            format: crate::cmd_kcl::get_output_format(format, settings.units.into()),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let (responses, _) = ctx
+                    .send_kcl_modeling_cmds_with_setup("", code, &[], &exports, settings, None)
+                    .await?;
+                for resp in responses {
+                    let OkWebSocketResponseData::Export { files } = &resp else {
+                        return Err(crate::engine_response::unexpected("an export", &resp));
+                    };
+                    for file in files {
+                        let filename = match std::path::Path::new(&file.name).extension() {
+                            Some(ext) => format!("{}.{}", stem, ext.to_string_lossy()),
+                            None => file.name.clone(),
+                        };
+                        write_output_file(ctx, &output_dir, &filename, &file.contents.0)?;
+                    }
+                }
+            }
+        }
+
+        if self.open {
+            // Open the file of the first format asked for.
+            let first_is_kcl = formats.first() == Some(&FileExportFormat::Kcl);
+            let open_path = if first_is_kcl { &kcl_path } else { &generated_path };
+            if let Some(path) = open_path {
+                let gltf_bytes = model.outputs.as_ref().and_then(|outputs| {
+                    outputs
+                        .iter()
+                        .find(|(key, _)| key.ends_with(".gltf"))
+                        .map(|(_, value)| value.0.clone())
+                });
+                open_export(ctx, path, !first_is_kcl, gltf_bytes).await?;
             }
-        } else if let Some(code) = &model.code {
-            let filename = prompt.replace(" ", "_").to_lowercase() + ".kcl";
-            let path = output_dir.clone().join(&filename);
-            std::fs::write(&path, code)?;
-            writeln!(
-                ctx.io.out,
-                "wrote file `{}` to {}",
-                filename,
-                path.to_str().unwrap_or("")
-            )?;
-        } else {
-            anyhow::bail!(
-                "no code was generated! (this is probably a bug in the API) you should report it to support@zoo.dev"
-            );
         }
 
         // Reset the outputs field of the model.
@@ -216,6 +304,12 @@ pub struct CmdTextToCadSnapshot {
     /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+
+    /// A standing instruction to send ahead of your prompt, like "always use metric,
+    /// prefer parametric dimensions". This defaults to the `system_prompt` config value,
+    /// pass an empty string to send none.
+    #[clap(long, visible_alias = "prompt-prefix")]
+    pub system: Option<String>,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -242,7 +336,12 @@ impl crate::cmd::Command for CmdTextToCadSnapshot {
         }
 
         let model = ctx
-            .get_model_for_prompt("", &prompt, false, kittycad::types::FileExportFormat::Gltf)
+            .get_model_for_prompt(
+                "",
+                &ctx.ml_prompt(&self.system, &prompt),
+                false,
+                kittycad::types::FileExportFormat::Gltf,
+            )
             .await?;
 
         // Get the gltf bytes.
@@ -296,6 +395,12 @@ pub struct CmdTextToCadView {
     /// Command output format.
     #[clap(long, short, value_enum)]
     pub format: Option<crate::types::FormatOutput>,
+
+    /// A standing instruction to send ahead of your prompt, like "always use metric,
+    /// prefer parametric dimensions". This defaults to the `system_prompt` config value,
+    /// pass an empty string to send none.
+    #[clap(long, visible_alias = "prompt-prefix")]
+    pub system: Option<String>,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -308,7 +413,12 @@ impl crate::cmd::Command for CmdTextToCadView {
         }
 
         let model = ctx
-            .get_model_for_prompt("", &prompt, false, kittycad::types::FileExportFormat::Gltf)
+            .get_model_for_prompt(
+                "",
+                &ctx.ml_prompt(&self.system, &prompt),
+                false,
+                kittycad::types::FileExportFormat::Gltf,
+            )
             .await?;
 
         // Get the gltf bytes.
@@ -333,23 +443,7 @@ impl crate::cmd::Command for CmdTextToCadView {
         // Save the snapshot locally.
         std::fs::write(&tmp_file, image_bytes)?;
 
-        let (width, height) = (ctx.io.tty_size)()?;
-
-        let offset_x = 0;
-        let offset_y = 0;
-        // Now we setup the terminal viewer.
-        let image_conf = viuer::Config {
-            // set offset
-            x: offset_x,
-            y: offset_y,
-            // set dimensions
-            width: Some(width as u32 - (offset_x * 2) as u32),
-            // Make sure to leave the last row at the bottom for the prompt.
-            // Which is what the +1 is.
-            height: Some(height as u32 - ((offset_y * 2) + 1) as u32),
-            ..Default::default()
-        };
-        viuer::print_from_file(&tmp_file, &image_conf)?;
+        print_image(ctx, &tmp_file)?;
 
         // Remove the temporary file.
         std::fs::remove_file(&tmp_file)?;
@@ -358,6 +452,97 @@ impl crate::cmd::Command for CmdTextToCadView {
     }
 }
 
+/// Print the image at `path` to the terminal, using all of it but the last row.
+fn print_image(ctx: &crate::context::Context<'_>, path: &std::path::Path) -> Result<()> {
+    let (width, height) = (ctx.io.tty_size)()?;
+
+    let offset_x = 0;
+    let offset_y = 0;
+    // Now we setup the terminal viewer.
+    let image_conf = viuer::Config {
+        // set offset
+        x: offset_x,
+        y: offset_y,
+        // set dimensions
+        width: Some(width as u32 - (offset_x * 2) as u32),
+        // Make sure to leave the last row at the bottom for the prompt.
+        // Which is what the +1 is.
+        height: Some(height as u32 - ((offset_y * 2) + 1) as u32),
+        ..Default::default()
+    };
+    viuer::print_from_file(path, &image_conf)?;
+
+    Ok(())
+}
+
+/// Write a file to the output directory, and say where it went.
+fn write_output_file(
+    ctx: &mut crate::context::Context<'_>,
+    output_dir: &std::path::Path,
+    filename: &str,
+    data: &[u8],
+) -> Result<std::path::PathBuf> {
+    let path = output_dir.join(filename);
+    std::fs::write(&path, data)?;
+    writeln!(
+        ctx.io.out,
+        "wrote file `{}` to {}",
+        filename,
+        path.to_str().unwrap_or("")
+    )?;
+    Ok(path)
+}
+
+/// Open an exported file for `--open`.
+///
+/// Files the system knows how to show are opened with the system opener. Otherwise,
+/// or if there is no opener (like over ssh), a render of the gltf output is shown in
+/// the terminal. If that is not possible either, we print where the file is.
+async fn open_export(
+    ctx: &mut crate::context::Context<'_>,
+    path: &std::path::Path,
+    viewable: bool,
+    gltf_bytes: Option<Vec<u8>>,
+) -> Result<()> {
+    if viewable && has_system_opener() {
+        match open::that(path) {
+            Ok(()) => return Ok(()),
+            Err(err) => log::debug!("failed to open `{}`: {}", path.display(), err),
+        }
+    }
+
+    if let Some(gltf_bytes) = gltf_bytes {
+        if ctx.io.is_stdout_tty() {
+            let mut tmp_file = std::env::temp_dir();
+            tmp_file.push(format!("zoo-text-to-cad-open-{}.png", uuid::Uuid::new_v4()));
+
+            let image_bytes = get_image_bytes(ctx, &gltf_bytes, ImageFormat::Png).await?;
+            std::fs::write(&tmp_file, image_bytes)?;
+            let result = print_image(ctx, &tmp_file);
+            std::fs::remove_file(&tmp_file)?;
+            return result;
+        }
+    }
+
+    writeln!(
+        ctx.io.err_out,
+        "could not open the result, you can find it at {}",
+        path.display()
+    )?;
+
+    Ok(())
+}
+
+/// If there is a desktop to open files on. Without a display on Linux, the opener
+/// starts but never shows anything.
+fn has_system_opener() -> bool {
+    if cfg!(target_os = "linux") {
+        std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+    } else {
+        true
+    }
+}
+
 async fn get_image_bytes(
     ctx: &mut crate::context::Context<'_>,
     gltf_bytes: &[u8],
@@ -384,7 +569,7 @@ async fn get_image_bytes(
         modeling_response: OkModelingCmdResponse::ImportFiles(data),
     } = &resp
     else {
-        anyhow::bail!("Unexpected response from engine import: {:?}", resp);
+        return Err(crate::engine_response::unexpected("an import", &resp));
     };
 
     let object_id = data.object_id;
@@ -415,6 +600,6 @@ async fn get_image_bytes(
         // Save the snapshot locally.
         Ok(data.contents.0.clone())
     } else {
-        anyhow::bail!("Unexpected response from engine: {:?}", resp);
+        return Err(crate::engine_response::unexpected("a snapshot", &resp));
     }
 }
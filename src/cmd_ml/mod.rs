@@ -21,6 +21,22 @@ enum SubCommand {
     Kcl(crate::cmd_ml::cmd_kcl::CmdKcl),
 }
 
+/// The header for the system instruction in a prompt sent to the ML API.
+const SYSTEM_HEADER: &str = "System instruction (applies to everything below):";
+/// The header for the user's own prompt, after a system instruction.
+const PROMPT_HEADER: &str = "User prompt:";
+
+/// Put a system instruction ahead of a prompt. The API only takes a single prompt, so
+/// each part gets a header to make it clear where the instruction ends and the user's
+/// prompt starts. If there is no instruction, the prompt is sent as it is.
+pub(crate) fn with_system_instruction(system: &str, prompt: &str) -> String {
+    let system = system.trim();
+    if system.is_empty() {
+        return prompt.to_string();
+    }
+    format!("{SYSTEM_HEADER}\n{system}\n\n{PROMPT_HEADER}\n{prompt}")
+}
+
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdMl {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
@@ -30,3 +46,19 @@ impl crate::cmd::Command for CmdMl {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_with_system_instruction() {
+        assert_eq!(
+            with_system_instruction("always use metric", "A 2x4 lego brick"),
+            "System instruction (applies to everything below):\nalways use metric\n\nUser prompt:\nA 2x4 lego brick"
+        );
+        assert_eq!(with_system_instruction("  ", "A 2x4 lego brick"), "A 2x4 lego brick");
+    }
+}
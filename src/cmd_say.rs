@@ -111,11 +111,7 @@ mod test {
             io.set_color_enabled(false);
             // TODO: we should figure out how to test the prompts.
             io.set_never_prompt(true);
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             let cmd_say = crate::cmd_say::CmdSay { input: t.cmd.input };
             match cmd_say.run(&mut ctx).await {
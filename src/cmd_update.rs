@@ -1,17 +1,48 @@
 use anyhow::Result;
 use clap::Parser;
 
+/// The exit code of `zoo update --check-only` when there is a newer version.
+pub const UPDATE_AVAILABLE_EXIT_CODE: i32 = 10;
+
 /// Update the current running binary to the latest version.
 ///
 /// This function will return an error if the current binary is under Homebrew or if
 /// the running version is already the latest version.
+///
+///     # check if there is a newer version, without installing it
+///     $ zoo update --check-only
+///
+/// With `--check-only`, the exit code is 10 if there is a newer version, and 0 if you are
+/// running the latest version.
+///
+///     # go back to the version you were running before the last update
+///     $ zoo update --rollback
+///
+/// Before updating, a copy of the current binary is saved in the config directory, which is
+/// what `--rollback` goes back to.
 #[derive(Parser, Debug, Clone)]
 #[clap(verbatim_doc_comment)]
-pub struct CmdUpdate {}
+pub struct CmdUpdate {
+    /// Only check if there is a newer version, do not install it.
+    #[clap(long, default_value = "false", conflicts_with = "rollback")]
+    pub check_only: bool,
+
+    /// Go back to the version that was installed before the last update.
+    #[clap(long, default_value = "false")]
+    pub rollback: bool,
+}
 
 #[async_trait::async_trait(?Send)]
 impl crate::cmd::Command for CmdUpdate {
     async fn run(&self, ctx: &mut crate::context::Context) -> Result<()> {
+        if self.check_only {
+            return self.check(ctx).await;
+        }
+
+        if self.rollback {
+            return self.roll_back(ctx);
+        }
+
         if crate::update::is_under_homebrew()? {
             anyhow::bail!("You are running under Homebrew. Please run `brew update && brew upgrade zoo` instead.");
         }
@@ -40,6 +71,9 @@ impl crate::cmd::Command for CmdUpdate {
         // Download the latest release.
         let temp_latest_binary_path = crate::update::download_binary_to_temp_file(&latest_release.version).await?;
 
+        // Keep a copy of the current binary so we can roll back.
+        crate::update::save_previous_binary(&current_binary_path, current_version)?;
+
         // Rename the file to that of the current running exe.
         std::fs::rename(temp_latest_binary_path, current_binary_path)?;
 
@@ -53,3 +87,88 @@ impl crate::cmd::Command for CmdUpdate {
         Ok(())
     }
 }
+
+impl CmdUpdate {
+    /// Report if there is a newer version.
+    async fn check(&self, ctx: &mut crate::context::Context<'_>) -> Result<()> {
+        let current_version = clap::crate_version!();
+        let Some(latest_release) = crate::update::check_for_update(current_version, true).await? else {
+            writeln!(
+                ctx.io.out,
+                "You are running the latest version ({}) of `zoo`.",
+                current_version
+            )?;
+            return Ok(());
+        };
+
+        let cs = ctx.io.color_scheme();
+        writeln!(
+            ctx.io.out,
+            "{} {} → {}",
+            cs.yellow("A new release of zoo is available:"),
+            cs.cyan(current_version),
+            cs.purple(&latest_release.version)
+        )?;
+        if crate::update::is_under_homebrew()? {
+            writeln!(ctx.io.out, "To upgrade, run: `brew update && brew upgrade zoo`")?;
+        } else {
+            writeln!(ctx.io.out, "To upgrade, run: `zoo update`")?;
+        }
+        writeln!(ctx.io.out, "{}", cs.yellow(&latest_release.url))?;
+
+        Err(crate::cmd::ExitCode(UPDATE_AVAILABLE_EXIT_CODE).into())
+    }
+
+    /// Go back to the binary saved before the last update.
+    fn roll_back(&self, ctx: &mut crate::context::Context<'_>) -> Result<()> {
+        if crate::update::is_under_homebrew()? {
+            anyhow::bail!("You are running under Homebrew, which manages the installed version of `zoo`. Please use `brew` to change versions instead.");
+        }
+
+        let Some((previous_binary_path, previous_version)) = crate::update::get_previous_binary()? else {
+            anyhow::bail!("There is no previous version of `zoo` to roll back to. Only versions installed with `zoo update` can be rolled back.");
+        };
+
+        // Make sure the previous binary actually runs before we swap it in.
+        let output = std::process::Command::new(&previous_binary_path)
+            .arg("--version")
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "The previous version of `zoo` ({}) at `{}` does not run: {}",
+                previous_version,
+                previous_binary_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let current_version = clap::crate_version!();
+        let current_binary_path = std::env::current_exe()?;
+
+        writeln!(
+            ctx.io.out,
+            "Rolling back from v{} to v{}...",
+            current_version,
+            previous_version.trim_start_matches('v')
+        )?;
+
+        // Copy next to the current exe first, so the rename can't fail across file systems.
+        let temp_binary_path = current_binary_path.with_extension("rollback");
+        std::fs::copy(&previous_binary_path, &temp_binary_path)?;
+
+        // Save the current binary as the previous one, so you can roll forward again.
+        crate::update::save_previous_binary(&current_binary_path, current_version)?;
+
+        std::fs::rename(temp_binary_path, current_binary_path)?;
+
+        let cs = ctx.io.color_scheme();
+        writeln!(
+            ctx.io.out,
+            "{} Rolled back to {}",
+            cs.success_icon(),
+            String::from_utf8_lossy(&output.stdout).trim()
+        )?;
+
+        Ok(())
+    }
+}
@@ -115,11 +115,7 @@ mod test {
             // This ensures it also works in GitHub actions/any CI.
             io.set_color_enabled(false);
             io.set_never_prompt(true);
-            let mut ctx = crate::context::Context {
-                config: &mut c,
-                io,
-                debug: false,
-            };
+            let mut ctx = crate::context::Context::test(&mut c, io);
 
             let cmd_user = crate::cmd_user::CmdUser { subcmd: t.cmd };
             match cmd_user.run(&mut ctx).await {
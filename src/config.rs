@@ -16,6 +16,9 @@ pub trait Config: Send + Sync {
     fn unset_host(&mut self, key: &str) -> Result<()>;
     /// Get the hosts.
     fn hosts(&self) -> Result<Vec<String>>;
+    /// Get the names of the accounts for a host. The `token` and `user` of the host are
+    /// the ones of its active `account`, if it has one.
+    fn accounts(&self, hostname: &str) -> Result<Vec<String>>;
 
     /// Get the default host.
     fn default_host(&self) -> Result<String>;
@@ -102,6 +105,48 @@ pub static CONFIG_OPTIONS: &[ConfigOption] = &[
         default_value: "table",
         allowed_values: crate::types::FormatOutput::variants(),
     },
+    ConfigOption::TopLevel {
+        key: "length_unit",
+        description: "the default output unit for lengths",
+        comment: "What unit zoo should output lengths in when `--output-unit` is not given.",
+        default_value: "",
+        allowed_values: &[],
+    },
+    ConfigOption::TopLevel {
+        key: "area_unit",
+        description: "the default output unit for areas",
+        comment: "What unit zoo should output areas in when `--output-unit` is not given.",
+        default_value: "",
+        allowed_values: &[],
+    },
+    ConfigOption::TopLevel {
+        key: "volume_unit",
+        description: "the default output unit for volumes",
+        comment: "What unit zoo should output volumes in when `--output-unit` is not given.",
+        default_value: "",
+        allowed_values: &[],
+    },
+    ConfigOption::TopLevel {
+        key: "mass_unit",
+        description: "the default output unit for masses",
+        comment: "What unit zoo should output masses in when `--output-unit` is not given.",
+        default_value: "",
+        allowed_values: &[],
+    },
+    ConfigOption::TopLevel {
+        key: "density_unit",
+        description: "the default output unit for densities",
+        comment: "What unit zoo should output densities in when `--output-unit` is not given.",
+        default_value: "",
+        allowed_values: &[],
+    },
+    ConfigOption::TopLevel {
+        key: "system_prompt",
+        description: "a standing instruction to send ahead of machine learning prompts",
+        comment: "What instruction zoo should send ahead of Text-to-CAD and KCL edit prompts.",
+        default_value: "",
+        allowed_values: &[],
+    },
     ConfigOption::HostLevel {
         key: "default",
         allowed_values: &["true", "false"],
@@ -142,6 +187,14 @@ pub fn validate_value(target_key: &str, value: &str) -> Result<()> {
         }
     }
 
+    // The unit options are validated against the units the API knows about. Empty unsets them.
+    if let Some(units) = unit_values(target_key) {
+        if value.is_empty() {
+            return Ok(());
+        }
+        valid_values = units;
+    }
+
     if valid_values.is_empty() {
         return Ok(());
     }
@@ -155,6 +208,49 @@ pub fn validate_value(target_key: &str, value: &str) -> Result<()> {
     Err(InvalidValueError::ValidValues(valid_values).into())
 }
 
+/// A kind of measurement that has a default output unit in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Measurement {
+    Length,
+    Area,
+    Volume,
+    Mass,
+    Density,
+}
+
+impl Measurement {
+    /// The config key holding the default output unit for this measurement.
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            Measurement::Length => "length_unit",
+            Measurement::Area => "area_unit",
+            Measurement::Volume => "volume_unit",
+            Measurement::Mass => "mass_unit",
+            Measurement::Density => "density_unit",
+        }
+    }
+}
+
+/// The valid values for one of the default unit options, or `None` if the key is not a unit option.
+fn unit_values(key: &str) -> Option<Vec<String>> {
+    fn names<T: clap::ValueEnum>() -> Vec<String> {
+        T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect()
+    }
+
+    match key {
+        "length_unit" => Some(names::<kittycad::types::UnitLength>()),
+        "area_unit" => Some(names::<kittycad::types::UnitArea>()),
+        "volume_unit" => Some(names::<kittycad::types::UnitVolume>()),
+        "mass_unit" => Some(names::<kittycad::types::UnitMass>()),
+        "density_unit" => Some(names::<kittycad::types::UnitDensity>()),
+        _ => None,
+    }
+}
+
 // new_from_string initializes a Config from a toml string.
 #[cfg(test)]
 pub fn new_from_string(s: &str) -> Result<impl Config> {
@@ -250,6 +346,43 @@ browser = "chrome""#;
         assert_eq!(hosts[1], "zoo.computer".to_string());
     }
 
+    #[test]
+    fn test_validate_unit_value() {
+        assert!(validate_value("volume_unit", "cm3").is_ok());
+        assert!(validate_value("length_unit", "mm").is_ok());
+        assert!(validate_value("mass_unit", "").is_ok());
+        assert!(validate_value("volume_unit", "cm").is_err());
+        assert!(validate_value("area_unit", "cm3").is_err());
+        assert_eq!(Measurement::Density.config_key(), "density_unit");
+    }
+
+    #[test]
+    fn test_file_config_accounts() {
+        let mut c = new_blank_config().unwrap();
+        assert!(c.set("example.com", "token", Some("host-token")).is_ok());
+        assert_eq!(c.accounts("example.com").unwrap(), Vec::<String>::new());
+
+        // Once an account is active, the token is stored with the account.
+        assert!(c.set("example.com", "account", Some("work")).is_ok());
+        assert!(c.get("example.com", "token").is_err());
+        assert!(c.set("example.com", "token", Some("work-token")).is_ok());
+        assert!(c.set("example.com", "account", Some("personal")).is_ok());
+        assert!(c.set("example.com", "token", Some("personal-token")).is_ok());
+
+        assert_eq!(c.accounts("example.com").unwrap(), vec!["work", "personal"]);
+        assert_eq!(c.get("example.com", "token").unwrap(), "personal-token");
+
+        assert!(c.set("example.com", "account", Some("work")).is_ok());
+        assert_eq!(c.get("example.com", "token").unwrap(), "work-token");
+
+        // Without an active account, the host token is used.
+        assert!(c.set("example.com", "account", None).is_ok());
+        assert_eq!(c.get("example.com", "token").unwrap(), "host-token");
+
+        let doc = c.hosts_to_string().unwrap();
+        assert!(doc.contains("[\"example.com\".accounts.work]"), "{}", doc);
+    }
+
     #[test]
     fn test_default_config() {
         let c = new_blank_config().unwrap();
@@ -270,7 +403,25 @@ browser = ""
 
 # What formatting zoo should use when printing text.
 # Supported values: table, json, yaml
-format = "table""#;
+format = "table"
+
+# What unit zoo should output lengths in when `--output-unit` is not given.
+length_unit = ""
+
+# What unit zoo should output areas in when `--output-unit` is not given.
+area_unit = ""
+
+# What unit zoo should output volumes in when `--output-unit` is not given.
+volume_unit = ""
+
+# What unit zoo should output masses in when `--output-unit` is not given.
+mass_unit = ""
+
+# What unit zoo should output densities in when `--output-unit` is not given.
+density_unit = ""
+
+# What instruction zoo should send ahead of Text-to-CAD and KCL edit prompts.
+system_prompt = """#;
         assert_eq!(doc_config, expected);
 
         let doc_hosts = c.hosts_to_string().unwrap();
@@ -9,6 +9,7 @@ use anyhow::{anyhow, Context, Result};
 const ZOO_CONFIG_DIR: &str = "ZOO_CONFIG_DIR";
 const XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
 const XDG_STATE_HOME: &str = "XDG_STATE_HOME";
+const XDG_CACHE_HOME: &str = "XDG_CACHE_HOME";
 #[allow(dead_code)]
 const XDG_DATA_HOME: &str = "XDG_DATA_HOME";
 const APP_DATA: &str = "CommandData";
@@ -82,6 +83,38 @@ pub fn state_dir() -> Result<String> {
     }
 }
 
+// Cache path precedence
+// 2. XDG_CACHE_HOME
+// 3. LocalCommandData (windows only)
+// 4. HOME
+pub fn cache_dir() -> Result<String> {
+    let path: PathBuf;
+
+    let xdg_cache_home = get_env_var(XDG_CACHE_HOME);
+    let local_app_data = get_env_var(LOCAL_APP_DATA);
+
+    if !xdg_cache_home.is_empty() {
+        path = Path::new(&xdg_cache_home).join("zoo");
+    } else if !local_app_data.is_empty() && std::env::consts::OS == "windows" {
+        path = Path::new(&local_app_data).join("Zoo CLI").join("cache");
+    } else {
+        match dirs::home_dir() {
+            Some(home) => {
+                path = home.join(".cache").join("zoo");
+            }
+            None => {
+                return Err(anyhow!("could not find home directory"));
+            }
+        }
+    }
+
+    // Convert the path into a string slice
+    match path.to_str() {
+        None => Err(anyhow!("path is not a valid UTF-8 sequence")),
+        Some(s) => Ok(s.to_string()),
+    }
+}
+
 // Data path precedence
 // 2. XDG_DATA_HOME
 // 3. LocalCommandData (windows only)
@@ -148,6 +181,18 @@ pub fn state_file() -> Result<String> {
     }
 }
 
+/// The directory where `zoo update` keeps the previous binary, so it can be rolled back.
+pub fn previous_binary_dir() -> Result<String> {
+    let config_dir = config_dir()?;
+    let path = Path::new(&config_dir).join("previous");
+
+    // Convert the path into a string slice
+    match path.to_str() {
+        None => Err(anyhow!("path is not a valid UTF-8 sequence")),
+        Some(s) => Ok(s.to_string()),
+    }
+}
+
 pub fn parse_default_config() -> Result<impl crate::config::Config> {
     let config_file_path = config_file()?;
 
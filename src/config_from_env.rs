@@ -7,6 +7,28 @@ use crate::config_file::get_env_var;
 
 const ZOO_HOST: &str = "ZOO_HOST";
 const ZOO_TOKEN: &str = "ZOO_TOKEN";
+const ZOO_TOKEN_FILE: &str = "ZOO_TOKEN_FILE";
+
+/// The token from `ZOO_TOKEN`, or else read from the file at `ZOO_TOKEN_FILE`, with the
+/// name of the variable it came from.
+fn token_from_env() -> Result<Option<(String, String)>> {
+    let token = get_env_var(ZOO_TOKEN);
+    if !token.is_empty() {
+        return Ok(Some((token, ZOO_TOKEN.to_string())));
+    }
+
+    let token_file = get_env_var(ZOO_TOKEN_FILE);
+    if !token_file.is_empty() {
+        let token = std::fs::read_to_string(&token_file)
+            .map_err(|err| anyhow::anyhow!("failed to read {} `{}`: {}", ZOO_TOKEN_FILE, token_file, err))?;
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(Some((token.to_string(), ZOO_TOKEN_FILE.to_string())));
+        }
+    }
+
+    Ok(None)
+}
 
 pub struct EnvConfig<'a> {
     pub config: &'a mut (dyn crate::config::Config + 'a),
@@ -33,9 +55,8 @@ impl crate::config::Config for EnvConfig<'_> {
     fn get_with_source(&self, hostname: &str, key: &str) -> Result<(String, String)> {
         // If they are asking specifically for the token, return the value.
         if key == "token" {
-            let token = get_env_var(ZOO_TOKEN);
-            if !token.is_empty() {
-                return Ok((token, ZOO_TOKEN.to_string()));
+            if let Some(token) = token_from_env()? {
+                return Ok(token);
             }
         } else {
             let var = format!("ZOO_{}", heck::AsShoutySnakeCase(key));
@@ -60,6 +81,10 @@ impl crate::config::Config for EnvConfig<'_> {
         self.config.hosts()
     }
 
+    fn accounts(&self, hostname: &str) -> Result<Vec<String>> {
+        self.config.accounts(hostname)
+    }
+
     fn default_host(&self) -> Result<String> {
         let (host, _) = self.default_host_with_source()?;
         Ok(host)
@@ -88,9 +113,8 @@ impl crate::config::Config for EnvConfig<'_> {
     fn check_writable(&self, hostname: &str, key: &str) -> Result<()> {
         // If they are asking specifically for the token, return the value.
         if key == "token" {
-            let token = get_env_var(ZOO_TOKEN);
-            if !token.is_empty() {
-                return Err(ReadOnlyEnvVarError::Variable(ZOO_TOKEN.to_string()).into());
+            if let Some((_, var)) = token_from_env()? {
+                return Err(ReadOnlyEnvVarError::Variable(var).into());
             }
         }
 
@@ -14,6 +14,36 @@ pub struct HostConfig {
     pub host: String,
 }
 
+/// The keys that belong to an account, rather than to the host. When a host has an
+/// active `account`, these are read from and written to its `accounts.<account>` table.
+const ACCOUNT_KEYS: &[&str] = &["token", "user"];
+
+impl HostConfig {
+    fn active_account(&self) -> Option<String> {
+        self.map.get_string_value("account").ok().filter(|a| !a.is_empty())
+    }
+
+    fn accounts_table(&self) -> toml_edit::Table {
+        let mut accounts = self
+            .map
+            .root
+            .get("accounts")
+            .and_then(|a| a.as_table())
+            .cloned()
+            .unwrap_or_default();
+        // Write the accounts as `[host.accounts.name]` rather than an empty `[host.accounts]`.
+        accounts.set_implicit(true);
+        accounts
+    }
+
+    fn account_map(&self, account: &str) -> Option<crate::config_map::ConfigMap> {
+        self.accounts_table()
+            .get(account)
+            .and_then(|a| a.as_table())
+            .map(|t| crate::config_map::ConfigMap { root: t.clone() })
+    }
+}
+
 impl FileConfig {
     fn get_hosts_table(&self) -> Result<toml_edit::Table> {
         match self.map.find_entry("hosts") {
@@ -124,6 +154,22 @@ impl crate::config::Config for FileConfig {
 
         let host_config = self.get_host_config(hostname)?;
 
+        if ACCOUNT_KEYS.contains(&key) {
+            if let Some(account) = host_config.active_account() {
+                let account_map = host_config.account_map(&account).ok_or_else(|| {
+                    anyhow!(
+                        "account `{}` not found for host `{}`. Try authenticating with `zoo auth login --account {}`.",
+                        account,
+                        hostname,
+                        account
+                    )
+                })?;
+                let value = account_map.get_string_value(key)?;
+
+                return Ok((value, hosts_source));
+            }
+        }
+
         let value = host_config.map.get_string_value(key)?;
 
         Ok((value, hosts_source))
@@ -142,7 +188,24 @@ impl crate::config::Config for FileConfig {
             }
         };
 
-        host_config.map.set_string_value(key, value)?;
+        match host_config.active_account() {
+            Some(account) if ACCOUNT_KEYS.contains(&key) => {
+                let mut account_map = host_config
+                    .account_map(&account)
+                    .unwrap_or(crate::config_map::ConfigMap {
+                        root: toml_edit::Table::new(),
+                    });
+                account_map.set_string_value(key, value)?;
+
+                let mut accounts = host_config.accounts_table();
+                accounts.insert(&account, toml_edit::Item::Table(account_map.root));
+                host_config
+                    .map
+                    .root
+                    .insert("accounts", toml_edit::Item::Table(accounts));
+            }
+            _ => host_config.map.set_string_value(key, value)?,
+        }
 
         // Get our hosts table.
         let mut hosts_table = self.get_hosts_table()?;
@@ -188,6 +251,18 @@ impl crate::config::Config for FileConfig {
         Ok(hosts)
     }
 
+    fn accounts(&self, hostname: &str) -> Result<Vec<String>> {
+        let Ok(host_config) = self.get_host_config(hostname) else {
+            return Ok(vec![]);
+        };
+
+        Ok(host_config
+            .accounts_table()
+            .iter()
+            .map(|(account, _)| account.to_string())
+            .collect())
+    }
+
     fn default_host(&self) -> Result<String> {
         let (host, _) = self.default_host_with_source()?;
         Ok(host)
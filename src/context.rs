@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{io::Read, str::FromStr};
 
 use anyhow::{anyhow, Result};
 use kcl_lib::native_engine::EngineConnection;
@@ -14,8 +14,79 @@ pub struct Context<'a> {
     pub config: &'a mut (dyn Config + Send + Sync + 'a),
     pub io: crate::iostreams::IoStreams,
     pub debug: bool,
+    /// How many times to reconnect to the engine and re-run a kcl program if the
+    /// connection drops.
+    pub engine_retries: u32,
+    /// How many times to re-run a kcl program from the start if it times out. These are
+    /// tried before `engine_retries`, which are for losing the connection.
+    pub timeout_retries: u32,
+    /// The largest input file, in bytes, we are willing to read into memory.
+    pub max_file_size: u64,
+    /// Connect to the engine on this host instead of the API host. Authentication still
+    /// uses the API host.
+    pub engine_host: Option<String>,
+    /// If set, how long each phase of running kcl takes is recorded here.
+    pub timings: Option<Timings>,
+    /// Skip checking for a newer release of the cli.
+    pub no_update_check: bool,
+    /// The size to ask the engine to render at, as `(width, height)` in pixels, or the
+    /// engine's default if not set.
+    pub video_resolution: Option<(u32, u32)>,
+    /// A handle to `io.err_out`, for messages for the user, like retries, from methods
+    /// that only borrow the context.
+    pub shared_err_out: crate::iostreams::SharedWriter,
 }
 
+/// How long the phases of running kcl took, for `--log-timings`.
+#[derive(Debug, Default)]
+pub struct Timings {
+    phases: std::sync::Mutex<Vec<(String, std::time::Duration)>>,
+}
+
+impl Timings {
+    /// Record how long a phase took.
+    pub fn record(&self, phase: &str, duration: std::time::Duration) {
+        if let Ok(mut phases) = self.phases.lock() {
+            phases.push((phase.to_string(), duration));
+        }
+    }
+
+    /// The phases in the order they were recorded.
+    pub fn phases(&self) -> Vec<(String, std::time::Duration)> {
+        self.phases.lock().map(|phases| phases.clone()).unwrap_or_default()
+    }
+
+    /// Write a table of the phases and the total, for people to read.
+    pub fn write_report(&self, w: &mut impl std::io::Write) -> Result<()> {
+        let phases = self.phases();
+        let width = phases.iter().map(|(phase, _)| phase.len()).max().unwrap_or(0).max(5);
+        writeln!(w, "timings:")?;
+        for (phase, duration) in &phases {
+            writeln!(w, "  {:<width$}  {:>10.2?}", phase, duration, width = width)?;
+        }
+        let total: std::time::Duration = phases.iter().map(|(_, duration)| *duration).sum();
+        writeln!(w, "  {:<width$}  {:>10.2?}", "total", total, width = width)?;
+        Ok(())
+    }
+}
+
+/// The name of a modeling command, like `export`, as the engine calls it.
+fn modeling_cmd_name(cmd: &ModelingCmd) -> String {
+    serde_json::to_value(cmd)
+        .ok()
+        .and_then(|value| value.get("type").and_then(|t| t.as_str()).map(|t| t.to_string()))
+        .unwrap_or_else(|| "command".to_string())
+}
+
+/// The default for `Context::max_file_size`, 2GiB.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// How much room to leave around the model when zooming to fit it, as a fraction of
+/// the view.
+pub const DEFAULT_ZOOM_PADDING: f32 = 0.1;
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), ".rs/", env!("CARGO_PKG_VERSION"));
+
 impl Context<'_> {
     pub fn new(config: &mut (dyn Config + Send + Sync)) -> Context {
         // Let's get our IO streams.
@@ -47,16 +118,55 @@ impl Context<'_> {
             }
         }
 
+        let shared_err_out = io.share_err_out();
+        Context {
+            config,
+            io,
+            debug: false,
+            engine_retries: 0,
+            timeout_retries: 0,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            engine_host: None,
+            timings: None,
+            no_update_check: false,
+            video_resolution: None,
+            shared_err_out,
+        }
+    }
+
+    /// A context for tests, with the given config and IO streams and every global
+    /// flag left at its default.
+    #[cfg(test)]
+    pub fn test(config: &mut (dyn Config + Send + Sync), mut io: crate::iostreams::IoStreams) -> Context {
+        let shared_err_out = io.share_err_out();
         Context {
             config,
             io,
             debug: false,
+            engine_retries: 0,
+            timeout_retries: 0,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            engine_host: None,
+            timings: None,
+            no_update_check: false,
+            video_resolution: None,
+            shared_err_out,
         }
     }
 
     /// This function returns an API client for Zoo that is based on the configured
     /// user.
     pub fn api_client(&self, hostname: &str) -> Result<kittycad::Client> {
+        self.client_for(hostname, None)
+    }
+
+    /// Like `api_client`, but pointed at the `engine_host` override, if there is one.
+    /// The token is still the one for the API host.
+    fn engine_client(&self, hostname: &str) -> Result<kittycad::Client> {
+        self.client_for(hostname, self.engine_host.as_deref())
+    }
+
+    fn client_for(&self, hostname: &str, base_host: Option<&str>) -> Result<kittycad::Client> {
         // Use the host passed in if it's set.
         // Otherwise, use the default host.
         let host = if hostname.is_empty() {
@@ -66,29 +176,23 @@ impl Context<'_> {
         };
 
         // Change the baseURL to the one we want.
-        let mut baseurl = host.to_string();
-        if !host.starts_with("http://") && !host.starts_with("https://") {
-            baseurl = format!("https://{host}");
-            if host.starts_with("localhost") {
-                baseurl = format!("http://{host}")
-            }
-        }
+        let baseurl = base_url(base_host.unwrap_or(&host));
 
-        let user_agent = concat!(env!("CARGO_PKG_NAME"), ".rs/", env!("CARGO_PKG_VERSION"),);
         let http_client = reqwest::Client::builder()
-            .user_agent(user_agent)
+            .user_agent(USER_AGENT)
             // For file conversions we need this to be long.
             .timeout(std::time::Duration::from_secs(600))
             .connect_timeout(std::time::Duration::from_secs(60));
         let ws_client = reqwest::Client::builder()
-            .user_agent(user_agent)
+            .user_agent(USER_AGENT)
             // For file conversions we need this to be long.
             .timeout(std::time::Duration::from_secs(600))
             .connect_timeout(std::time::Duration::from_secs(60))
             .tcp_keepalive(std::time::Duration::from_secs(600))
             .http1_only();
 
-        // Get the token for that host.
+        // Get the token for that host, which is the token of its active account if it has
+        // more than one.
         let token = self.config.get(&host, "token")?;
 
         // Create the client.
@@ -101,6 +205,52 @@ impl Context<'_> {
         Ok(client)
     }
 
+    /// Open a websocket to an API endpoint, like `/ws/modeling/commands`, as the
+    /// configured user.
+    pub async fn api_ws(&self, hostname: &str, endpoint: &str) -> Result<reqwest::Upgraded> {
+        let host = if hostname.is_empty() {
+            self.config.default_host()?
+        } else {
+            hostname.to_string()
+        };
+        let token = self.config.get(&host, "token")?;
+
+        // Websockets are upgraded from HTTP/1.1 connections.
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(std::time::Duration::from_secs(60))
+            .tcp_keepalive(std::time::Duration::from_secs(600))
+            .http1_only()
+            .build()?;
+
+        let mut key = [0u8; 16];
+        ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut key)
+            .map_err(|_| anyhow!("failed to generate a websocket key"))?;
+
+        let resp = client
+            .get(format!("{}{}", base_url(&host), endpoint))
+            .bearer_auth(token)
+            .header(http::header::CONNECTION, "Upgrade")
+            .header(http::header::UPGRADE, "websocket")
+            .header(http::header::SEC_WEBSOCKET_VERSION, "13")
+            .header(http::header::SEC_WEBSOCKET_KEY, data_encoding::BASE64.encode(&key))
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if status != reqwest::StatusCode::SWITCHING_PROTOCOLS {
+            let body = resp.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "failed to open a websocket to `{}`: {}: {}",
+                endpoint,
+                status,
+                body.trim()
+            );
+        }
+
+        Ok(resp.upgrade().await?)
+    }
+
     #[allow(dead_code)]
     pub async fn send_single_modeling_cmd(
         &self,
@@ -116,17 +266,40 @@ impl Context<'_> {
         Ok(resp)
     }
 
-    async fn engine_ws(&self, hostname: &str, replay: Option<String>) -> Result<reqwest::Upgraded> {
-        let client = self.api_client(hostname)?;
+    /// Open a websocket to the engine, at the `video_resolution` if there is one. The
+    /// `settings` of a kcl program turn on the same scene options as
+    /// `kcl_lib::ExecutorContext::new` does.
+    async fn engine_ws(
+        &self,
+        hostname: &str,
+        settings: Option<&kcl_lib::ExecutorSettings>,
+        replay: Option<String>,
+    ) -> Result<reqwest::Upgraded> {
+        let client = self.engine_client(hostname)?;
+        let post_effect = settings
+            .filter(|settings| settings.enable_ssao)
+            .map(|_| kittycad::types::PostEffectType::Ssao);
+        let show_grid = settings.filter(|settings| settings.show_grid).map(|_| true);
+        let (width, height) = self.video_resolution.unzip();
         let (ws, _headers) = client
             .modeling()
-            .commands_ws(None, None, None, replay, None, None, None, None, Some(false))
+            .commands_ws(
+                None,
+                None,
+                post_effect,
+                replay,
+                show_grid,
+                None,
+                height,
+                width,
+                Some(false),
+            )
             .await?;
         Ok(ws)
     }
 
     pub async fn engine(&self, hostname: &str, replay: Option<String>) -> Result<EngineConnection> {
-        let ws = self.engine_ws(hostname, replay).await?;
+        let ws = self.engine_ws(hostname, None, replay).await?;
 
         let engine = EngineConnection::new(ws).await?;
 
@@ -139,37 +312,278 @@ impl Context<'_> {
         code: &str,
         cmd: kittycad_modeling_cmds::ModelingCmd,
         settings: kcl_lib::ExecutorSettings,
+        zoom_padding: Option<f32>,
     ) -> Result<(OkWebSocketResponseData, Option<ModelingSessionData>)> {
-        let client = self.api_client(hostname)?;
+        self.send_kcl_modeling_cmd_with_setup(hostname, code, &[], cmd, settings, zoom_padding)
+            .await
+    }
 
-        let program =
-            kcl_lib::Program::parse_no_errs(code).map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
+    /// Execute the kcl code, then send the `setup_cmds` (for example camera or scene
+    /// settings) before zooming to fit and sending the final `cmd`.
+    /// The zoom to fit leaves `zoom_padding` around the model, and is skipped if it is
+    /// `None`.
+    pub async fn send_kcl_modeling_cmd_with_setup(
+        &self,
+        hostname: &str,
+        code: &str,
+        setup_cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        cmd: kittycad_modeling_cmds::ModelingCmd,
+        settings: kcl_lib::ExecutorSettings,
+        zoom_padding: Option<f32>,
+    ) -> Result<(OkWebSocketResponseData, Option<ModelingSessionData>)> {
+        let (mut responses, session_data) = self
+            .send_kcl_modeling_cmds_with_setup(hostname, code, setup_cmds, &[cmd], settings, zoom_padding)
+            .await?;
 
-        let ctx = kcl_lib::ExecutorContext::new(&client, settings).await?;
-        let session_data = ctx
-            .run_with_session_data(program.into(), &mut Default::default())
+        let resp = responses
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("no response from engine"))?;
+        Ok((resp, session_data))
+    }
+
+    /// Like `send_kcl_modeling_cmd_with_setup`, but sends several `cmds` over the same
+    /// engine session and returns their responses in order.
+    pub async fn send_kcl_modeling_cmds_with_setup(
+        &self,
+        hostname: &str,
+        code: &str,
+        setup_cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        settings: kcl_lib::ExecutorSettings,
+        zoom_padding: Option<f32>,
+    ) -> Result<(Vec<OkWebSocketResponseData>, Option<ModelingSessionData>)> {
+        // Parse errors will never go away by retrying, so parse before we start.
+        let program = self.parse_kcl(code)?;
+
+        self.with_engine_retries(|| {
+            self.execute_kcl_modeling_cmds(
+                hostname,
+                code,
+                &program,
+                setup_cmds,
+                cmds,
+                settings.clone(),
+                zoom_padding,
+            )
+        })
+        .await
+    }
+
+    /// Execute the kcl code and return the executor context, so the caller can send
+    /// their own modeling commands over the same engine connection.
+    pub async fn execute_kcl(
+        &self,
+        hostname: &str,
+        code: &str,
+        settings: kcl_lib::ExecutorSettings,
+    ) -> Result<(kcl_lib::ExecutorContext, Option<ModelingSessionData>)> {
+        // Parse errors will never go away by retrying, so parse before we start.
+        let program = self.parse_kcl(code)?;
+
+        self.with_engine_retries(|| self.run_kcl(hostname, code, &program, settings.clone()))
             .await
-            .map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
+    }
+
+    /// Execute the kcl code again, over the engine connection of an executor context from
+    /// `execute_kcl`. The scene is cleared first, so the model isn't drawn twice.
+    pub async fn reexecute_kcl(&self, ctx: &kcl_lib::ExecutorContext, code: &str) -> Result<()> {
+        let program = self.parse_kcl(code)?;
 
-        // Zoom on the object.
         ctx.engine
             .send_modeling_cmd(
                 uuid::Uuid::new_v4(),
                 kcl_lib::SourceRange::default(),
-                ModelingCmd::from(mcmd::ZoomToFit {
-                    animated: false,
-                    object_ids: Default::default(),
-                    padding: 0.1,
-                }),
+                ModelingCmd::from(mcmd::SceneClearAll {}),
             )
             .await?;
 
-        let resp = ctx
-            .engine
-            .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), cmd)
+        let start = std::time::Instant::now();
+        ctx.run_with_session_data(program.into(), &mut Default::default())
             .await
             .map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
-        Ok((resp, session_data))
+        self.record_timing("execute", start);
+
+        Ok(())
+    }
+
+    fn parse_kcl(&self, code: &str) -> Result<kcl_lib::Program> {
+        let start = std::time::Instant::now();
+        let program =
+            kcl_lib::Program::parse_no_errs(code).map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
+        self.record_timing("parse", start);
+        Ok(program)
+    }
+
+    /// Write a message for the user to stderr right away, from a method that only
+    /// borrows the context.
+    fn notice(&self, message: &str) {
+        self.shared_err_out.write_line(message);
+    }
+
+    /// Record how long a phase took since `start`, if we are recording timings.
+    pub fn record_timing(&self, phase: &str, start: std::time::Instant) {
+        if let Some(timings) = &self.timings {
+            timings.record(phase, start.elapsed());
+        }
+    }
+
+    /// Run `f`, and if it times out, run it again right away up to `timeout_retries`
+    /// more times. If it loses the engine connection, or keeps timing out, run it again
+    /// with exponential backoff up to `engine_retries` more times. Any other error, like
+    /// one in the kcl program, would happen again, so it is returned right away.
+    async fn with_engine_retries<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        let mut timeout_attempt = 0;
+        loop {
+            match f().await {
+                Ok(result) => return Ok(result),
+                Err(err) if timeout_attempt < self.timeout_retries && is_timeout(&err) => {
+                    timeout_attempt += 1;
+                    self.notice(&format!(
+                        "timed out, retrying ({}/{}): {}",
+                        timeout_attempt, self.timeout_retries, err
+                    ));
+                }
+                Err(err) if attempt < self.engine_retries && (is_connection_error(&err) || is_timeout(&err)) => {
+                    attempt += 1;
+                    let backoff = engine_backoff(attempt);
+                    self.notice(&format!(
+                        "engine attempt {}/{} failed, retrying in {:?}: {}",
+                        attempt,
+                        self.engine_retries + 1,
+                        backoff,
+                        err
+                    ));
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Execute an already parsed kcl program over a fresh engine connection.
+    async fn run_kcl(
+        &self,
+        hostname: &str,
+        code: &str,
+        program: &kcl_lib::Program,
+        settings: kcl_lib::ExecutorSettings,
+    ) -> Result<(kcl_lib::ExecutorContext, Option<ModelingSessionData>)> {
+        let start = std::time::Instant::now();
+        let ctx = self
+            .connect_executor(hostname, settings)
+            .await
+            .map_err(EngineConnectError)?;
+        self.record_timing("connect", start);
+
+        let start = std::time::Instant::now();
+        let session_data = ctx
+            .run_with_session_data(program.clone().into(), &mut Default::default())
+            .await
+            .map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
+        self.record_timing("execute", start);
+
+        Ok((ctx, session_data))
+    }
+
+    /// Connect a new executor context to the engine.
+    async fn connect_executor(
+        &self,
+        hostname: &str,
+        settings: kcl_lib::ExecutorSettings,
+    ) -> Result<kcl_lib::ExecutorContext> {
+        if self.video_resolution.is_some() {
+            // The executor can't be asked for a resolution, so connect it ourselves.
+            let ws = self
+                .engine_ws(hostname, Some(&settings), settings.replay.clone())
+                .await?;
+            let engine = EngineConnection::new(ws).await?;
+            Ok(kcl_lib::ExecutorContext {
+                engine: std::sync::Arc::new(Box::new(engine)),
+                fs: Default::default(),
+                stdlib: Default::default(),
+                settings,
+                is_mock: false,
+            })
+        } else {
+            let client = self.engine_client(hostname)?;
+            kcl_lib::ExecutorContext::new(&client, settings).await
+        }
+    }
+
+    /// Execute an already parsed kcl program over a fresh engine connection and send the
+    /// modeling commands.
+    async fn execute_kcl_modeling_cmds(
+        &self,
+        hostname: &str,
+        code: &str,
+        program: &kcl_lib::Program,
+        setup_cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        settings: kcl_lib::ExecutorSettings,
+        zoom_padding: Option<f32>,
+    ) -> Result<(Vec<OkWebSocketResponseData>, Option<ModelingSessionData>)> {
+        let (ctx, session_data) = self.run_kcl(hostname, code, program, settings).await?;
+        let responses = self
+            .send_modeling_cmds_with_setup(&ctx, code, setup_cmds, cmds, zoom_padding)
+            .await?;
+        Ok((responses, session_data))
+    }
+
+    /// Send the `setup_cmds`, zoom to fit with `zoom_padding` around the model if it is
+    /// set, then send the `cmds`, over the engine connection of an executor context from
+    /// `execute_kcl`. The `code` is only used in error messages.
+    pub async fn send_modeling_cmds_with_setup(
+        &self,
+        ctx: &kcl_lib::ExecutorContext,
+        code: &str,
+        setup_cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        cmds: &[kittycad_modeling_cmds::ModelingCmd],
+        zoom_padding: Option<f32>,
+    ) -> Result<Vec<OkWebSocketResponseData>> {
+        let start = std::time::Instant::now();
+        for setup_cmd in setup_cmds {
+            ctx.engine
+                .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), setup_cmd.clone())
+                .await?;
+        }
+        if !setup_cmds.is_empty() {
+            self.record_timing("setup", start);
+        }
+
+        // Zoom on the object.
+        if let Some(padding) = zoom_padding {
+            let start = std::time::Instant::now();
+            ctx.engine
+                .send_modeling_cmd(
+                    uuid::Uuid::new_v4(),
+                    kcl_lib::SourceRange::default(),
+                    ModelingCmd::from(mcmd::ZoomToFit {
+                        animated: false,
+                        object_ids: Default::default(),
+                        padding,
+                    }),
+                )
+                .await?;
+            self.record_timing("zoom", start);
+        }
+
+        let mut responses = Vec::with_capacity(cmds.len());
+        for cmd in cmds {
+            let start = std::time::Instant::now();
+            let resp = ctx
+                .engine
+                .send_modeling_cmd(uuid::Uuid::new_v4(), kcl_lib::SourceRange::default(), cmd.clone())
+                .await
+                .map_err(|err| kcl_error_fmt::KclError::new(code.to_string(), err))?;
+            self.record_timing(&modeling_cmd_name(cmd), start);
+            responses.push(resp);
+        }
+        Ok(responses)
     }
 
     pub async fn get_model_for_prompt(
@@ -400,15 +814,61 @@ impl Context<'_> {
         Ok(())
     }
 
-    /// Return the configured output format or override the default with the value passed in,
-    /// if it is some.
+    /// Return the output format passed in, if it is some, then the one from
+    /// `ZOO_OUTPUT_FORMAT`, then the one from the config, then the default.
     pub fn format(&self, format: &Option<FormatOutput>) -> Result<FormatOutput> {
         if let Some(format) = format {
-            Ok(format.clone())
-        } else {
-            let value = self.config.get("", "format")?;
-            Ok(FormatOutput::from_str(&value).unwrap_or_default())
+            return Ok(format.clone());
+        }
+
+        let value = get_env_var("ZOO_OUTPUT_FORMAT");
+        if !value.is_empty() {
+            return FormatOutput::from_str(&value).map_err(|_| {
+                anyhow!(
+                    "invalid `ZOO_OUTPUT_FORMAT` `{}`, expected one of: {}",
+                    value,
+                    FormatOutput::variants().join(", ")
+                )
+            });
+        }
+
+        let value = self.config.get("", "format")?;
+        Ok(FormatOutput::from_str(&value).unwrap_or_default())
+    }
+
+    /// Return the prompt to send to the ML API, with the system instruction passed in, or
+    /// the `system_prompt` from the config if it is none, ahead of it.
+    pub fn ml_prompt(&self, system: &Option<String>, prompt: &str) -> String {
+        let system = match system {
+            Some(system) => system.clone(),
+            None => self.config.get("", "system_prompt").unwrap_or_default(),
+        };
+        crate::cmd_ml::with_system_instruction(&system, prompt)
+    }
+
+    /// Return the output unit passed in, or the default unit for the measurement from the
+    /// config if it is none.
+    pub fn output_unit<T: clap::ValueEnum + Clone>(
+        &self,
+        unit: &Option<T>,
+        measurement: crate::config::Measurement,
+    ) -> Result<T> {
+        if let Some(unit) = unit {
+            return Ok(unit.clone());
         }
+
+        let key = measurement.config_key();
+        let value = self.config.get("", key).unwrap_or_default();
+        if value.is_empty() {
+            anyhow::bail!(
+                "`--output-unit` is required, or set a default with `zoo config set-default-unit {} <unit>`",
+                clap::ValueEnum::to_possible_value(&measurement)
+                    .map(|v| v.get_name().to_string())
+                    .unwrap_or_default()
+            );
+        }
+
+        T::from_str(&value, true).map_err(|err| anyhow!("invalid `{}` in the config: {}", key, err))
     }
 
     /// Read the file at the given path and returns the contents.
@@ -421,8 +881,17 @@ impl Context<'_> {
         if filename == "-" {
             let mut buffer = Vec::new();
 
-            // Read everything from stdin.
-            self.io.stdin.read_to_end(&mut buffer)?;
+            // Read everything from stdin, but stop as soon as we know it is too big, rather
+            // than after it has all been read into memory.
+            (&mut self.io.stdin)
+                .take(self.max_file_size.saturating_add(1))
+                .read_to_end(&mut buffer)?;
+            if buffer.len() as u64 > self.max_file_size {
+                anyhow::bail!(
+                    "Standard input is larger than the maximum file size of {} bytes. Use `--max-file-size` to raise the limit.",
+                    self.max_file_size
+                );
+            }
 
             return Ok(buffer);
         }
@@ -431,8 +900,149 @@ impl Context<'_> {
             anyhow::bail!("File '{}' does not exist.", filename);
         }
 
+        let size = std::fs::metadata(filename)?.len();
+        if size > self.max_file_size {
+            anyhow::bail!(
+                "File '{}' is {} bytes, which is larger than the maximum file size of {} bytes. Use `--max-file-size` to raise the limit.",
+                filename,
+                size,
+                self.max_file_size
+            );
+        }
+
         std::fs::read(filename).map_err(Into::into)
     }
+
+    /// Download the file at an `http://` or `https://` URL and return the contents.
+    /// Like `read_file`, this stops at `max_file_size` bytes.
+    pub async fn read_url(&self, url: &str) -> Result<Vec<u8>> {
+        let too_large = || {
+            anyhow!(
+                "`{}` is larger than the maximum file size of {} bytes. Use `--max-file-size` to raise the limit.",
+                url,
+                self.max_file_size
+            )
+        };
+
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(std::time::Duration::from_secs(60))
+            .build()?;
+        let mut resp = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| anyhow!("could not download `{}`: {}", url, err))?;
+        if resp.content_length().is_some_and(|len| len > self.max_file_size) {
+            return Err(too_large());
+        }
+
+        // The length is only a hint, so count what actually comes back too.
+        let mut contents = Vec::new();
+        while let Some(chunk) = resp.chunk().await? {
+            contents.extend_from_slice(&chunk);
+            if contents.len() as u64 > self.max_file_size {
+                return Err(too_large());
+            }
+        }
+
+        Ok(contents)
+    }
+}
+
+/// The URL of the API at a host, like `https://api.zoo.dev` for `api.zoo.dev`.
+fn base_url(host: &str) -> String {
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else if host.starts_with("localhost") {
+        format!("http://{host}")
+    } else {
+        format!("https://{host}")
+    }
+}
+
+/// If an error is from a request or engine command taking too long.
+fn is_timeout(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(EngineConnectError(err)) = cause.downcast_ref::<EngineConnectError>() {
+            return is_timeout(err);
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout();
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return err.kind() == std::io::ErrorKind::TimedOut;
+        }
+        // The engine reports its timeouts as messages, like "Modeling command timed out".
+        cause.is::<tokio::time::error::Elapsed>() || cause.to_string().to_lowercase().contains("timed out")
+    })
+}
+
+/// An error connecting to the engine, as opposed to one running the kcl program.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+struct EngineConnectError(anyhow::Error);
+
+/// Whether an error is from connecting to the engine or losing the connection, which
+/// running again can fix.
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if cause.is::<EngineConnectError>() {
+            return true;
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_connect() || err.is_request();
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                err.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            );
+        }
+        // The engine connection dropping partway through a program comes back as an
+        // engine error, so all we have to go on is the message.
+        let message = cause.to_string().to_lowercase();
+        message.contains("websocket") || message.contains("connection closed")
+    })
+}
+
+/// How long to wait before the `attempt`th retry of a kcl program: exponential from half
+/// a second, but never more than 30 seconds.
+fn engine_backoff(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(500u64.saturating_mul(2u64.saturating_pow(attempt.saturating_sub(1))))
+        .min(std::time::Duration::from_secs(30))
+}
+
+/// Parse a file size like `2GB`, `512M` or `1024`.
+/// Units are powers of 1024, and a number without a unit is in bytes.
+pub fn parse_file_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(digits);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("invalid file size `{}`, expected a size like `2GB` or `512MB`", s))?;
+
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        "T" | "TB" | "TIB" => 1024 * 1024 * 1024 * 1024,
+        _ => anyhow::bail!(
+            "invalid file size unit `{}`, expected one of B, KB, MB, GB or TB",
+            unit.trim()
+        ),
+    };
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow!("file size `{}` is too large", s))
 }
 
 #[cfg(test)]
@@ -482,6 +1092,20 @@ mod test {
         want_terminal_width_override: i32,
     }
 
+    #[test]
+    fn test_timings_report() {
+        let timings = Timings::default();
+        timings.record("parse", std::time::Duration::from_millis(2));
+        timings.record("export", std::time::Duration::from_millis(30));
+
+        let mut out = Vec::new();
+        timings.write_report(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timings:\n  parse       2.00ms\n  export     30.00ms\n  total      32.00ms\n"
+        );
+    }
+
     #[test_context(TContext)]
     #[test]
     #[serial_test::serial]
@@ -587,4 +1211,153 @@ mod test {
             }
         }
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_format_from_env() {
+        let orig = std::env::var("ZOO_OUTPUT_FORMAT");
+
+        let mut config = crate::config::new_blank_config().unwrap();
+        let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+        c.set("", "format", Some("yaml")).unwrap();
+        let (io, stdout_path, _) = crate::iostreams::IoStreams::test();
+        let mut ctx = Context::new(&mut c);
+        ctx.io = io;
+
+        std::env::set_var("ZOO_OUTPUT_FORMAT", "json");
+        let format = ctx.format(&None);
+        let flag = ctx.format(&Some(FormatOutput::Table));
+        std::env::set_var("ZOO_OUTPUT_FORMAT", "xml");
+        let invalid = ctx.format(&None);
+        std::env::remove_var("ZOO_OUTPUT_FORMAT");
+        let config = ctx.format(&None);
+        if let Ok(val) = orig {
+            std::env::set_var("ZOO_OUTPUT_FORMAT", val);
+        }
+
+        // The flag wins over the env, which wins over the config.
+        let format = format.unwrap();
+        assert_eq!(format, FormatOutput::Json);
+        assert_eq!(flag.unwrap(), FormatOutput::Table);
+        assert_eq!(config.unwrap(), FormatOutput::Yaml);
+        assert!(invalid
+            .unwrap_err()
+            .to_string()
+            .contains("invalid `ZOO_OUTPUT_FORMAT` `xml`"));
+
+        #[derive(serde::Serialize, tabled::Tabled)]
+        struct Row {
+            name: String,
+        }
+        ctx.io
+            .write_output(
+                &format,
+                &Row {
+                    name: "gear".to_string(),
+                },
+            )
+            .unwrap();
+        drop(ctx);
+        let out: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(stdout_path).unwrap()).unwrap();
+        assert_eq!(out, serde_json::json!({ "name": "gear" }));
+    }
+
+    #[test]
+    fn test_is_timeout() {
+        assert!(is_timeout(&anyhow!("Modeling command timed out `zoom_to_fit`")));
+        assert!(is_timeout(&anyhow::Error::from(std::io::Error::from(
+            std::io::ErrorKind::TimedOut
+        ))));
+        assert!(is_timeout(
+            &anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut)).context("connecting")
+        ));
+        assert!(is_timeout(&anyhow::Error::from(EngineConnectError(
+            anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut))
+        ))));
+        assert!(!is_timeout(&anyhow!("syntax error")));
+        assert!(!is_timeout(&anyhow::Error::from(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+    }
+
+    #[test]
+    fn test_is_connection_error() {
+        assert!(is_connection_error(&anyhow::Error::from(EngineConnectError(anyhow!(
+            "failed to connect"
+        )))));
+        assert!(is_connection_error(&anyhow::Error::from(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+        assert!(is_connection_error(&anyhow!(
+            "Failed to send modeling command: WebSocket protocol error: Connection reset without closing handshake"
+        )));
+        assert!(!is_connection_error(&anyhow!("syntax error")));
+        assert!(!is_connection_error(&anyhow!(
+            "Modeling command failed: the sketch is not closed"
+        )));
+
+        // The connect error doesn't change how the error reads.
+        let err = anyhow::Error::from(EngineConnectError(anyhow!("no token for zoo.dev")));
+        assert_eq!(err.to_string(), "no token for zoo.dev");
+    }
+
+    #[test]
+    fn test_engine_backoff() {
+        assert_eq!(engine_backoff(1), std::time::Duration::from_millis(500));
+        assert_eq!(engine_backoff(3), std::time::Duration::from_secs(2));
+        assert_eq!(engine_backoff(10), std::time::Duration::from_secs(30));
+        assert_eq!(engine_backoff(u32::MAX), std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_parse_file_size() {
+        assert_eq!(parse_file_size("1024").unwrap(), 1024);
+        assert_eq!(parse_file_size("10B").unwrap(), 10);
+        assert_eq!(parse_file_size("2KB").unwrap(), 2048);
+        assert_eq!(parse_file_size("512mb").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_file_size("2G").unwrap(), DEFAULT_MAX_FILE_SIZE);
+        assert_eq!(parse_file_size(" 1 TiB ").unwrap(), 1024 * 1024 * 1024 * 1024);
+
+        assert!(parse_file_size("").is_err());
+        assert!(parse_file_size("GB").is_err());
+        assert!(parse_file_size("2PB").is_err());
+        assert!(parse_file_size("99999999999TB").is_err());
+    }
+
+    #[test]
+    fn test_notice() {
+        let mut config = crate::config::new_blank_config().unwrap();
+        let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+        let (io, _, stderr_path) = crate::iostreams::IoStreams::test();
+        let mut ctx = Context::test(&mut c, io);
+
+        // Notices are written as they happen, in order with everything else on stderr.
+        std::io::Write::write_all(&mut ctx.io.err_out, b"starting\n").unwrap();
+        ctx.notice("timed out, retrying (1/2): Modeling command timed out");
+        assert_eq!(
+            std::fs::read_to_string(&stderr_path).unwrap(),
+            "starting\ntimed out, retrying (1/2): Modeling command timed out\n"
+        );
+    }
+
+    #[test]
+    fn test_read_file_max_file_size() {
+        let mut config = crate::config::new_blank_config().unwrap();
+        let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+        let (mut io, _, _) = crate::iostreams::IoStreams::test();
+        io.stdin = Box::new(std::io::Cursor::new(vec![0u8; 100]));
+        let mut ctx = Context::test(&mut c, io);
+        ctx.max_file_size = 99;
+
+        let err = ctx.read_file("-").unwrap_err();
+        assert!(err.to_string().contains("maximum file size of 99 bytes"), "{err}");
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), vec![0u8; 100]).unwrap();
+        let err = ctx.read_file(file.path().to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("is 100 bytes"), "{err}");
+
+        ctx.max_file_size = 100;
+        assert_eq!(ctx.read_file(file.path().to_str().unwrap()).unwrap().len(), 100);
+    }
 }
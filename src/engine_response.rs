@@ -0,0 +1,173 @@
+//! Errors for when the engine answers a command with something other than what was asked
+//! for.
+//!
+//! Printing the whole response with `{:?}` can mean pages of bytes, so this names the kind
+//! of response that came back, pulls out anything that looks like an error message, and
+//! shortens the rest.
+
+/// Keys in a response whose values explain what went wrong.
+const DIAGNOSTIC_KEYS: &[&str] = &["error", "errors", "message", "messages", "diagnostics", "warnings"];
+
+/// The most of the response to show, in characters, when it has no diagnostics.
+const MAX_RESPONSE_LEN: usize = 300;
+
+/// Arrays longer than this are shown as their length, since they are most likely bytes.
+const MAX_ARRAY_LEN: usize = 8;
+
+/// Strings longer than this are cut short.
+const MAX_STRING_LEN: usize = 80;
+
+/// The error for an unexpected response, where `expected` describes what was asked for,
+/// like "a snapshot".
+pub fn unexpected(expected: &str, resp: &impl serde::Serialize) -> anyhow::Error {
+    let value = match serde_json::to_value(resp) {
+        Ok(value) => value,
+        Err(err) => return anyhow::anyhow!("Unexpected response from engine, expected {}: {}", expected, err),
+    };
+
+    let mut message = format!(
+        "Unexpected response from engine, expected {} but got `{}`",
+        expected,
+        kind(&value)
+    );
+
+    let mut diagnostics = Vec::new();
+    find_diagnostics(&value, &mut diagnostics);
+    if !diagnostics.is_empty() {
+        message.push_str(": ");
+        message.push_str(&diagnostics.join(", "));
+    } else if !value.is_null() {
+        let summary = summarize(&value).to_string();
+        message.push_str(": ");
+        message.push_str(&truncate(&summary, MAX_RESPONSE_LEN));
+    }
+
+    anyhow::anyhow!(message)
+}
+
+/// The kind of a response, from its `type` tags, like `modeling/volume`.
+fn kind(value: &serde_json::Value) -> String {
+    if value.is_null() {
+        return "nothing".to_string();
+    }
+
+    let mut kinds = Vec::new();
+    let mut value = Some(value);
+    while let Some(tag) = value.and_then(|value| value.get("type")).and_then(|tag| tag.as_str()) {
+        kinds.push(tag);
+        value = value
+            .and_then(|value| value.get("data"))
+            .and_then(|data| data.get("modeling_response"));
+    }
+
+    if kinds.is_empty() {
+        "unknown".to_string()
+    } else {
+        kinds.join("/")
+    }
+}
+
+/// Collect the diagnostic fields anywhere in the response, as `key: value`.
+fn find_diagnostics(value: &serde_json::Value, diagnostics: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if value.is_null() {
+                    continue;
+                }
+                if DIAGNOSTIC_KEYS.contains(&key.as_str()) {
+                    let value = match value {
+                        serde_json::Value::String(s) => truncate(s, MAX_RESPONSE_LEN),
+                        value => truncate(&summarize(value).to_string(), MAX_RESPONSE_LEN),
+                    };
+                    diagnostics.push(format!("{}: {}", key, value));
+                } else {
+                    find_diagnostics(value, diagnostics);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                find_diagnostics(value, diagnostics);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A copy of the response with long arrays and strings cut short.
+fn summarize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Array(values) if values.len() > MAX_ARRAY_LEN => {
+            serde_json::Value::String(format!("<{} items>", values.len()))
+        }
+        serde_json::Value::Array(values) => values.iter().map(summarize).collect(),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), summarize(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+        serde_json::Value::String(s) => serde_json::Value::String(truncate(s, MAX_STRING_LEN)),
+        value => value.clone(),
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    format!("{}...", s.chars().take(max).collect::<String>())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_unexpected_names_the_response() {
+        let resp = serde_json::json!({
+            "type": "modeling",
+            "data": {"modeling_response": {"type": "volume", "data": {"volume": 1.0, "output_unit": "cm3"}}},
+        });
+        let err = unexpected("a snapshot", &resp).to_string();
+        assert!(
+            err.starts_with("Unexpected response from engine, expected a snapshot but got `modeling/volume`: {"),
+            "{err}"
+        );
+        assert!(err.contains(r#""volume":1.0"#), "{err}");
+
+        let resp: Option<serde_json::Value> = None;
+        assert_eq!(
+            unexpected("a snapshot", &resp).to_string(),
+            "Unexpected response from engine, expected a snapshot but got `nothing`"
+        );
+    }
+
+    #[test]
+    fn test_unexpected_shows_diagnostics() {
+        let resp = serde_json::json!({
+            "type": "modeling",
+            "data": {"modeling_response": {"type": "other", "data": {"error": "no solids in scene", "message": null}}},
+        });
+        assert_eq!(
+            unexpected("a volume", &resp).to_string(),
+            "Unexpected response from engine, expected a volume but got `modeling/other`: error: no solids in scene"
+        );
+    }
+
+    #[test]
+    fn test_unexpected_shortens_payloads() {
+        let resp = serde_json::json!({
+            "type": "export",
+            "data": {"files": [{"name": "output.step", "contents": vec![0u8; 100_000]}]},
+        });
+        let err = unexpected("a snapshot", &resp).to_string();
+        assert!(
+            err.starts_with("Unexpected response from engine, expected a snapshot but got `export`: {"),
+            "{err}"
+        );
+        assert!(err.contains(r#""contents":"<100000 items>""#), "{err}");
+    }
+}
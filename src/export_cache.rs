@@ -0,0 +1,165 @@
+//! A cache of `zoo kcl export` results, so exporting a model that hasn't changed since
+//! the last run doesn't have to go through the engine again.
+//!
+//! Each entry is keyed by a hash of everything that can change the engine's output (see
+//! [`CacheKey`]) and holds the exported files along with a manifest of their checksums.
+//! An entry whose files don't match their checksums is treated as a miss.
+
+use anyhow::Result;
+
+/// The name of the manifest in each cache entry.
+const MANIFEST: &str = "manifest.json";
+
+/// Builds the key for a cache entry, by hashing the inputs to an export.
+pub struct CacheKey {
+    context: ring::digest::Context,
+}
+
+impl CacheKey {
+    /// Start a key. The version of the CLI is always part of it, since a new version
+    /// may send different commands to the engine.
+    pub fn new() -> Self {
+        let mut key = Self {
+            context: ring::digest::Context::new(&ring::digest::SHA256),
+        };
+        key.add("version", clap::crate_version!().as_bytes());
+        key
+    }
+
+    /// Add an input to the key. The label keeps inputs from running into each other,
+    /// for example an empty file followed by another one.
+    pub fn add(&mut self, label: &str, value: &[u8]) {
+        for part in [label.as_bytes(), value] {
+            self.context.update(&(part.len() as u64).to_le_bytes());
+            self.context.update(part);
+        }
+    }
+
+    /// The key, as a hex string.
+    pub fn finish(self) -> String {
+        data_encoding::HEXLOWER.encode(self.context.finish().as_ref())
+    }
+}
+
+impl Default for CacheKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A file in a cache entry's manifest.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct ManifestFile {
+    /// The name of the file, as the engine sent it.
+    name: String,
+    /// The SHA-256 checksum of the contents.
+    sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Manifest {
+    files: Vec<ManifestFile>,
+}
+
+/// The directory exports are cached in.
+pub fn dir() -> Result<std::path::PathBuf> {
+    Ok(std::path::Path::new(&crate::config_file::cache_dir()?).join("exports"))
+}
+
+/// Get the files cached for a key, if there are any and they are intact.
+pub fn lookup(dir: &std::path::Path, key: &str) -> Result<Option<Vec<(String, Vec<u8>)>>> {
+    let entry = dir.join(key);
+    let Ok(manifest) = std::fs::read(entry.join(MANIFEST)) else {
+        return Ok(None);
+    };
+    let Ok(manifest) = serde_json::from_slice::<Manifest>(&manifest) else {
+        return Ok(None);
+    };
+
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for file in manifest.files {
+        let Ok(contents) = std::fs::read(entry.join("files").join(&file.name)) else {
+            return Ok(None);
+        };
+        if sha256(&contents) != file.sha256 {
+            log::debug!("cached export `{}` does not match its checksum, ignoring it", file.name);
+            return Ok(None);
+        }
+        files.push((file.name, contents));
+    }
+
+    Ok(Some(files))
+}
+
+/// Save the files exported for a key, replacing any entry already there.
+pub fn store(dir: &std::path::Path, key: &str, files: &[(String, Vec<u8>)]) -> Result<()> {
+    let entry = dir.join(key);
+    if entry.exists() {
+        std::fs::remove_dir_all(&entry)?;
+    }
+    let files_dir = entry.join("files");
+    std::fs::create_dir_all(&files_dir)?;
+
+    let mut manifest = Manifest { files: Vec::new() };
+    for (name, contents) in files {
+        std::fs::write(files_dir.join(name), contents)?;
+        manifest.files.push(ManifestFile {
+            name: name.clone(),
+            sha256: sha256(contents),
+        });
+    }
+    // Write the manifest last, so an interrupted store is never mistaken for an entry.
+    std::fs::write(entry.join(MANIFEST), serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(())
+}
+
+fn sha256(contents: &[u8]) -> String {
+    data_encoding::HEXLOWER.encode(ring::digest::digest(&ring::digest::SHA256, contents).as_ref())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn key(inputs: &[(&str, &[u8])]) -> String {
+        let mut key = CacheKey::new();
+        for (label, value) in inputs {
+            key.add(label, value);
+        }
+        key.finish()
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let a = key(&[("file", b"main.kcl"), ("code", b"x = 1")]);
+        assert_eq!(a, key(&[("file", b"main.kcl"), ("code", b"x = 1")]));
+        assert_ne!(a, key(&[("file", b"main.kcl"), ("code", b"x = 2")]));
+        assert_ne!(a, key(&[("file", b"other.kcl"), ("code", b"x = 1")]));
+        // Moving bytes from one input to the next is a different key.
+        assert_ne!(
+            key(&[("code", b"ab"), ("code", b"c")]),
+            key(&[("code", b"a"), ("code", b"bc")])
+        );
+    }
+
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = vec![
+            ("output.gltf".to_string(), b"{}".to_vec()),
+            ("output.bin".to_string(), vec![0, 1, 2]),
+        ];
+
+        assert_eq!(lookup(dir.path(), "abc").unwrap(), None);
+        store(dir.path(), "abc", &files).unwrap();
+        assert_eq!(lookup(dir.path(), "abc").unwrap(), Some(files));
+        assert_eq!(lookup(dir.path(), "def").unwrap(), None);
+
+        // A file that was changed after it was cached is a miss.
+        std::fs::write(dir.path().join("abc").join("files").join("output.bin"), [3]).unwrap();
+        assert_eq!(lookup(dir.path(), "abc").unwrap(), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Printing only the examples from a command's help, for `--help-examples`.
+//!
+//! The examples are the indented blocks in the long help that have at least one
+//! `$ zoo ...` line, along with the `# comment` lines that explain them.
+
+use std::io::Write;
+
+use anyhow::Result;
+
+/// Find the subcommand the arguments name, like `kcl export` in
+/// `zoo kcl export --output-format=step my-file.kcl .`, returning it and its full name.
+/// Anything after the last subcommand is ignored.
+pub fn find_command<'a>(app: &'a clap::Command, args: &[String]) -> (&'a clap::Command, String) {
+    let mut cmd = app;
+    let mut name = app.get_name().to_string();
+    for arg in args {
+        if arg.starts_with('-') {
+            continue;
+        }
+        match cmd.find_subcommand(arg) {
+            Some(subcmd) => {
+                cmd = subcmd;
+                name = format!("{} {}", name, subcmd.get_name());
+            }
+            None => break,
+        }
+    }
+    (cmd, name)
+}
+
+/// Find `--help-examples` in the arguments, not counting the program name. It only
+/// counts as the flag before any `--`, and not where it is the value of an option
+/// before it, like in `zoo kcl export --var --help-examples`.
+pub fn flag_position(app: &clap::Command, args: &[String]) -> Option<usize> {
+    let takes_value = |option: &clap::Arg| {
+        option.get_action().takes_values() && !option.is_require_equals_set() && !option.is_positional()
+    };
+
+    let mut cmds = vec![app];
+    let mut args = args.iter().enumerate();
+    while let Some((i, arg)) = args.next() {
+        if arg == "--" {
+            return None;
+        }
+        if arg == "--help-examples" {
+            return Some(i);
+        }
+
+        let option = if let Some(long) = arg.strip_prefix("--") {
+            if long.contains('=') {
+                continue;
+            }
+            cmds.iter()
+                .rev()
+                .find_map(|cmd| cmd.get_arguments().find(|option| option.get_long() == Some(long)))
+        } else if let Some(short) = arg.strip_prefix('-') {
+            let mut chars = short.chars();
+            match (chars.next(), chars.next()) {
+                (Some(short), None) => cmds
+                    .iter()
+                    .rev()
+                    .find_map(|cmd| cmd.get_arguments().find(|option| option.get_short() == Some(short))),
+                _ => continue,
+            }
+        } else {
+            if let Some(subcmd) = cmds.last().copied().and_then(|cmd| cmd.find_subcommand(arg)) {
+                cmds.push(subcmd);
+            }
+            continue;
+        };
+
+        // Skip the value of an option, so it isn't mistaken for a flag or a subcommand.
+        if option.is_some_and(takes_value) {
+            args.next();
+        }
+    }
+    None
+}
+
+/// The example blocks in some help text, with their indentation removed.
+pub fn examples(help: &str) -> Vec<String> {
+    let mut blocks: Vec<Vec<&str>> = vec![];
+    let mut block: Vec<&str> = vec![];
+    for line in help.lines().chain(std::iter::once("")) {
+        if let Some(line) = line.strip_prefix("    ") {
+            block.push(line.trim_end());
+        } else if line.trim().is_empty() && !block.is_empty() {
+            blocks.push(std::mem::take(&mut block));
+        } else {
+            block.clear();
+        }
+    }
+
+    blocks
+        .into_iter()
+        .filter(|block| block.iter().any(|line| line.starts_with("$ ")))
+        .map(|block| block.join("\n"))
+        .collect()
+}
+
+/// Print the examples for the subcommand the arguments name.
+pub fn print(ctx: &mut crate::context::Context, app: &clap::Command, args: &[String]) -> Result<()> {
+    let (cmd, name) = find_command(app, args);
+    let help = cmd
+        .get_long_about()
+        .or_else(|| cmd.get_about())
+        .map(|help| help.to_string())
+        .unwrap_or_default();
+
+    let examples = examples(&help);
+    if examples.is_empty() {
+        anyhow::bail!("`{}` has no examples, see `{} --help`", name, name);
+    }
+    writeln!(ctx.io.out, "{}", examples.join("\n\n"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        let help = r#"Format a `kcl` file.
+
+    # Output to stdout by default
+    $ zoo kcl fmt my-file.kcl
+
+    # Overwrite the file
+    $ zoo kcl fmt -w my-file.kcl
+
+Some options:
+
+    --not-an-example
+    also not one
+
+The end.
+    $ zoo kcl fmt -"#;
+
+        assert_eq!(
+            examples(help),
+            vec![
+                "# Output to stdout by default\n$ zoo kcl fmt my-file.kcl",
+                "# Overwrite the file\n$ zoo kcl fmt -w my-file.kcl",
+                "$ zoo kcl fmt -",
+            ]
+        );
+        assert_eq!(examples("No examples here."), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_find_command() {
+        let app = clap::Command::new("zoo")
+            .subcommand(clap::Command::new("kcl").subcommand(clap::Command::new("fmt").visible_alias("format")));
+
+        let args = ["kcl", "--debug", "format", "my-file.kcl"].map(String::from);
+        let (cmd, name) = find_command(&app, &args);
+        assert_eq!(cmd.get_name(), "fmt");
+        assert_eq!(name, "zoo kcl fmt");
+
+        let (cmd, name) = find_command(&app, &[]);
+        assert_eq!(cmd.get_name(), "zoo");
+        assert_eq!(name, "zoo");
+    }
+
+    #[test]
+    fn test_flag_position() {
+        let app = clap::Command::new("zoo").subcommand(
+            clap::Command::new("kcl").subcommand(
+                clap::Command::new("export")
+                    .arg(clap::Arg::new("var").long("var").short('v'))
+                    .arg(clap::Arg::new("zip").long("zip").action(clap::ArgAction::SetTrue))
+                    .arg(clap::Arg::new("input")),
+            ),
+        );
+        let position = |args: &[&str]| flag_position(&app, &args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(position(&["kcl", "export", "--help-examples"]), Some(2));
+        assert_eq!(position(&["kcl", "export", "--zip", "--help-examples"]), Some(3));
+        assert_eq!(position(&["kcl", "export", "--var=a", "--help-examples"]), Some(3));
+        assert_eq!(position(&["kcl", "export", "--var", "--help-examples"]), None);
+        assert_eq!(position(&["kcl", "export", "-v", "--help-examples"]), None);
+        assert_eq!(position(&["kcl", "export", "--", "--help-examples"]), None);
+        assert_eq!(position(&["kcl", "export", "main.kcl"]), None);
+    }
+}
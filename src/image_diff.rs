@@ -0,0 +1,117 @@
+//! Compare rendered images against a baseline, for catching unintended changes to
+//! models in CI.
+
+use anyhow::Result;
+
+/// How far apart two channels of a pixel can be and still count as the same. Renders
+/// of the same model are not always bit for bit identical, for example around
+/// anti-aliased edges.
+const CHANNEL_TOLERANCE: u8 = 8;
+
+/// The result of comparing two images.
+#[derive(Debug)]
+pub struct ImageDiff {
+    /// The percentage of pixels that differ, from 0 to 100.
+    pub percent: f64,
+    /// The baseline, faded out, with the pixels that differ in red.
+    pub diff_image: image::RgbaImage,
+}
+
+impl ImageDiff {
+    /// Encode the diff image as a png.
+    pub fn diff_png(&self) -> Result<Vec<u8>> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(self.diff_image.clone()).write_to(&mut out, image::ImageFormat::Png)?;
+        Ok(out.into_inner())
+    }
+}
+
+/// Compare two PNG images pixel by pixel. They must be the same size.
+pub fn compare_png(actual: &[u8], baseline: &[u8]) -> Result<ImageDiff> {
+    let actual = image::load_from_memory_with_format(actual, image::ImageFormat::Png)
+        .map_err(|err| anyhow::anyhow!("failed to decode the snapshot as a png: {}", err))?
+        .to_rgba8();
+    let baseline = image::load_from_memory_with_format(baseline, image::ImageFormat::Png)
+        .map_err(|err| anyhow::anyhow!("failed to decode the baseline as a png: {}", err))?
+        .to_rgba8();
+
+    if actual.dimensions() != baseline.dimensions() {
+        anyhow::bail!(
+            "the snapshot is {}x{} but the baseline is {}x{}",
+            actual.width(),
+            actual.height(),
+            baseline.width(),
+            baseline.height()
+        );
+    }
+
+    let mut diff_image = image::RgbaImage::new(baseline.width(), baseline.height());
+    let mut different = 0u64;
+    for ((a, b), d) in actual.pixels().zip(baseline.pixels()).zip(diff_image.pixels_mut()) {
+        if a.0
+            .iter()
+            .zip(b.0.iter())
+            .any(|(x, y)| x.abs_diff(*y) > CHANNEL_TOLERANCE)
+        {
+            different += 1;
+            *d = image::Rgba([255, 0, 0, 255]);
+        } else {
+            // Fade the baseline out, so the differences stand out.
+            let gray = ((b[0] as u32 + b[1] as u32 + b[2] as u32) / 3 / 4 + 192) as u8;
+            *d = image::Rgba([gray, gray, gray, 255]);
+        }
+    }
+
+    let total = baseline.width() as u64 * baseline.height() as u64;
+    let percent = if total == 0 {
+        0.0
+    } else {
+        different as f64 / total as f64 * 100.0
+    };
+
+    Ok(ImageDiff { percent, diff_image })
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn png(img: image::RgbaImage) -> Vec<u8> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(img)
+            .write_to(&mut out, image::ImageFormat::Png)
+            .unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_compare_png() {
+        let baseline = image::RgbaImage::from_pixel(10, 10, image::Rgba([100, 100, 100, 255]));
+        let mut actual = baseline.clone();
+        // Within the tolerance.
+        actual.put_pixel(0, 0, image::Rgba([104, 100, 100, 255]));
+        // Different.
+        actual.put_pixel(9, 9, image::Rgba([200, 100, 100, 255]));
+        actual.put_pixel(5, 5, image::Rgba([100, 100, 100, 0]));
+
+        let diff = compare_png(&png(actual), &png(baseline.clone())).unwrap();
+        assert_eq!(diff.percent, 2.0);
+        assert_eq!(*diff.diff_image.get_pixel(9, 9), image::Rgba([255, 0, 0, 255]));
+        assert_eq!(*diff.diff_image.get_pixel(0, 0), image::Rgba([217, 217, 217, 255]));
+
+        let same = compare_png(&png(baseline.clone()), &png(baseline)).unwrap();
+        assert_eq!(same.percent, 0.0);
+    }
+
+    #[test]
+    fn test_compare_png_different_sizes() {
+        let a = png(image::RgbaImage::new(10, 10));
+        let b = png(image::RgbaImage::new(10, 11));
+        assert_eq!(
+            compare_png(&a, &b).unwrap_err().to_string(),
+            "the snapshot is 10x10 but the baseline is 10x11"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::io::IsTerminal;
+use std::io::{IsTerminal, Write};
 use std::{collections::HashMap, env, process::Command};
 
 use anyhow::{anyhow, Result};
@@ -34,6 +34,8 @@ pub struct IoStreams {
     pager_process: Option<std::process::Child>,
 
     never_prompt: bool,
+
+    columns: Vec<String>,
 }
 
 impl IoStreams {
@@ -173,6 +175,29 @@ impl IoStreams {
         Ok(())
     }
 
+    /// Flush stdout and stderr, so everything written to them is out before the process
+    /// exits. `std::process::exit` doesn't run destructors, so a writer that buffers could
+    /// otherwise lose the end of a large output, like an image piped to another program.
+    /// A reader that hung up early, like `head`, is not an error.
+    pub fn flush(&mut self) -> Result<()> {
+        for writer in [&mut self.out, &mut self.err_out] {
+            match writer.flush() {
+                Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Make stderr shared, and get a handle to it that can be written to without
+    /// borrowing the streams mutably, like from work running concurrently.
+    pub fn share_err_out(&mut self) -> SharedWriter {
+        let err_out = std::mem::replace(&mut self.err_out, Box::new(std::io::sink()));
+        let shared = SharedWriter(std::sync::Arc::new(std::sync::Mutex::new(err_out)));
+        self.err_out = Box::new(shared.clone());
+        shared
+    }
+
     pub fn can_prompt(&self) -> bool {
         if self.never_prompt {
             return false;
@@ -186,6 +211,12 @@ impl IoStreams {
         self.never_prompt
     }
 
+    /// Only show these columns in tables, in this order, instead of all of them.
+    /// The names are the headers of the table, matched without regard to case.
+    pub fn set_columns(&mut self, columns: Vec<String>) {
+        self.columns = columns;
+    }
+
     pub fn set_never_prompt(&mut self, never_prompt: bool) {
         self.never_prompt = never_prompt;
     }
@@ -273,9 +304,7 @@ impl IoStreams {
 
     #[allow(dead_code)]
     pub fn write_output_table_for_vec<T: tabled::Tabled>(&mut self, value: impl IntoIterator<Item = T>) -> Result<()> {
-        let table = tabled::Table::new(value)
-            .with(tabled::settings::Style::psql())
-            .to_string();
+        let table = self.table(value)?.with(tabled::settings::Style::psql()).to_string();
 
         writeln!(self.out, "{table}")?;
 
@@ -283,7 +312,8 @@ impl IoStreams {
     }
 
     pub fn write_output_table<T: tabled::Tabled>(&mut self, value: &T) -> Result<()> {
-        let table = tabled::Table::new(vec![value])
+        let table = self
+            .table(vec![value])?
             .with(tabled::settings::Rotate::Left)
             .with(
                 tabled::settings::Modify::new(tabled::settings::object::Segment::all())
@@ -298,6 +328,23 @@ impl IoStreams {
         Ok(())
     }
 
+    /// Build a table of the rows, with only the columns set with `set_columns`.
+    fn table<T: tabled::Tabled>(&self, rows: impl IntoIterator<Item = T>) -> Result<tabled::Table> {
+        if self.columns.is_empty() {
+            return Ok(tabled::Table::new(rows));
+        }
+
+        let headers = T::headers();
+        let indexes = select_columns(&headers, &self.columns)?;
+        let mut builder = tabled::builder::Builder::default();
+        builder.push_record(indexes.iter().map(|&i| headers[i].to_string()));
+        for row in rows {
+            let fields = row.fields();
+            builder.push_record(indexes.iter().map(|&i| fields[i].to_string()));
+        }
+        Ok(builder.build())
+    }
+
     pub fn system() -> Self {
         let stdout_is_tty = std::io::stdout().is_terminal();
         let stderr_is_tty = std::io::stderr().is_terminal();
@@ -347,6 +394,8 @@ impl IoStreams {
 
             pager_process: None,
             never_prompt: false,
+
+            columns: Vec::new(),
         };
 
         if stdout_is_tty && stderr_is_tty {
@@ -380,6 +429,55 @@ impl IoStreams {
     }
 }
 
+/// A writer that can be written to from several places at once, from
+/// `IoStreams::share_err_out`.
+#[derive(Clone)]
+pub struct SharedWriter(std::sync::Arc<std::sync::Mutex<Box<dyn std::io::Write + Send + Sync>>>);
+
+impl SharedWriter {
+    /// Write a line, ignoring errors, since there is nowhere else to report them.
+    pub fn write_line(&self, line: &str) {
+        if let Ok(mut writer) = self.0.lock() {
+            let _ = writeln!(writer, "{}", line);
+        }
+    }
+}
+
+impl std::io::Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("stderr lock poisoned"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("stderr lock poisoned"))?
+            .flush()
+    }
+}
+
+/// The indexes of the `columns` in the `headers` of a table, in the order of `columns`.
+fn select_columns(headers: &[std::borrow::Cow<'static, str>], columns: &[String]) -> Result<Vec<usize>> {
+    columns
+        .iter()
+        .map(|column| {
+            headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(column.trim()))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown column `{}`, the columns are: {}",
+                        column.trim(),
+                        headers.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 fn test_tty_size() -> Result<(i32, i32)> {
     Err(anyhow::anyhow!("tty_size not implemented in tests"))
@@ -468,4 +566,63 @@ mod test {
             assert_eq!(width, t.want_width, "test {}", t.name);
         }
     }
+
+    #[test]
+    fn test_flush_writes_large_output() {
+        let (mut io, stdout_path, _) = IoStreams::test();
+        // Bigger than any pipe or file buffer, like a large snapshot or export.
+        let contents: Vec<u8> = (0..16 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+        io.out.write_all(&contents).unwrap();
+        io.flush().unwrap();
+
+        let written = std::fs::read(stdout_path).unwrap();
+        assert_eq!(written.len(), contents.len());
+        assert!(written == contents, "the output was not written in full");
+    }
+
+    #[derive(tabled::Tabled)]
+    struct Row {
+        id: u32,
+        name: String,
+        created_at: String,
+    }
+
+    #[test]
+    fn test_write_output_table_columns() {
+        let (mut io, stdout_path, _) = IoStreams::test();
+        io.set_columns(vec!["Created_At".to_string(), "id".to_string()]);
+        let rows = vec![
+            Row {
+                id: 1,
+                name: "gear".to_string(),
+                created_at: "2024-01-01".to_string(),
+            },
+            Row {
+                id: 2,
+                name: "bracket".to_string(),
+                created_at: "2024-01-02".to_string(),
+            },
+        ];
+        io.write_output_table_for_vec(rows).unwrap();
+
+        let stdout = std::fs::read_to_string(stdout_path).unwrap();
+        let lines = stdout.lines().map(|line| line.trim_end()).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                " created_at | id",
+                "------------+----",
+                " 2024-01-01 | 1",
+                " 2024-01-02 | 2",
+            ]
+        );
+
+        io.set_columns(vec!["id".to_string(), "size".to_string()]);
+        let err = io.write_output_table_for_vec(Vec::<Row>::new()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown column `size`, the columns are: id, name, created_at"
+        );
+    }
 }
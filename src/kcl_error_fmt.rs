@@ -12,6 +12,27 @@ const SEPARATOR: &str = " | ";
 /// Ellipse used to indicated if a long line has been contextualized.
 const ELLIPSE: &str = "...";
 
+/// Suggestions for common mistakes, printed under the error. A hint applies when the
+/// error message contains all of its patterns, ignoring case. The first match wins.
+const HINTS: &[(&[&str], &str)] = &[
+    (
+        &["sketchsurface"],
+        "sketches are drawn on a plane or face, start them with `startSketchOn('XY')` before `startProfileAt`",
+    ),
+    (
+        &["unit", "mismatch"],
+        "the units of your values may not match, set the project units in `project.toml` or with `--src-unit`",
+    ),
+    (
+        &["is not defined"],
+        "check the spelling of the name, and that it is declared before it is used",
+    ),
+    (
+        &["unexpected end of file"],
+        "check for a missing closing bracket, brace or parenthesis",
+    ),
+];
+
 /// Struct for formatting the error together with the source file to give a
 /// nicer output.
 #[derive(Debug)]
@@ -19,6 +40,7 @@ pub struct KclError {
     input: String,
     message: String,
     source_ranges: Vec<SourceRange>,
+    hint: Option<&'static str>,
     contextualize: bool,
     context_lines: usize,
     context_characters: usize,
@@ -55,10 +77,13 @@ impl KclError {
             ErrorTypes::Kcl(err) => (err.get_message().to_owned(), err.source_ranges()),
         };
 
+        let hint = hint_for(&message);
+
         Self {
             input,
             message,
             source_ranges,
+            hint,
             // If the output should be contextualized or not.
             contextualize: true,
             // Amount of lines to show before and after the line containing the error.
@@ -112,6 +137,17 @@ impl KclError {
     }
 
     fn format(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.format_error(f)?;
+
+        // Keep the original error intact, and add the hint below it.
+        if let Some(hint) = self.hint {
+            writeln!(f, "{} {}", "hint:".green().bold(), hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn format_error(&self, f: &mut fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         // If line and column are not set we assume that we can't make a nice output
         // so we will just print the original message in red and bold
         let plain_error_msg = self.message.red().bold();
@@ -360,3 +396,53 @@ impl KclError {
         (minimized_input, new_error_column, context_before, context_after)
     }
 }
+
+/// Find the hint for an error message, if it matches one of the common mistakes in
+/// [`HINTS`].
+fn hint_for(message: &str) -> Option<&'static str> {
+    let message = message.to_lowercase();
+    HINTS
+        .iter()
+        .find(|(patterns, _)| patterns.iter().all(|p| message.contains(p)))
+        .map(|(_, hint)| *hint)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_hint_for() {
+        assert_eq!(
+            hint_for("Expected a SketchSurface for the first argument of startProfileAt"),
+            Some(HINTS[0].1)
+        );
+        assert_eq!(hint_for("Memory item key `width` is not defined"), Some(HINTS[2].1));
+        assert_eq!(
+            hint_for("Unexpected end of file. The compiler expected )"),
+            Some(HINTS[3].1)
+        );
+        assert_eq!(hint_for("Unit mismatch between mm and in"), Some(HINTS[1].1));
+        assert_eq!(hint_for("something else went wrong"), None);
+    }
+
+    #[test]
+    fn test_hint_is_below_the_error() {
+        let err = KclError {
+            input: "".to_string(),
+            message: "Memory item key `width` is not defined".to_string(),
+            source_ranges: vec![],
+            hint: hint_for("Memory item key `width` is not defined"),
+            contextualize: true,
+            context_lines: 3,
+            context_characters: 30,
+        };
+
+        let out = err.to_string();
+        let error_at = out.find("Memory item key `width` is not defined").unwrap();
+        let hint_at = out.find("check the spelling of the name").unwrap();
+        assert!(error_at < hint_at);
+    }
+}
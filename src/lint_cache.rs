@@ -0,0 +1,82 @@
+//! A cache of `zoo kcl lint` findings, so linting a file that hasn't changed since the
+//! last run doesn't have to parse and lint it again.
+//!
+//! Each entry is keyed by a hash of the code, the version of the CLI and the version of
+//! kcl-lib, and holds every finding for the code. Rules are selected after the cache, so
+//! changing `--select` or `--ignore` doesn't invalidate it.
+
+use anyhow::Result;
+
+use crate::cmd_kcl::LintFinding;
+
+/// The directory lint findings are cached in.
+pub fn dir() -> Result<std::path::PathBuf> {
+    Ok(std::path::Path::new(&crate::config_file::cache_dir()?).join("lint"))
+}
+
+/// The key for the findings of some code.
+pub fn key(code: &str) -> String {
+    let mut key = crate::export_cache::CacheKey::new();
+    key.add("kcl-lib", env!("KCL_LIB_VERSION").as_bytes());
+    key.add("code", code.as_bytes());
+    key.finish()
+}
+
+/// Get the findings cached for a key, if there are any.
+pub fn lookup(dir: &std::path::Path, key: &str) -> Option<Vec<LintFinding>> {
+    let entry = std::fs::read(dir.join(format!("{}.json", key))).ok()?;
+    serde_json::from_slice(&entry).ok()
+}
+
+/// Save the findings for a key, replacing any entry already there.
+pub fn store(dir: &std::path::Path, key: &str, findings: &[LintFinding]) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    // Write to a temporary file first, so an interrupted store is never mistaken for an
+    // entry.
+    let tmp = dir.join(format!("{}.json.tmp", key));
+    std::fs::write(&tmp, serde_json::to_vec(findings)?)?;
+    std::fs::rename(tmp, dir.join(format!("{}.json", key)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(key("x = 1"), key("x = 1"));
+        assert_ne!(key("x = 1"), key("x = 2"));
+    }
+
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let findings = vec![LintFinding {
+            path: "main.kcl".to_string(),
+            line: 1,
+            column: 1,
+            end_line: 1,
+            end_column: 4,
+            code: "Z0001".to_string(),
+            title: "Identifiers must be lowerCamelCase".to_string(),
+            description: "Use lowerCamelCase.".to_string(),
+        }];
+
+        assert_eq!(lookup(dir.path(), "abc"), None);
+        store(dir.path(), "abc", &findings).unwrap();
+        assert_eq!(lookup(dir.path(), "abc"), Some(findings));
+        assert_eq!(lookup(dir.path(), "def"), None);
+
+        // A broken entry is a miss.
+        std::fs::write(dir.path().join("abc.json"), "[").unwrap();
+        assert_eq!(lookup(dir.path(), "abc"), None);
+
+        // No findings is still an entry.
+        store(dir.path(), "abc", &[]).unwrap();
+        assert_eq!(lookup(dir.path(), "abc"), Some(vec![]));
+    }
+}
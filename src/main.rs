@@ -19,6 +19,8 @@ pub mod cmd_auth;
 pub mod cmd_completion;
 /// The config command.
 pub mod cmd_config;
+/// The doctor command.
+pub mod cmd_doctor;
 /// The drake command.
 pub mod cmd_drake;
 /// The file command.
@@ -50,6 +52,8 @@ mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
 
+mod annotate;
+mod api_validate;
 mod colors;
 mod config;
 mod config_alias;
@@ -59,8 +63,18 @@ mod config_from_file;
 mod config_map;
 mod context;
 mod docs_markdown;
+mod engine_response;
+mod export_cache;
+mod help_examples;
+mod image_diff;
 mod iostreams;
+mod lint_cache;
+#[cfg(feature = "cloud-export")]
+mod object_storage;
+mod png_metadata;
+mod strip_metadata;
 mod types;
+mod units;
 
 #[cfg(test)]
 mod tests;
@@ -70,7 +84,7 @@ mod update;
 use std::io::{Read, Write};
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use slog::Drain;
 
 /// The default host for the Zoo API.
@@ -86,6 +100,9 @@ pub const DEFAULT_HOST: &str = "https://api.zoo.dev";
 /// avoids being prompted to authenticate and takes precedence over previously
 /// stored credentials.
 ///
+/// ZOO_TOKEN_FILE: the path to a file containing an authentication token, used when
+/// ZOO_TOKEN is not set. This is useful with secret managers that mount secrets as files.
+///
 /// ZOO_HOST: specify the Zoo hostname for commands that would otherwise assume
 /// the "api.zoo.dev" host.
 ///
@@ -111,7 +128,21 @@ pub const DEFAULT_HOST: &str = "https://api.zoo.dev";
 ///
 /// ZOO_NO_UPDATE_NOTIFIER: set to any value to disable update notifications. By
 /// default, `zoo` checks for new releases once every 24 hours and displays an upgrade
-/// notice on standard error if a newer version was found.
+/// notice on standard error if a newer version was found. To skip the check for a
+/// single invocation, pass `--no-update-check`.
+///
+/// ZOO_ENGINE_RETRIES: how many times to reconnect to the engine and re-run a kcl
+/// program if the websocket connection drops. Same as `--engine-retries`.
+///
+/// ZOO_ENGINE_HOST: connect to the engine on this host instead of the API host.
+/// Same as `--engine-host`.
+///
+/// ZOO_MAX_FILE_SIZE: the largest input file `zoo` will read into memory, like `2GB`
+/// or `512MB`. Same as `--max-file-size`.
+///
+/// ZOO_OUTPUT_FORMAT: the output format, `table`, `json` or `yaml`, for commands that
+/// take `--format`. The flag takes precedence over this, which takes precedence over
+/// the `format` set with `zoo config set format`.
 ///
 /// ZOO_CONFIG_DIR: the directory where `zoo` will store configuration files.
 /// Default: `$XDG_CONFIG_HOME/zoo` or `$HOME/.config/zoo`.
@@ -122,6 +153,44 @@ struct Opts {
     #[clap(short, long, global = true, env)]
     debug: bool,
 
+    /// How many times to reconnect to the engine and re-run a kcl program if the
+    /// websocket connection drops. Retries back off exponentially.
+    #[clap(long, global = true, env = "ZOO_ENGINE_RETRIES", default_value = "0")]
+    engine_retries: u32,
+
+    /// Connect to the engine on this host instead of the API host, for example to
+    /// compare engine versions. Authentication still uses the API host.
+    #[clap(long, global = true, env = "ZOO_ENGINE_HOST")]
+    engine_host: Option<String>,
+
+    /// The largest input file to read into memory, like `2GB` or `512MB`.
+    /// Reading a bigger file, or more than this from stdin, is an error.
+    #[clap(
+        long,
+        global = true,
+        env = "ZOO_MAX_FILE_SIZE",
+        default_value = "2GB",
+        value_parser = crate::context::parse_file_size
+    )]
+    max_file_size: u64,
+
+    /// Only show these columns in table output, in this order, like `id,name,created_at`.
+    /// The names are the headers of the table. This does not change json or yaml output.
+    #[clap(long, global = true, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// Don't check for a newer release of zoo, not even in the background.
+    /// This means no request is made to GitHub.
+    #[clap(long, global = true)]
+    no_update_check: bool,
+
+    /// Print only the examples from the help of a command, as a cheat sheet.
+    // This is handled before the arguments are parsed, since the arguments of the
+    // command are usually incomplete, so it is never read here.
+    #[allow(dead_code)]
+    #[clap(long, global = true)]
+    help_examples: bool,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -136,6 +205,7 @@ enum SubCommand {
     Auth(cmd_auth::CmdAuth),
     Completion(cmd_completion::CmdCompletion),
     Config(cmd_config::CmdConfig),
+    Doctor(cmd_doctor::CmdDoctor),
     Drake(cmd_drake::CmdDrake),
     File(cmd_file::CmdFile),
     Generate(cmd_generate::CmdGenerate),
@@ -154,10 +224,14 @@ enum SubCommand {
 #[tokio::main]
 async fn main() -> Result<(), ()> {
     let build_version = clap::crate_version!();
-    // Check for updates to the cli.
-    // We don't await here since we don't want to block the main thread.
-    // We'll check again before we exit.
-    let update = crate::update::check_for_update(build_version, false);
+    // Check for updates to the cli in the background while the command runs.
+    // `--no-update-check` is looked for before the arguments are parsed, so with it the
+    // check is never started and no request is made.
+    let update = if update_check_disabled(std::env::args()) {
+        None
+    } else {
+        Some(tokio::spawn(crate::update::check_for_update(build_version, false)))
+    };
 
     // Let's get our configuration.
     let mut c = crate::config_file::parse_default_config().unwrap();
@@ -168,8 +242,22 @@ async fn main() -> Result<(), ()> {
     let args: Vec<String> = std::env::args().collect();
     let result = do_main(args, &mut ctx).await;
 
-    // If we have an update, let's print it.
-    handle_update(&mut ctx, update.await.unwrap_or_default(), build_version).unwrap();
+    // If we have an update, let's print it. An alias can also turn the check off, and
+    // then it is stopped instead.
+    if let Some(update) = update {
+        if ctx.no_update_check {
+            update.abort();
+        } else {
+            let update = update.await.ok().and_then(|update| update.ok()).flatten();
+            handle_update(&mut ctx, update, build_version).unwrap();
+        }
+    }
+
+    // `exit` doesn't flush anything for us, so make sure all the output made it out first.
+    if let Err(err) = ctx.io.flush() {
+        eprintln!("failed to write output: {err}");
+        std::process::exit(1);
+    }
 
     if let Err(err) = result {
         eprintln!("{err}");
@@ -179,6 +267,11 @@ async fn main() -> Result<(), ()> {
     std::process::exit(result.unwrap_or(0));
 }
 
+/// If `--no-update-check` was passed, before any `--` that ends the options.
+fn update_check_disabled(args: impl Iterator<Item = String>) -> bool {
+    args.take_while(|arg| arg != "--").any(|arg| arg == "--no-update-check")
+}
+
 async fn do_main(mut args: Vec<String>, ctx: &mut crate::context::Context<'_>) -> Result<i32> {
     let original_args = args.clone();
 
@@ -230,11 +323,24 @@ async fn do_main(mut args: Vec<String>, ctx: &mut crate::context::Context<'_>) -
         args = original_args;
     }
 
+    // Print only the examples for a command, before parsing the arguments, since the
+    // command's required arguments are usually missing.
+    if let Some(i) = crate::help_examples::flag_position(&Opts::command(), &args[1..]) {
+        args.remove(i + 1);
+        crate::help_examples::print(ctx, &Opts::command(), &args[1..])?;
+        return Ok(0);
+    }
+
     // Parse the command line arguments.
     let opts: Opts = Opts::parse_from(args);
 
     // Set our debug flag.
     ctx.debug = opts.debug;
+    ctx.engine_retries = opts.engine_retries;
+    ctx.max_file_size = opts.max_file_size;
+    ctx.engine_host = opts.engine_host;
+    ctx.no_update_check = opts.no_update_check;
+    ctx.io.set_columns(opts.columns.clone());
 
     // Setup our logger. This is mainly for debug purposes.
     // And getting debug logs from other libraries we consume, like even Zoo.
@@ -259,6 +365,7 @@ async fn do_main(mut args: Vec<String>, ctx: &mut crate::context::Context<'_>) -
         SubCommand::Auth(cmd) => run_cmd(&cmd, ctx).await,
         SubCommand::Completion(cmd) => run_cmd(&cmd, ctx).await,
         SubCommand::Config(cmd) => run_cmd(&cmd, ctx).await,
+        SubCommand::Doctor(cmd) => run_cmd(&cmd, ctx).await,
         SubCommand::Drake(cmd) => run_cmd(&cmd, ctx).await,
         SubCommand::File(cmd) => run_cmd(&cmd, ctx).await,
         SubCommand::Generate(cmd) => run_cmd(&cmd, ctx).await,
@@ -277,6 +384,11 @@ async fn run_cmd(cmd: &impl crate::cmd::Command, ctx: &mut context::Context<'_>)
     let cs = ctx.io.color_scheme();
 
     if let Err(err) = cmd.run(ctx).await {
+        // The command already printed what it wanted to, it just wants a specific exit code.
+        if let Some(crate::cmd::ExitCode(code)) = err.downcast_ref::<crate::cmd::ExitCode>() {
+            return Ok(*code);
+        }
+
         // If the error was from the API, let's handle it better for each type of error.
         match err.downcast::<kittycad::types::error::Error>() {
             Ok(err) => {
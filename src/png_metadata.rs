@@ -0,0 +1,128 @@
+//! Add text metadata to PNG images, so a saved snapshot can be traced back to the file
+//! it was rendered from.
+//!
+//! The text goes in `iTXt` chunks, which hold UTF-8, right after the header. Nothing
+//! else in the image is touched, so this doesn't need to decode or re-encode it.
+
+use anyhow::Result;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Add a text chunk for each `(keyword, text)` pair to a PNG image.
+/// Keywords must be 1 to 79 printable Latin-1 characters, like `Software`.
+pub fn add_text(contents: &[u8], entries: &[(&str, &str)]) -> Result<Vec<u8>> {
+    if !contents.starts_with(SIGNATURE) {
+        anyhow::bail!("the snapshot is not a png");
+    }
+    // The header is always the first chunk: its length, type, data and crc.
+    let header_len = contents
+        .get(8..12)
+        .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+        .filter(|_| contents.get(12..16) == Some(&b"IHDR"[..]))
+        .ok_or_else(|| anyhow::anyhow!("the snapshot is not a valid png"))?;
+    let header_end = SIGNATURE.len() + 12 + header_len;
+    if contents.len() < header_end {
+        anyhow::bail!("the snapshot is not a valid png");
+    }
+
+    let mut out = Vec::with_capacity(contents.len() + 64 * entries.len());
+    out.extend_from_slice(&contents[..header_end]);
+    for (keyword, text) in entries {
+        if keyword.is_empty() || keyword.len() > 79 || !keyword.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+            anyhow::bail!("`{}` is not a valid png text keyword", keyword);
+        }
+        // Not compressed, with no language or translated keyword.
+        let mut data = keyword.as_bytes().to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(text.as_bytes());
+        write_chunk(&mut out, b"iTXt", &data);
+    }
+    out.extend_from_slice(&contents[header_end..]);
+
+    Ok(out)
+}
+
+fn write_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(chunk_type);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// The CRC-32 png chunks end with, over their type and data.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// Read back the `iTXt` chunks of a PNG image, checking every crc on the way.
+    fn read_text(contents: &[u8]) -> Vec<(String, String)> {
+        let mut text = Vec::new();
+        let mut rest = &contents[SIGNATURE.len()..];
+        while rest.len() >= 12 {
+            let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            let (chunk_type, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + len]));
+            if chunk_type == b"iTXt" {
+                let keyword_end = data.iter().position(|&b| b == 0).unwrap();
+                text.push((
+                    String::from_utf8(data[..keyword_end].to_vec()).unwrap(),
+                    String::from_utf8(data[keyword_end + 5..].to_vec()).unwrap(),
+                ));
+            }
+            rest = &rest[12 + len..];
+        }
+        text
+    }
+
+    fn png() -> Vec<u8> {
+        let mut out = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(2, 2))
+            .write_to(&mut out, image::ImageFormat::Png)
+            .unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_add_text() {
+        let png = add_text(&png(), &[("Source", "parts/gear ⚙.kcl"), ("Software", "zoo 1.0.0")]).unwrap();
+        assert_eq!(
+            read_text(&png),
+            vec![
+                ("Source".to_string(), "parts/gear ⚙.kcl".to_string()),
+                ("Software".to_string(), "zoo 1.0.0".to_string()),
+            ]
+        );
+
+        // The image is still the same image.
+        let img = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((img.width(), img.height()), (2, 2));
+
+        assert!(add_text(&png, &[("", "empty")]).is_err());
+        assert!(add_text(b"GIF89a", &[("Source", "gear.kcl")]).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! Remove identifying metadata from exported files, so they can be shared without
+//! leaking details about who made them, or on what.
+//!
+//! Which fields are scrubbed depends on the format, which we get from the file name.
+//! Formats we don't know how to scrub are left as they are.
+
+use anyhow::Result;
+
+/// Remove the identifying metadata from the contents of an exported file.
+pub fn strip_metadata(file_name: &str, contents: &[u8]) -> Result<Vec<u8>> {
+    let ext = std::path::Path::new(file_name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "gltf" => strip_gltf(contents),
+        "glb" => strip_glb(contents),
+        "step" | "stp" => Ok(strip_step(contents)),
+        "obj" => Ok(strip_obj(contents)),
+        "ply" => Ok(strip_ply(contents)),
+        "stl" => Ok(strip_stl(contents)),
+        _ => Ok(contents.to_vec()),
+    }
+}
+
+/// Remove the generator and copyright from the glTF `asset`.
+fn strip_gltf_json(json: &[u8]) -> Result<serde_json::Value> {
+    let mut value: serde_json::Value = serde_json::from_slice(json)?;
+    if let Some(asset) = value.get_mut("asset").and_then(|asset| asset.as_object_mut()) {
+        asset.remove("generator");
+        asset.remove("copyright");
+        asset.remove("extras");
+    }
+    Ok(value)
+}
+
+fn strip_gltf(contents: &[u8]) -> Result<Vec<u8>> {
+    // We export glTF pretty printed, so keep it that way.
+    let mut out = serde_json::to_vec_pretty(&strip_gltf_json(contents)?)?;
+    if contents.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+/// A binary glTF is a header followed by chunks. The first chunk is the JSON, which
+/// we rewrite, and the rest are copied as they are.
+/// See <https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#binary-gltf-layout>.
+fn strip_glb(contents: &[u8]) -> Result<Vec<u8>> {
+    const HEADER_LEN: usize = 12;
+    const CHUNK_HEADER_LEN: usize = 8;
+
+    let read_u32 = |at: usize| -> Result<usize> {
+        let bytes = contents
+            .get(at..at + 4)
+            .ok_or_else(|| anyhow::anyhow!("the glb file is truncated"))?;
+        Ok(u32::from_le_bytes(bytes.try_into()?) as usize)
+    };
+
+    if !contents.starts_with(b"glTF") || contents.get(HEADER_LEN + 4..HEADER_LEN + 8) != Some(b"JSON".as_slice()) {
+        anyhow::bail!("the glb file does not start with a glTF header and JSON chunk");
+    }
+
+    let json_len = read_u32(HEADER_LEN)?;
+    let json_start = HEADER_LEN + CHUNK_HEADER_LEN;
+    let json = contents
+        .get(json_start..json_start + json_len)
+        .ok_or_else(|| anyhow::anyhow!("the glb file is truncated"))?;
+    let rest = &contents[json_start + json_len..];
+
+    let mut json = serde_json::to_vec(&strip_gltf_json(json)?)?;
+    // Chunks are padded to 4 bytes, with spaces for the JSON chunk.
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+
+    let total_len = HEADER_LEN + CHUNK_HEADER_LEN + json.len() + rest.len();
+    let mut out = Vec::with_capacity(total_len);
+    out.extend_from_slice(&contents[..8]);
+    out.extend_from_slice(&(total_len as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+    out.extend_from_slice(rest);
+    Ok(out)
+}
+
+/// Blank the author, organization, preprocessor, originating system and authorization
+/// in the STEP `FILE_NAME` header. The name and timestamp are kept.
+fn strip_step(contents: &[u8]) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(contents) else {
+        return contents.to_vec();
+    };
+    let Some(start) = text.find("FILE_NAME(") else {
+        return contents.to_vec();
+    };
+    let args_start = start + "FILE_NAME(".len();
+
+    // Split the arguments on the top level commas, skipping over quoted strings (where
+    // a quote is escaped by doubling it) and nested lists.
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut arg_start = args_start;
+    let mut end = None;
+    for (i, c) in text[args_start..].char_indices() {
+        let i = args_start + i;
+        match c {
+            '\'' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                args.push(&text[arg_start..i]);
+                end = Some(i);
+                break;
+            }
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(&text[arg_start..i]);
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let Some(end) = end else {
+        return contents.to_vec();
+    };
+
+    let args: Vec<String> = args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            if i < 2 {
+                arg.to_string()
+            } else if arg.trim().starts_with('(') {
+                "('')".to_string()
+            } else {
+                "''".to_string()
+            }
+        })
+        .collect();
+
+    format!("{}{}{}", &text[..args_start], args.join(","), &text[end..]).into_bytes()
+}
+
+/// Remove the comments, which is where OBJ exporters put their name and version.
+fn strip_obj(contents: &[u8]) -> Vec<u8> {
+    let Ok(text) = std::str::from_utf8(contents) else {
+        return contents.to_vec();
+    };
+    text.split_inclusive('\n')
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Remove the `comment` and `obj_info` lines from the PLY header. The body may be
+/// binary, so it is copied as it is.
+fn strip_ply(contents: &[u8]) -> Vec<u8> {
+    const END_HEADER: &[u8] = b"end_header";
+    let Some(header_end) = contents.windows(END_HEADER.len()).position(|w| w == END_HEADER) else {
+        return contents.to_vec();
+    };
+    let Ok(header) = std::str::from_utf8(&contents[..header_end]) else {
+        return contents.to_vec();
+    };
+
+    let mut out: Vec<u8> = header
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with("comment") && !line.starts_with("obj_info"))
+        .collect::<String>()
+        .into_bytes();
+    out.extend_from_slice(&contents[header_end..]);
+    out
+}
+
+/// Blank the 80 byte header of a binary STL, or the name of an ASCII STL solid.
+fn strip_stl(contents: &[u8]) -> Vec<u8> {
+    if let Ok(text) = std::str::from_utf8(contents) {
+        if text.starts_with("solid") {
+            return text
+                .split_inclusive('\n')
+                .map(|line| {
+                    let trimmed = line.trim_start();
+                    let newline = if line.ends_with('\n') { "\n" } else { "" };
+                    if trimmed.starts_with("solid") {
+                        format!("solid{}", newline)
+                    } else if trimmed.starts_with("endsolid") {
+                        format!("endsolid{}", newline)
+                    } else {
+                        line.to_string()
+                    }
+                })
+                .collect::<String>()
+                .into_bytes();
+        }
+    }
+
+    let mut out = contents.to_vec();
+    if out.len() >= 84 {
+        out[..80].fill(0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_strip_gltf() {
+        let gltf = br#"{"asset": {"version": "2.0", "generator": "zoo engine 1.2.3", "copyright": "me"}, "nodes": []}"#;
+        let out = strip_metadata("output.gltf", gltf).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value, serde_json::json!({"asset": {"version": "2.0"}, "nodes": []}));
+    }
+
+    #[test]
+    fn test_strip_glb() {
+        let json = br#"{"asset":{"version":"2.0","generator":"zoo engine 1.2.3"}}"#;
+        let mut padded = json.to_vec();
+        while padded.len() % 4 != 0 {
+            padded.push(b' ');
+        }
+        let bin = [1u8, 2, 3, 4];
+        let total = 12 + 8 + padded.len() + 8 + bin.len();
+
+        let mut glb = Vec::new();
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(padded.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&padded);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(&bin);
+
+        let out = strip_metadata("output.glb", &glb).unwrap();
+        assert!(!String::from_utf8_lossy(&out).contains("generator"));
+        assert_eq!(u32::from_le_bytes(out[8..12].try_into().unwrap()) as usize, out.len());
+        let json_len = u32::from_le_bytes(out[12..16].try_into().unwrap()) as usize;
+        assert_eq!(json_len % 4, 0);
+        let value: serde_json::Value = serde_json::from_slice(&out[20..20 + json_len]).unwrap();
+        assert_eq!(value, serde_json::json!({"asset": {"version": "2.0"}}));
+        // The binary chunk is untouched.
+        assert!(out.ends_with(&bin));
+    }
+
+    #[test]
+    fn test_strip_step() {
+        let step = b"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('part.step', '2024-01-01T00:00:00.0+00:00', ('Jane Doe'), ('Acme, Inc.'), 'zoo engine 1.2.3', 'zoo', 'it''s me');
+FILE_SCHEMA(('AP214'));
+ENDSEC;
+";
+        let out = String::from_utf8(strip_metadata("part.step", step).unwrap()).unwrap();
+        assert!(out.contains("FILE_NAME('part.step', '2024-01-01T00:00:00.0+00:00',(''),(''),'','','');"));
+        assert!(!out.contains("Jane Doe"));
+        assert!(!out.contains("Acme"));
+        assert!(!out.contains("zoo engine"));
+        assert!(out.contains("FILE_SCHEMA(('AP214'));"));
+    }
+
+    #[test]
+    fn test_strip_obj() {
+        let obj = b"# zoo engine 1.2.3\nv 0 0 0\n  # another comment\nv 1 0 0\n";
+        assert_eq!(strip_metadata("output.obj", obj).unwrap(), b"v 0 0 0\nv 1 0 0\n");
+    }
+
+    #[test]
+    fn test_strip_ply() {
+        let mut ply = b"ply\nformat binary_little_endian 1.0\ncomment zoo engine 1.2.3\nobj_info by me\nelement vertex 0\nend_header\n".to_vec();
+        ply.extend_from_slice(&[0xff, 0x00]);
+        let out = strip_metadata("output.ply", &ply).unwrap();
+        let mut want = b"ply\nformat binary_little_endian 1.0\nelement vertex 0\nend_header\n".to_vec();
+        want.extend_from_slice(&[0xff, 0x00]);
+        assert_eq!(out, want);
+    }
+
+    #[test]
+    fn test_strip_stl() {
+        let ascii = b"solid zoo engine\nfacet normal 0 0 1\nendfacet\nendsolid zoo engine\n";
+        assert_eq!(
+            strip_metadata("output.stl", ascii).unwrap(),
+            b"solid\nfacet normal 0 0 1\nendfacet\nendsolid\n"
+        );
+
+        let mut binary = vec![b'z'; 80];
+        binary.extend_from_slice(&0u32.to_le_bytes());
+        let out = strip_metadata("output.stl", &binary).unwrap();
+        assert!(out[..80].iter().all(|b| *b == 0));
+        assert_eq!(out.len(), 84);
+    }
+
+    #[test]
+    fn test_strip_unknown_format() {
+        assert_eq!(strip_metadata("output.fbx", b"Kaydara").unwrap(), b"Kaydara");
+    }
+}
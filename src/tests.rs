@@ -243,6 +243,45 @@ access-control-allow-credentials:  """#
             want_code: 1,
             ..Default::default()
         },
+        TestItem {
+            name: "api batch".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "api".to_string(),
+                "batch".to_string(),
+                "-".to_string(),
+            ],
+            stdin: Some(r#"[{"key": "me", "path": "/user"}, {"method": "GET", "path": "foo/bar"}]"#.to_string()),
+            want_out: r#""me": {"#.to_string(),
+            want_err: "".to_string(),
+            want_code: 1,
+        },
+        TestItem {
+            name: "api batch with duplicate keys".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "api".to_string(),
+                "batch".to_string(),
+                "-".to_string(),
+            ],
+            stdin: Some(r#"[{"key": "me", "path": "/user"}, {"key": "me", "path": "/user"}]"#.to_string()),
+            want_out: "".to_string(),
+            want_err: "duplicate key `me` in batch of requests".to_string(),
+            want_code: 1,
+        },
+        TestItem {
+            name: "api ws with a message that is not json".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "api".to_string(),
+                "ws".to_string(),
+                "/ws/modeling/commands".to_string(),
+            ],
+            stdin: Some("{\"type\": \"ping\"}\nping\n".to_string()),
+            want_out: "".to_string(),
+            want_err: "line 2 of the input is not valid JSON".to_string(),
+            want_code: 1,
+        },
         TestItem {
             name: "get your user".to_string(),
             args: vec!["zoo".to_string(), "user".to_string(), "view".to_string()],
@@ -410,6 +449,21 @@ access-control-allow-credentials:  """#
             want_code: 0,
             ..Default::default()
         },
+        TestItem {
+            name: "snapshot a kcl file to stdout".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "snapshot".to_string(),
+                "tests/gear.kcl".to_string(),
+                "-".to_string(),
+            ],
+            // The png is not valid utf-8, so there is no text output to compare.
+            want_out: "".to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
         TestItem {
             name: "get the mass of a kcl file".to_string(),
             args: vec![
@@ -523,6 +577,22 @@ access-control-allow-credentials:  """#
             want_code: 0,
             ..Default::default()
         },
+        TestItem {
+            name: "get the bounding box of a kcl file".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "bbox".to_string(),
+                "tests/gear.kcl".to_string(),
+                "--src-unit=mm".to_string(),
+                "--output-unit".to_string(),
+                "mm".to_string(),
+            ],
+            want_out: r#"output_unit | mm"#.to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
         TestItem {
             name: "get the center-of-mass of a kcl file".to_string(),
             args: vec![
@@ -572,6 +642,39 @@ access-control-allow-credentials:  """#
             want_code: 0,
             ..Default::default()
         },
+        TestItem {
+            name: "export a kcl file as gltf into a zip".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "export".to_string(),
+                "--output-format=gltf".to_string(),
+                "--src-unit=mm".to_string(),
+                "--zip=tests/gear-gltf.zip".to_string(),
+                "tests/gear.kcl".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "export a kcl file as step with a thumbnail".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "export".to_string(),
+                "--output-format=step".to_string(),
+                "--src-unit=mm".to_string(),
+                "--thumbnail=tests/gear-thumbnail.png".to_string(),
+                "tests/gear.kcl".to_string(),
+                "tests/".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
         TestItem {
             name: "export a kcl file with a parse error".to_string(),
             args: vec![
@@ -588,6 +691,35 @@ access-control-allow-credentials:  """#
             want_code: 1,
             ..Default::default()
         },
+        TestItem {
+            name: "turntable with an fps of 0".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "turntable".to_string(),
+                "--fps=0".to_string(),
+                "tests/gear.kcl".to_string(),
+                "tests/gear.gif".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err: "--fps must be between 1 and 100".to_string(),
+            want_code: 1,
+            ..Default::default()
+        },
+        TestItem {
+            name: "warm a kcl file with a parse error".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "warm".to_string(),
+                "--src-unit=mm".to_string(),
+                "tests/parse_error.kcl".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err: "syntax: Unexpected token".to_string(),
+            want_code: 1,
+            ..Default::default()
+        },
         TestItem {
             name: "format a kcl file".to_string(),
             args: vec![
@@ -601,6 +733,91 @@ access-control-allow-credentials:  """#
             want_code: 0,
             ..Default::default()
         },
+        TestItem {
+            name: "format a directory of kcl files".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "fmt".to_string(),
+                "tests/zooignore".to_string(),
+            ],
+            want_out: "// === tests/zooignore/main.kcl ===\nwidth = 10\n".to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "check the formatting of a directory of kcl files".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "fmt".to_string(),
+                "--check".to_string(),
+                "tests/nested-settings".to_string(),
+            ],
+            want_out: "tests/nested-settings/subdir/gear.kcl\n".to_string(),
+            want_err: "1 file is not formatted, run `zoo kcl fmt -w` to format it".to_string(),
+            want_code: 1,
+            ..Default::default()
+        },
+        TestItem {
+            name: "format a kcl file with CRLF line endings".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "fmt".to_string(),
+                "tests/crlf.kcl".to_string(),
+            ],
+            want_out: "thickness = 2\nwidth = 10\n".to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "get the parameters of a kcl file".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "meta".to_string(),
+                "tests/gear.kcl".to_string(),
+                "--format".to_string(),
+                "json".to_string(),
+            ],
+            want_out: r#""name": "nTeeth""#.to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "run a kcl file with an invalid command".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "run".to_string(),
+                "tests/gear.kcl".to_string(),
+                "--cmds".to_string(),
+                "tests/bad_cmds.jsonl".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err: "line 2 of `tests/bad_cmds.jsonl` is not a valid modeling command".to_string(),
+            want_code: 1,
+            ..Default::default()
+        },
+        TestItem {
+            name: "format a binary file".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "fmt".to_string(),
+                "tests/binary.kcl".to_string(),
+            ],
+            want_out: r#""#.to_string(),
+            want_err:
+                "`tests/binary.kcl` does not look like KCL text, it looks like a glb file. Did you mean `zoo file`"
+                    .to_string(),
+            want_code: 1,
+            ..Default::default()
+        },
         TestItem {
             name: "lint some kcl".to_string(),
             args: vec![
@@ -614,6 +831,49 @@ access-control-allow-credentials:  """#
             want_code: 0,
             ..Default::default()
         },
+        TestItem {
+            name: "lint a kcl file with a finding".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "lint".to_string(),
+                "--format=json".to_string(),
+                "tests/zooignore/drafts/wide.kcl".to_string(),
+            ],
+            want_out: r#""code": "Z0001""#.to_string(),
+            want_err: "1 finding: Z0001=1".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "lint a kcl file with a finding, without the summary".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "lint".to_string(),
+                "--format=json".to_string(),
+                "--quiet".to_string(),
+                "tests/zooignore/drafts/wide.kcl".to_string(),
+            ],
+            want_out: r#""code": "Z0001""#.to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
+        TestItem {
+            name: "lint a directory, skipping the paths in .zooignore".to_string(),
+            args: vec![
+                "zoo".to_string(),
+                "kcl".to_string(),
+                "lint".to_string(),
+                "--format=json".to_string(),
+                "tests/zooignore".to_string(),
+            ],
+            want_out: "[]".to_string(),
+            want_err: "".to_string(),
+            want_code: 0,
+            ..Default::default()
+        },
         TestItem {
             name: "snapshot a gltf with embedded buffer".to_string(),
             args: vec![
@@ -727,11 +987,7 @@ access-control-allow-credentials:  """#
         if let Some(stdin) = t.stdin {
             io.stdin = Box::new(std::io::Cursor::new(stdin));
         }
-        let mut ctx = crate::context::Context {
-            config: &mut c,
-            io,
-            debug: false,
-        };
+        let mut ctx = crate::context::Context::test(&mut c, io);
 
         let result = crate::do_main(t.args, &mut ctx).await;
 
@@ -790,3 +1046,59 @@ access-control-allow-credentials:  """#
         }
     }
 }
+
+#[test_context(MainContext)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+#[serial_test::serial]
+async fn test_api_raw(ctx: &mut MainContext) {
+    // Get the exact bytes the server sends for a known endpoint.
+    let req = ctx
+        .client
+        .request_raw(http::method::Method::GET, "/ping", None)
+        .await
+        .unwrap();
+    let want = req.0.send().await.unwrap().bytes().await.unwrap();
+
+    let output_file = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+
+    let mut config = crate::config::new_blank_config().unwrap();
+    let mut c = crate::config_from_env::EnvConfig::inherit_env(&mut config);
+
+    let (mut io, _, _) = crate::iostreams::IoStreams::test();
+    io.stdin = Box::new(std::io::Cursor::new(ctx.test_token.clone()));
+    let mut login_ctx = crate::context::Context::test(&mut c, io);
+    let login = vec![
+        "zoo".to_string(),
+        "auth".to_string(),
+        "login".to_string(),
+        "--host".to_string(),
+        ctx.test_host.clone(),
+        "--with-token".to_string(),
+    ];
+    assert_eq!(crate::do_main(login, &mut login_ctx).await.unwrap(), 0);
+
+    for output in [None, Some(output_file.to_str().unwrap().to_string())] {
+        let (mut io, stdout_path, stderr_path) = crate::iostreams::IoStreams::test();
+        io.set_stdout_tty(false);
+        io.set_color_enabled(false);
+        let mut ctx = crate::context::Context::test(&mut c, io);
+
+        let mut args = vec![
+            "zoo".to_string(),
+            "api".to_string(),
+            "ping".to_string(),
+            "--raw".to_string(),
+        ];
+        if let Some(output) = &output {
+            args.push(format!("--output={output}"));
+        }
+
+        let result = crate::do_main(args, &mut ctx).await;
+        let stderr = std::fs::read_to_string(stderr_path).unwrap_or_default();
+        assert_eq!(result.unwrap(), 0, "stderr: {stderr}");
+
+        let got = std::fs::read(output.unwrap_or(stdout_path)).unwrap();
+        assert_eq!(got.len(), want.len());
+        assert_eq!(got, want.to_vec());
+    }
+}
@@ -0,0 +1,282 @@
+//! Parsing for the unit flags, like `--src-unit` and `--output-unit`.
+//!
+//! Besides the names clap knows the units by (`mm`, `cm3`, ...), this accepts
+//! the ways people tend to spell them out, like `millimeters`, `sq ft` or `kg/m3`, and
+//! suggests the closest unit when there is a typo.
+
+use anyhow::Result;
+
+/// Spelled out names for units, and the name clap knows them by.
+const ALIASES: &[(&str, &str)] = &[
+    ("millimeter", "mm"),
+    ("millimeters", "mm"),
+    ("millimetre", "mm"),
+    ("millimetres", "mm"),
+    ("centimeter", "cm"),
+    ("centimeters", "cm"),
+    ("centimetre", "cm"),
+    ("centimetres", "cm"),
+    ("decimeter", "dm"),
+    ("decimeters", "dm"),
+    ("decimetre", "dm"),
+    ("decimetres", "dm"),
+    ("meter", "m"),
+    ("meters", "m"),
+    ("metre", "m"),
+    ("metres", "m"),
+    ("kilometer", "km"),
+    ("kilometers", "km"),
+    ("kilometre", "km"),
+    ("kilometres", "km"),
+    ("inch", "in"),
+    ("inches", "in"),
+    ("foot", "ft"),
+    ("feet", "ft"),
+    ("yard", "yd"),
+    ("yards", "yd"),
+    ("gram", "g"),
+    ("grams", "g"),
+    ("kilogram", "kg"),
+    ("kilograms", "kg"),
+    ("pound", "lb"),
+    ("pounds", "lb"),
+    ("lbs", "lb"),
+    ("milliliter", "ml"),
+    ("milliliters", "ml"),
+    ("millilitre", "ml"),
+    ("millilitres", "ml"),
+    ("liter", "l"),
+    ("liters", "l"),
+    ("litre", "l"),
+    ("litres", "l"),
+    ("gal", "usgal"),
+    ("gallon", "usgal"),
+    ("gallons", "usgal"),
+    ("floz", "usfloz"),
+];
+
+/// A clap value parser for a unit, see the module docs.
+/// The possible values are the same as with `value_enum`, so help and shell completions
+/// still list them.
+#[derive(Clone)]
+pub struct UnitParser<T>(std::marker::PhantomData<T>);
+
+impl<T> UnitParser<T> {
+    pub fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<T> Default for UnitParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: clap::ValueEnum + Clone + Send + Sync + 'static> clap::builder::TypedValueParser for UnitParser<T> {
+    type Value = T;
+
+    fn parse_ref(
+        &self,
+        _cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value = value
+            .to_str()
+            .ok_or_else(|| clap::Error::new(clap::error::ErrorKind::InvalidUtf8))?;
+        parse_unit(value).map_err(|err| {
+            let arg = arg.map(|arg| arg.to_string()).unwrap_or_else(|| "...".to_string());
+            clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                format!("invalid value '{}' for '{}': {}\n", value, arg, err),
+            )
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            T::value_variants()
+                .iter()
+                .filter_map(|variant| variant.to_possible_value()),
+        ))
+    }
+}
+
+/// Parse a unit, accepting the spelled out names in [`ALIASES`].
+pub fn parse_unit<T: clap::ValueEnum>(value: &str) -> Result<T> {
+    let normalized = normalize(value);
+    // Compound units, like densities, may be named with either separator.
+    for candidate in [normalized.clone(), normalized.replace(':', "-")] {
+        if let Ok(unit) = T::from_str(&candidate, true) {
+            return Ok(unit);
+        }
+    }
+
+    let names: Vec<String> = T::value_variants()
+        .iter()
+        .filter_map(|variant| variant.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    match suggest(&normalized, &names) {
+        Some(name) => anyhow::bail!("did you mean `{}`? valid units are: {}", name, names.join(", ")),
+        None => anyhow::bail!("valid units are: {}", names.join(", ")),
+    }
+}
+
+/// Turn the ways a unit might be written into the form clap knows, for example
+/// `Square Feet` into `ft2` and `kilograms per cubic meter` into `kg:m3`.
+fn normalize(value: &str) -> String {
+    let value = value.trim().to_lowercase().replace(" per ", "/");
+    value
+        .split(['/', ':'])
+        .map(normalize_part)
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn normalize_part(part: &str) -> String {
+    let mut part: String = part
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_' && *c != '^')
+        .map(|c| match c {
+            '²' => '2',
+            '³' => '3',
+            c => c,
+        })
+        .collect();
+
+    let mut power = "";
+    for (prefix, p) in [("square", "2"), ("sq", "2"), ("cubic", "3"), ("cu", "3")] {
+        if let Some(rest) = part.strip_prefix(prefix) {
+            power = p;
+            part = rest.to_string();
+            break;
+        }
+    }
+    if power.is_empty() {
+        if let Some(rest) = part.strip_suffix('2') {
+            power = "2";
+            part = rest.to_string();
+        } else if let Some(rest) = part.strip_suffix('3') {
+            power = "3";
+            part = rest.to_string();
+        }
+    }
+
+    let base = ALIASES
+        .iter()
+        .find(|(word, _)| *word == part)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(part);
+    format!("{}{}", base, power)
+}
+
+/// The unit name closest to a normalized value, comparing against the spelled out names
+/// too, if it is close enough to be a typo.
+fn suggest(normalized: &str, names: &[String]) -> Option<String> {
+    let mut best: Option<(usize, &String)> = None;
+    for name in names {
+        for spelling in spellings(name) {
+            let distance = levenshtein(normalized, &spelling);
+            // Allow about one typo for every three letters.
+            if distance > (spelling.chars().count() / 3).max(1) {
+                continue;
+            }
+            if best.map(|(best, _)| distance < best).unwrap_or(true) {
+                best = Some((distance, name));
+            }
+        }
+    }
+    best.map(|(_, name)| name.clone())
+}
+
+/// The ways a unit name can be spelled out, after normalizing, including the name itself.
+/// `mm2` is also `millimeters2`, `millimetre2`, and so on.
+fn spellings(name: &str) -> Vec<String> {
+    let mut spellings = vec![String::new()];
+    for (i, part) in name.split([':', '-']).enumerate() {
+        let base = part.trim_end_matches(['2', '3']);
+        let power = &part[base.len()..];
+        let words = std::iter::once(base).chain(
+            ALIASES
+                .iter()
+                .filter(|(_, alias)| *alias == base)
+                .map(|(word, _)| *word),
+        );
+
+        // Normalizing always joins the parts with `:`.
+        let separator = if i == 0 { "" } else { ":" };
+        spellings = spellings
+            .iter()
+            .flat_map(|prefix| {
+                words
+                    .clone()
+                    .map(move |word| format!("{}{}{}{}", prefix, separator, word, power))
+            })
+            .collect();
+    }
+    spellings
+}
+
+/// The number of single character edits to get from one string to another.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use kittycad::types::{UnitArea, UnitDensity, UnitLength, UnitMass};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_unit_aliases() {
+        assert_eq!(parse_unit::<UnitLength>("mm").unwrap(), UnitLength::Mm);
+        assert_eq!(parse_unit::<UnitLength>("MM").unwrap(), UnitLength::Mm);
+        assert_eq!(parse_unit::<UnitLength>("millimeters").unwrap(), UnitLength::Mm);
+        assert_eq!(parse_unit::<UnitLength>("Inches").unwrap(), UnitLength::In);
+        assert_eq!(parse_unit::<UnitLength>("feet").unwrap(), UnitLength::Ft);
+        assert_eq!(parse_unit::<UnitArea>("sq ft").unwrap(), UnitArea::Ft2);
+        assert_eq!(parse_unit::<UnitArea>("m²").unwrap(), UnitArea::M2);
+        assert_eq!(parse_unit::<UnitMass>("kilograms").unwrap(), UnitMass::Kg);
+        assert_eq!(parse_unit::<UnitDensity>("kg/m3").unwrap(), UnitDensity::KgM3);
+        assert_eq!(
+            parse_unit::<UnitDensity>("pounds per cubic foot").unwrap(),
+            UnitDensity::LbFt3
+        );
+    }
+
+    #[test]
+    fn test_parse_unit_suggestion() {
+        let err = parse_unit::<UnitLength>("milimeters").unwrap_err();
+        assert!(err.to_string().starts_with("did you mean `mm`?"), "{err}");
+        assert!(err.to_string().contains("valid units are: "), "{err}");
+
+        let err = parse_unit::<UnitArea>("square centimetrs").unwrap_err();
+        assert!(err.to_string().starts_with("did you mean `cm2`?"), "{err}");
+
+        // Nothing is close to this, so there is no suggestion, only the list.
+        let err = parse_unit::<UnitLength>("parsecs").unwrap_err();
+        assert!(err.to_string().starts_with("valid units are: "), "{err}");
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("mm", "mm"), 0);
+        assert_eq!(levenshtein("milimeters", "millimeters"), 1);
+        assert_eq!(levenshtein("ft", "yd"), 2);
+    }
+}
@@ -244,6 +244,38 @@ pub async fn download_binary_to_temp_file(version: &str) -> Result<String> {
     Ok(temp_file_path.to_string())
 }
 
+/// Save a copy of the binary and its version before it is replaced, so that
+/// `zoo update --rollback` can go back to it.
+pub fn save_previous_binary(binary_path: &std::path::Path, version: &str) -> Result<()> {
+    let dir = std::path::PathBuf::from(crate::config_file::previous_binary_dir()?);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create directory {}", dir.display()))?;
+
+    fs::copy(binary_path, dir.join(previous_binary_name()))
+        .with_context(|| format!("failed to save a copy of {}", binary_path.display()))?;
+    fs::write(dir.join("version"), version)?;
+
+    Ok(())
+}
+
+/// The file name the previous binary is saved as, with the platform's executable
+/// extension so it can still be run on Windows.
+fn previous_binary_name() -> String {
+    format!("zoo{}", std::env::consts::EXE_SUFFIX)
+}
+
+/// Get the path and version of the binary saved before the last update, if there is one.
+pub fn get_previous_binary() -> Result<Option<(std::path::PathBuf, String)>> {
+    let dir = std::path::PathBuf::from(crate::config_file::previous_binary_dir()?);
+    let binary = dir.join(previous_binary_name());
+    let version = dir.join("version");
+
+    if !binary.is_file() || !version.is_file() {
+        return Ok(None);
+    }
+
+    Ok(Some((binary, fs::read_to_string(version)?.trim().to_string())))
+}
+
 /// Calculates the SHA256 hash of a reader.
 fn sha256_digest<R: std::io::Read>(mut reader: R) -> Result<String> {
     let mut context = ring::digest::Context::new(&ring::digest::SHA256);